@@ -4,14 +4,13 @@ use ark_bls12_381::Bls12_381;
 use ark_bw6_761::BW6_761;
 use ark_ec::{AffineRepr, CurveGroup, Group};
 use ark_ff::Field;
-use ark_groth16::Groth16;
-use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
 use ark_std::{rand, test_rng, UniformRand};
 use sha2::Digest;
 use tracing::{info_span, info, Level};
 use tracing_subscriber::fmt::{format, init};
 use tracing_subscriber::fmt::format::FmtSpan;
-use zk_tlock::{Circuit, GeminiNativeCircuit, NonnativeCircuit, Parameters};
+use zk_tlock::{bn254, halo2, Circuit, GeminiNativeCircuit, NonnativeCircuit, Parameters};
+use zk_tlock::prover::{Gemini, Groth16Native, Groth16Nonnative, Halo2, TimelockProver};
 use zk_tlock::utils::ZkCryptoDeserialize;
 use ark_std::rand::Rng;
 use tracing_subscriber::layer::SubscriberExt;
@@ -19,6 +18,7 @@ use tracing_subscriber::util::SubscriberInitExt;
 
 fn test_groth16_native_bls12_377() {
     type TestCircuit = Circuit::<Bls12_377, ark_bls12_377::Parameters>;
+    type Prover = Groth16Native::<Bls12_377, ark_bls12_377::Parameters, BW6_761>;
     let mut rng = rand::thread_rng();
     let bytes = [1, 2, 3];
     let msg = ark_bls12_377::Fq::from_random_bytes(&bytes).unwrap();
@@ -46,29 +46,26 @@ fn test_groth16_native_bls12_377() {
         &hash.finalize().to_vec()[0..32]
     };
 
+    let (pk, vk) = info_span!("groth16::setup").in_scope(||
+        Prover::setup(TestCircuit::blank(), &mut rng)
+    ).unwrap();
+
     let circuit = info_span!("encrypt-message").in_scope(|| {
-        Circuit::<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters>::new(
+        TestCircuit::new(
             master.clone(),
             &id,
             msg.clone().into(),
             &mut rng)
     }).unwrap();
-
-    let (pk, vk) = info_span!("groth16::setup").in_scope(||
-        Groth16::<BW6_761>::setup(circuit, &mut rng)
-    ).unwrap();
-
-    let circuit = TestCircuit::new(master, id, msg.clone().into(), &mut rng).unwrap();
-    let ct = circuit.ciphertext.clone();
-
-    let public_input = TestCircuit::get_public_inputs(&circuit.gid, &ct);
+    let ct = circuit.ciphertext.clone().unwrap();
+    let gid = circuit.gid.clone().unwrap();
 
     let proof = info_span!("groth16::prove").in_scope(||
-        Groth16::prove(&pk, circuit, &mut rng)
+        Prover::prove(&pk, circuit, &mut rng)
     ).unwrap();
 
     let verified = info_span!("groth16::verify").in_scope(||
-        Groth16::verify(&vk, &public_input, &proof)
+        Prover::verify(&vk, &gid, &ct, &[], &proof)
     ).unwrap();
 
     assert!(verified);
@@ -82,6 +79,7 @@ fn test_groth16_native_bls12_377() {
 
 fn test_groth16_nonnative_bls12_381() {
     type TestCircuit = NonnativeCircuit::<ark_bls12_377::G1Projective>;
+    type Prover = Groth16Nonnative::<BW6_761, ark_bls12_377::G1Projective>;
 
     let mut rng = rand::thread_rng();
     let bytes = [1, 2, 3];
@@ -98,6 +96,10 @@ fn test_groth16_nonnative_bls12_381() {
         &hash.finalize().to_vec()[0..32]
     };
 
+    let (pk, vk) = info_span!("groth16::setup").in_scope(||
+        Prover::setup(TestCircuit::blank(), &mut rng)
+    ).unwrap();
+
     let circuit = info_span!("encrypt-message").in_scope(|| {
         TestCircuit::new(
             master.clone(),
@@ -105,30 +107,73 @@ fn test_groth16_nonnative_bls12_381() {
             msg.clone().into(),
             &mut rng)
     }).unwrap();
+    let ct = circuit.ciphertext.clone().unwrap();
+    let gid = circuit.gid.clone().unwrap();
+
+    let proof = info_span!("groth16::prove").in_scope(||
+        Prover::prove(&pk, circuit, &mut rng)
+    ).unwrap();
+
+    let priv_key = {
+        let bytes = hex::decode("a4721e6c3eafcd823f138cd29c6c82e8c5149101d0bb4bafddbac1c2d1fe3738895e4e21dd4b8b41bf007046440220910bb1cdb91f50a84a0d7f33ff2e8577aa62ac64b35a291a728a9db5ac91e06d1312b48a376138d77b4d6ad27c24221afe").unwrap();
+        ark_bls12_381::G2Affine::deserialize_zk_crypto(&bytes).unwrap()
+    };
+
+    let verified = info_span!("groth16::verify").in_scope(||
+        Prover::verify(&vk, &gid, &ct, &[], &proof)
+    ).unwrap();
+
+    assert!(verified);
+
+    let pt = info_span!("decrypt-message").in_scope(||
+        TestCircuit::decrypt(&priv_key, &ct)
+    ).unwrap();
+
+    assert_eq!(msg, pt)
+}
+
+fn test_groth16_nonnative_bn254() {
+    type TestCircuit = bn254::Bn254Circuit;
+
+    let mut rng = rand::thread_rng();
+    let bytes = [1, 2, 3];
+    let msg = ark_bls12_381::Fq::from_random_bytes(&bytes).unwrap();
 
-    let (pk, _vk) = info_span!("groth16::setup").in_scope(||
-        Groth16::<BW6_761>::setup(circuit, &mut rng)
+    let master: _ = {
+        let bytes = hex::decode("8200fc249deb0148eb918d6e213980c5d01acd7fc251900d9260136da3b54836ce125172399ddc69c4e3e11429b62c11").unwrap();
+        ark_bls12_381::G1Affine::deserialize_zk_crypto(&bytes).unwrap()
+    };
+    let round_number = 1000u64;
+    let id = {
+        let mut hash = sha2::Sha256::new();
+        hash.update(&round_number.to_be_bytes());
+        hash.finalize().to_vec()[0..32].to_vec()
+    };
+
+    let (pk, vk) = info_span!("groth16::setup").in_scope(||
+        bn254::setup(&mut rng)
     ).unwrap();
 
-    let circuit = TestCircuit::new(master, id, msg.clone().into(), &mut rng).unwrap();
-    let ct = circuit.ciphertext.clone();
-    // let public_input = TestCircuit::get_public_inputs(&circuit.gid, &ct);
+    let circuit = info_span!("encrypt-message").in_scope(|| {
+        TestCircuit::new(master.clone(), &id, msg.clone().into(), &mut rng)
+    }).unwrap();
+    let ct = circuit.ciphertext.clone().unwrap();
+
+    let proof = info_span!("groth16::prove").in_scope(||
+        bn254::prove(&pk, circuit, &mut rng)
+    ).unwrap();
 
-    let _proof = info_span!("groth16::prove").in_scope(||
-        Groth16::prove(&pk, circuit, &mut rng)
+    let verified = info_span!("groth16::verify").in_scope(||
+        bn254::verify(&vk, &master, &id, &ct, &proof)
     ).unwrap();
 
+    assert!(verified);
+
     let priv_key = {
         let bytes = hex::decode("a4721e6c3eafcd823f138cd29c6c82e8c5149101d0bb4bafddbac1c2d1fe3738895e4e21dd4b8b41bf007046440220910bb1cdb91f50a84a0d7f33ff2e8577aa62ac64b35a291a728a9db5ac91e06d1312b48a376138d77b4d6ad27c24221afe").unwrap();
         ark_bls12_381::G2Affine::deserialize_zk_crypto(&bytes).unwrap()
     };
 
-    // let verified = info_span!("groth16::verify").in_scope(||
-    //     Groth16::verify(&vk, &public_input, &proof)
-    // ).unwrap();
-
-    // assert!(verified);
-
     let pt = info_span!("decrypt-message").in_scope(||
         TestCircuit::decrypt(&priv_key, &ct)
     ).unwrap();
@@ -162,28 +207,22 @@ fn test_gemini_native_yata_127() {
             &mut rng).unwrap())
     });
 
-    let ct = circuit.0.ciphertext.clone();
+    let ct = circuit.0.ciphertext.clone().unwrap();
+    let gid = circuit.0.gid.clone().unwrap();
 
-    let r1cs = info_span!("gemini::generate_relation").in_scope(||
-        ark_gemini::circuit::generate_relation::<ark_bls12_381::Fq, GeminiNativeCircuit>(circuit)
-    );
-
-    info!("r1cs size: {}", r1cs.a.len());
+    let (ck, vk) = info_span!("gemini::setup").in_scope(||
+        Gemini::setup(GeminiNativeCircuit::blank(), &mut rng)
+    ).unwrap();
 
-    let num_constraints = 68000;
-    let num_variables = 100;
-    let num_non_zero = 68000;
+    let proof = info_span!("gemini::prove").in_scope(||
+        Gemini::prove(&ck, circuit, &mut rng)
+    ).unwrap();
 
-    let ck = info_span!("gemini::setup").in_scope(||
-        ark_gemini::kzg::CommitterKey::<zk_tlock::yt6_776::YT6_776>::new(
-        num_non_zero + num_variables + num_constraints,
-        5,
-        &mut rng,
-    ));
+    let verified = info_span!("gemini::verify").in_scope(||
+        Gemini::verify(&vk, &gid, &ct, &[], &proof)
+    ).unwrap();
 
-    let _proof = info_span!("gemini::prove").in_scope(||
-        ark_gemini::psnark::Proof::new_time(&r1cs, &ck)
-    );
+    assert!(verified);
 
     let priv_key = {
         let bytes = hex::decode("a4721e6c3eafcd823f138cd29c6c82e8c5149101d0bb4bafddbac1c2d1fe3738895e4e21dd4b8b41bf007046440220910bb1cdb91f50a84a0d7f33ff2e8577aa62ac64b35a291a728a9db5ac91e06d1312b48a376138d77b4d6ad27c24221afe").unwrap();
@@ -197,6 +236,61 @@ fn test_gemini_native_yata_127() {
     assert_eq!(msg, pt)
 }
 
+fn test_halo2_bls12_381() {
+    let mut rng = rand::thread_rng();
+    let bytes = [1, 2, 3];
+    let msg = ark_bls12_381::Fq::from_random_bytes(&bytes).unwrap();
+
+    let master: _ = {
+        let bytes = hex::decode("8200fc249deb0148eb918d6e213980c5d01acd7fc251900d9260136da3b54836ce125172399ddc69c4e3e11429b62c11").unwrap();
+        ark_bls12_381::G1Affine::deserialize_zk_crypto(&bytes).unwrap()
+    };
+    let round_number = 1000u64;
+    let id = {
+        let mut hash = sha2::Sha256::new();
+        hash.update(&round_number.to_be_bytes());
+        &hash.finalize().to_vec()[0..32]
+    };
+
+    let circuit = info_span!("encrypt-message").in_scope(|| {
+        halo2::TimelockCircuit::new(
+            master.clone(),
+            &id,
+            msg.clone().into(),
+            &mut rng).unwrap()
+    });
+
+    let ct = circuit.ciphertext.clone().unwrap();
+    let gid = circuit.gid.clone().unwrap();
+
+    let (pk, vk) = info_span!("halo2::setup").in_scope(||
+        Halo2::setup(halo2::TimelockCircuit::blank(), &mut rng)
+    ).unwrap();
+
+    let proof = info_span!("halo2::prove").in_scope(||
+        Halo2::prove(&pk, circuit, &mut rng)
+    ).unwrap();
+
+    info!("proof size: {} bytes", proof.len());
+
+    let verified = info_span!("halo2::verify").in_scope(||
+        Halo2::verify(&vk, &gid, &ct, &[], &proof)
+    ).unwrap();
+
+    assert!(verified);
+
+    let priv_key = {
+        let bytes = hex::decode("a4721e6c3eafcd823f138cd29c6c82e8c5149101d0bb4bafddbac1c2d1fe3738895e4e21dd4b8b41bf007046440220910bb1cdb91f50a84a0d7f33ff2e8577aa62ac64b35a291a728a9db5ac91e06d1312b48a376138d77b4d6ad27c24221afe").unwrap();
+        ark_bls12_381::G2Affine::deserialize_zk_crypto(&bytes).unwrap()
+    };
+
+    let pt = info_span!("decrypt-message").in_scope(||
+        halo2::TimelockCircuit::decrypt(&priv_key, &ct)
+    ).unwrap();
+
+    assert_eq!(msg, pt)
+}
+
 fn setup_tracing() {
     let filter = tracing_subscriber::filter::Targets::new()
         .with_target("ibe_benchmark", Level::INFO);
@@ -219,6 +313,12 @@ fn main() {
     println!("Groth16 (nonnative) on BLS12-381");
     test_groth16_nonnative_bls12_381();
 
+    println!("Groth16 (nonnative) on BN254");
+    test_groth16_nonnative_bn254();
+
     println!("Gemini (native) on Yata-127");
     test_gemini_native_yata_127();
+
+    println!("Halo2 (nonnative) on BLS12-381 with IPA over Pallas");
+    test_halo2_bls12_381();
 }
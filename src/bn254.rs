@@ -0,0 +1,130 @@
+//! Groth16 over BN254 for the non-native circuit, so that a timelock ciphertext
+//! can be checked by a contract using the EVM `ecPairing` precompile.
+//!
+//! The constraint field is BN254 `Fr`, which is the base field of Baby Jubjub,
+//! hence the circuit is instantiated with `ark_ed_on_bn254::EdwardsProjective`.
+
+use ark_bn254::Bn254;
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_std::rand::{CryptoRng, Rng};
+use crate::prover::{Groth16Nonnative, TimelockProver};
+use crate::{Ciphertext, NonnativeCircuit, PublicKey};
+
+/// Non-native circuit whose constraint field is BN254 `Fr`.
+pub type Bn254Circuit = NonnativeCircuit<ark_ed_on_bn254::EdwardsProjective>;
+
+/// Groth16 over BN254 for [`Bn254Circuit`].
+pub type Bn254Prover = Groth16Nonnative<Bn254, ark_ed_on_bn254::EdwardsProjective>;
+
+/// Runs the circuit-specific setup of [`Bn254Circuit`] without plaintext predicates.
+pub fn setup<R: Rng + CryptoRng>(rng: &mut R) -> anyhow::Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>)> {
+    Bn254Prover::setup(Bn254Circuit::blank(), rng)
+}
+
+/// Proves that `circuit.ciphertext` is a valid encryption.
+pub fn prove<R: Rng + CryptoRng>(
+    pk: &ProvingKey<Bn254>,
+    circuit: Bn254Circuit,
+    rng: &mut R,
+) -> anyhow::Result<Proof<Bn254>> {
+    Bn254Prover::prove(pk, circuit, rng)
+}
+
+/// Verifies that `ct` is a valid encryption to round `id` of the network with `master` public key.
+pub fn verify<I: AsRef<[u8]>>(
+    vk: &VerifyingKey<Bn254>,
+    master: &PublicKey<ark_bls12_381::Bls12_381>,
+    id: I,
+    ct: &Ciphertext<ark_bls12_381::G1Projective>,
+    proof: &Proof<Bn254>,
+) -> anyhow::Result<bool> {
+    Bn254Prover::verify(vk, &Bn254Circuit::gid(master, id)?, ct, &[], proof)
+}
+
+/// Packs `gid` and the ciphertext into BN254 `Fr` public inputs, in the order expected by [`verify`].
+pub fn public_inputs(
+    gid: &ark_bls12_381::Fq12,
+    ct: &Ciphertext<ark_bls12_381::G1Projective>,
+) -> Vec<ark_bn254::Fr> {
+    Bn254Circuit::get_public_inputs(gid, ct)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::{CurveGroup, Group};
+    use ark_ff::One;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::{test_rng, UniformRand};
+    use crate::{Circuit, Plaintext, H2C_DST};
+    use crate::utils::Hash2Curve;
+    use super::*;
+
+    /// Returns a random master key of the network and a random plaintext.
+    fn random_master_and_msg<R: Rng>(rng: &mut R) -> (PublicKey<ark_bls12_381::Bls12_381>, Plaintext<ark_bls12_381::G1Projective>) {
+        let master = (ark_bls12_381::G1Projective::generator() * ark_bls12_381::Fr::rand(rng)).into_affine();
+        (master, ark_bls12_381::Fq::rand(rng))
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mut rng = test_rng();
+        let sk = ark_bls12_381::Fr::rand(&mut rng);
+        let master = (ark_bls12_381::G1Projective::generator() * sk).into_affine();
+        let id = b"round-1000";
+        let msg = ark_bls12_381::Fq::rand(&mut rng);
+
+        let circuit = Bn254Circuit::new(master, id, msg, &mut rng).unwrap();
+        let gid = Bn254Circuit::gid(&master, id).unwrap();
+        assert_eq!(circuit.gid, Some(gid));
+
+        // The drand private key for round `id` is `sk * Q_id`.
+        let qid: ark_bls12_381::G2Affine = ark_bls12_381::Bls12_381::hash(id, H2C_DST).unwrap();
+        let round_key = (qid * sk).into_affine();
+        let ct = circuit.ciphertext.unwrap();
+        let pt = Bn254Circuit::decrypt(&round_key, &ct).unwrap();
+        assert_eq!(pt, msg);
+
+        // Ciphertexts are those of the native circuit over BLS12-381.
+        let pt = Circuit::<ark_bls12_381::Bls12_381, ark_bls12_381::Parameters>::decrypt(&round_key, &ct).unwrap();
+        assert_eq!(pt, msg);
+
+        // 12 coefficients of gid, then U.x, U.y, V, W, packed into 253-bit chunks.
+        assert_eq!(public_inputs(&gid, &ct).len(), (16 * 381 + 252) / 253);
+    }
+
+    #[test]
+    fn test_circuit_satisfied() {
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        let circuit = Bn254Circuit::new(master, b"round-1000", msg, &mut rng).unwrap();
+        let gid = circuit.gid.unwrap();
+        let ct = circuit.ciphertext.clone().unwrap();
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], public_inputs(&gid, &ct));
+    }
+
+    #[test]
+    fn test_tampered_ciphertext() {
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        // Circuits are built from the same randomness, hence with the sigma of `ct`.
+        let new_circuit = || Bn254Circuit::new(master, b"round-1000", msg, &mut rng.clone()).unwrap();
+        let ct = new_circuit().ciphertext.unwrap();
+        let tampered = [
+            Ciphertext { u: ct.u + ark_bls12_381::G1Projective::generator(), ..ct.clone() },
+            Ciphertext { v: ct.v + ark_bls12_381::Fq::one(), ..ct.clone() },
+            Ciphertext { w: ct.w + ark_bls12_381::Fq::one(), ..ct.clone() },
+        ];
+        for tampered in tampered {
+            let mut circuit = new_circuit();
+            circuit.ciphertext = Some(tampered);
+            let cs = ConstraintSystem::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(!cs.is_satisfied().unwrap());
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Self-contained verifiable ciphertexts.
+//!
+//! A [`VerifiableCiphertext`] bundles a ciphertext of the non-native circuit with everything
+//! needed to check it: the drand round it is encrypted to, the hash of the drand chain and the
+//! proof of correct encryption. It is serialized as a single blob, e.g. to be posted on-chain or
+//! gossiped, and verified against the public [`ChainInfo`] alone.
+
+use std::marker::PhantomData;
+use anyhow::{anyhow, bail};
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use ark_sponge::Absorb;
+use ark_std::rand::{CryptoRng, Rng};
+use crate::nonnative::NonNativeConfig;
+use crate::utils::round_identity;
+use crate::{Bls12_381, Ciphertext, NonnativeCircuit, Plaintext, PublicKey, SecretKey};
+
+/// Identifier of the proof system that produced the proof of a [`VerifiableCiphertext`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ProofSystem {
+    Groth16 = 1,
+}
+
+impl TryFrom<u8> for ProofSystem {
+    type Error = anyhow::Error;
+
+    fn try_from(id: u8) -> anyhow::Result<Self> {
+        match id {
+            1 => Ok(ProofSystem::Groth16),
+            _ => bail!("unknown proof system {id}"),
+        }
+    }
+}
+
+/// Public information of a drand chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainInfo {
+    /// Group public key of the network.
+    pub public_key: PublicKey<Bls12_381>,
+    /// Chain hash, which identifies the network.
+    pub hash: [u8; 32],
+}
+
+/// Ciphertext to a drand round along with a proof of its correct encryption, proven with
+/// Groth16 over `E` for the non-native circuit over `PC::BaseField`.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifiableCiphertext<E: Pairing, PC: CurveGroup<BaseField = E::ScalarField>> {
+    /// Proof system id, see [`ProofSystem`].
+    proof_system: u8,
+    /// Hash of the chain whose round signature decrypts the ciphertext.
+    pub chain_hash: [u8; 32],
+    /// Round whose signature decrypts the ciphertext.
+    pub round: u64,
+    pub ciphertext: Ciphertext<ark_bls12_381::G1Projective>,
+    pub proof: Proof<E>,
+    _circuit: PhantomData<PC>,
+}
+
+impl<E: Pairing, PC: CurveGroup<BaseField = E::ScalarField>> VerifiableCiphertext<E, PC>
+    where E::ScalarField: NonNativeConfig + Absorb
+{
+    /// Encrypts `msg` to `round` of `chain` and proves the encryption with `pk`.
+    pub fn seal<R: Rng + CryptoRng>(
+        pk: &ProvingKey<E>,
+        chain: &ChainInfo,
+        round: u64,
+        msg: Plaintext<ark_bls12_381::G1Projective>,
+        rng: &mut R,
+    ) -> anyhow::Result<Self> {
+        let circuit = NonnativeCircuit::<PC>::new(chain.public_key, round_identity(round), msg, rng)?;
+        let ciphertext = circuit.ciphertext.clone()
+            .ok_or_else(|| anyhow!("circuit is missing the ciphertext"))?;
+        let proof = Groth16::<E>::prove(pk, circuit, rng)
+            .map_err(|e| anyhow!("error proving encryption: {e}"))?;
+
+        Ok(Self {
+            proof_system: ProofSystem::Groth16 as u8,
+            chain_hash: chain.hash,
+            round,
+            ciphertext,
+            proof,
+            _circuit: PhantomData,
+        })
+    }
+
+    /// Returns the proof system that produced the proof.
+    pub fn proof_system(&self) -> anyhow::Result<ProofSystem> {
+        ProofSystem::try_from(self.proof_system)
+    }
+
+    /// Verifies that the ciphertext is a valid encryption to its round of `chain`.
+    ///
+    /// `Gid` is recomputed from the chain's public key, so that the proof can't be checked
+    /// against an identity other than the one of the stated round.
+    pub fn verify(&self, vk: &VerifyingKey<E>, chain: &ChainInfo) -> anyhow::Result<bool> {
+        if self.proof_system()? != ProofSystem::Groth16 {
+            bail!("unsupported proof system {:?}", self.proof_system()?);
+        }
+        if self.chain_hash != chain.hash {
+            bail!("ciphertext is for chain {}, expected {}", hex::encode(self.chain_hash), hex::encode(chain.hash));
+        }
+
+        let gid = NonnativeCircuit::<PC>::gid(&chain.public_key, round_identity(self.round))?;
+        let public_inputs = NonnativeCircuit::<PC>::get_public_inputs(&gid, &self.ciphertext);
+
+        Groth16::<E>::verify(vk, &public_inputs, &self.proof)
+            .map_err(|e| anyhow!("error verifying proof: {e}"))
+    }
+
+    /// Decrypts the ciphertext with `sk`, the signature of the chain on the round.
+    pub fn open(&self, sk: &SecretKey<Bls12_381>) -> anyhow::Result<Plaintext<ark_bls12_381::G1Projective>> {
+        NonnativeCircuit::<PC>::decrypt(sk, &self.ciphertext)
+    }
+
+    /// Serializes the bundle with compressed points.
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = vec![];
+        self.serialize_compressed(&mut bytes)
+            .map_err(|e| anyhow!("error serializing ciphertext: {e}"))?;
+        Ok(bytes)
+    }
+
+    /// Deserializes a bundle produced by [`Self::to_bytes`], checking that all points are valid.
+    pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        let bundle = Self::deserialize_compressed(&mut bytes)
+            .map_err(|e| anyhow!("error deserializing ciphertext: {e}"))?;
+        if !bytes.is_empty() {
+            bail!("trailing bytes after ciphertext");
+        }
+        bundle.proof_system()?;
+
+        Ok(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Bn254;
+    use ark_ec::{CurveGroup, Group};
+    use ark_ff::One;
+    use ark_std::{test_rng, UniformRand};
+    use crate::H2C_DST;
+    use crate::prover::{Groth16Nonnative, TimelockProver};
+    use crate::utils::Hash2Curve;
+    use super::*;
+
+    type TestCiphertext = VerifiableCiphertext<Bn254, ark_ed_on_bn254::EdwardsProjective>;
+
+    // Runs the setup and proof of the non-native circuit over BN254, which take minutes.
+    #[test]
+    #[ignore]
+    fn test_bundle_seal_verify_open() {
+        let mut rng = test_rng();
+        let sk = ark_bls12_381::Fr::rand(&mut rng);
+        let chain = ChainInfo {
+            public_key: (ark_bls12_381::G1Projective::generator() * sk).into_affine(),
+            hash: [7; 32],
+        };
+        let round = 1000;
+        let msg = ark_bls12_381::Fq::rand(&mut rng);
+
+        let (pk, vk) = Groth16Nonnative::<Bn254, ark_ed_on_bn254::EdwardsProjective>::setup(NonnativeCircuit::blank(), &mut rng).unwrap();
+        let bundle = TestCiphertext::seal(&pk, &chain, round, msg, &mut rng).unwrap();
+
+        let bytes = bundle.to_bytes().unwrap();
+        let decoded = TestCiphertext::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, bundle);
+        assert!(decoded.verify(&vk, &chain).unwrap());
+
+        let qid: ark_bls12_381::G2Affine = Bls12_381::hash(&round_identity(round), H2C_DST).unwrap();
+        let round_key = (qid * sk).into_affine();
+        assert_eq!(decoded.open(&round_key).unwrap(), msg);
+
+        // Another chain hash is refused, another public key under the same hash fails the proof.
+        let other_hash = ChainInfo { hash: [8; 32], ..chain.clone() };
+        assert!(decoded.verify(&vk, &other_hash).is_err());
+        let other_key = ChainInfo {
+            public_key: (ark_bls12_381::G1Projective::generator() * (sk + ark_bls12_381::Fr::one())).into_affine(),
+            ..chain.clone()
+        };
+        assert!(!decoded.verify(&vk, &other_key).unwrap());
+
+        let mut tampered = decoded.clone();
+        tampered.ciphertext.w += ark_bls12_381::Fq::one();
+        assert!(!tampered.verify(&vk, &chain).unwrap());
+        let mut tampered = decoded.clone();
+        tampered.round += 1;
+        assert!(!tampered.verify(&vk, &chain).unwrap());
+
+        let mut tampered = bytes.clone();
+        tampered[0] = 0;
+        assert!(TestCiphertext::from_bytes(&tampered).is_err());
+        assert!(TestCiphertext::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+    }
+}
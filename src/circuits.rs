@@ -1,5 +1,5 @@
 use crate::poseidon::get_poseidon_params;
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use ark_ff::{BigInteger, BitIteratorLE, Field, PrimeField, ToConstraintField, Zero, Fp12, One, QuadExtField, BigInteger384, Fp12ConfigWrapper};
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::prelude::*;
@@ -7,6 +7,7 @@ use ark_r1cs_std::ToConstraintFieldGadget;
 use ark_relations::ns;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, Namespace, SynthesisError};
 use ark_r1cs_std::groups::{bls12, CurveVar};
+use ark_r1cs_std::pairing::{bls12::PairingVar as Bls12PairingVar, PairingVar};
 use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
 use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
 use ark_sponge::poseidon::{PoseidonConfig, PoseidonSponge};
@@ -18,9 +19,12 @@ use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::iter;
 use std::ops::{Add, Mul, MulAssign};
+use std::rc::Rc;
 use std::str::FromStr;
 use ark_ec::bls12::Bls12Parameters;
+use ark_ec::short_weierstrass::SWCurveConfig;
 use ark_r1cs_std::fields::fp12::Fp12Var;
 use ark_ec::{pairing::Pairing, CurveGroup, Group};
 use ark_sponge::constraints::CryptographicSpongeVar;
@@ -30,26 +34,50 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_bls12_381::Bls12_381;
 use ark_r1cs_std::fields::nonnative::NonNativeFieldVar;
 use ark_r1cs_std::groups::curves::short_weierstrass::ProjectiveVar;
-use crate::utils::{curve_scalar_mul_le, gt_scalar_mul_le, GtAbsorbable, gtvar_to_fqvars, Hash2Curve, ZkCryptoDeserialize};
+use crate::h2c::hash_to_g2;
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use crate::utils::{curve_scalar_mul_le, fixed_base_mul_le, gt_fixed_base_pow_le, gt_fixed_base_tables, gt_pow_le, gt_scalar_mul_le, GtAbsorbable, gtvar_to_fqvars, round_identity, Hash2Curve, ZkCryptoDeserialize};
 use sha2::Sha256;
 use crate::nonnative::*;
+use crate::predicate::PlaintextPredicate;
 use crate::{Randomness, Plaintext, Ciphertext, PublicKey, SecretKey, Parameters};
 
-const R_BYTES_SQUEEZE: usize = 32;
-const H2C_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+pub(crate) const R_BYTES_SQUEEZE: usize = 32;
+/// Domain separation tag of the hash-to-curve used by the drand network for identities.
+pub const H2C_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
 
 pub struct Circuit<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>>
     where <E::G1 as CurveGroup>::BaseField: PrimeField
 {
-    sigma: Randomness<E::G1>,
-    master: PublicKey<E>,
-    msg: Plaintext<E::G1>,
-    pub ciphertext: Ciphertext<E::G1>,
-    pub gid: E::TargetField,
+    sigma: Option<Randomness<E::G1>>,
+    master: Option<PublicKey<E>>,
+    msg: Option<Plaintext<E::G1>>,
+    pub ciphertext: Option<Ciphertext<E::G1>>,
+    pub gid: Option<E::TargetField>,
     params: Parameters<E::G1>,
+    predicates: Vec<Rc<dyn PlaintextPredicate<<E::G1 as CurveGroup>::BaseField>>>,
     _curve_params: PhantomData<P>
 }
 
+// Predicates are shared between clones, e.g. of a blank circuit that is synthesized to size a
+// universal SRS, then indexed.
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Clone for Circuit<E, P>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField
+{
+    fn clone(&self) -> Self {
+        Self {
+            sigma: self.sigma.as_ref().map(|sigma| Randomness(sigma.0)),
+            master: self.master,
+            msg: self.msg,
+            ciphertext: self.ciphertext.clone(),
+            gid: self.gid.clone(),
+            params: self.params.clone(),
+            predicates: self.predicates.clone(),
+            _curve_params: PhantomData,
+        }
+    }
+}
+
 impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circuit<E, P>
     where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
           E: Hash2Curve + GtAbsorbable,
@@ -69,16 +97,47 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
             .map_err(|e| anyhow!("error encrypting message: {e}"))?;
 
         Ok(Self {
-            gid,
-            sigma,
-            msg,
-            master,
-            ciphertext: ct,
+            gid: Some(gid),
+            sigma: Some(sigma),
+            msg: Some(msg),
+            master: Some(master),
+            ciphertext: Some(ct),
             params,
+            predicates: vec![],
             _curve_params: Default::default()
         })
     }
 
+    /// Returns a circuit without any assignments, which synthesizes the same constraint
+    /// system as a real instance, e.g. to generate keys without performing an encryption.
+    pub fn blank() -> Self {
+        Self {
+            gid: None,
+            sigma: None,
+            msg: None,
+            master: None,
+            ciphertext: None,
+            params: Parameters::<E::G1>::default(),
+            predicates: vec![],
+            _curve_params: Default::default()
+        }
+    }
+
+    /// Attaches `predicate` to the plaintext, which the circuit then proves along with the
+    /// encryption. Keys must be generated with the same predicates, e.g. from
+    /// `Circuit::blank().with_predicate(..)`, which is also the circuit to compute their
+    /// [`KeyHeader`](crate::keys::KeyHeader) from.
+    pub fn with_predicate(mut self, predicate: impl PlaintextPredicate<<E::G1 as CurveGroup>::BaseField> + 'static) -> Self {
+        self.predicates.push(Rc::new(predicate));
+        self
+    }
+
+    /// Returns the public inputs allocated by the predicates of the circuit, which follow those
+    /// of [`Self::get_public_inputs`].
+    pub fn predicate_inputs(&self) -> Vec<<E::G1 as CurveGroup>::BaseField> {
+        self.predicates.iter().flat_map(|predicate| predicate.public_inputs()).collect()
+    }
+
     pub fn encrypt<I: AsRef<[u8]>, R: Rng>(
         master: &PublicKey<E>,
         id: I,
@@ -90,7 +149,7 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
         Ok(ct)
     }
 
-    fn encrypt_inner<I: AsRef<[u8]>, R: Rng>(
+    pub(crate) fn encrypt_inner<I: AsRef<[u8]>, R: Rng>(
         master: &PublicKey<E>,
         id: I,
         msg: &Plaintext<E::G1>,
@@ -100,10 +159,7 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
         // 1. Compute Gid = e(master,Q_id)
         // Note: hash-to-curve algo is `draft-irtf-cfrg-bls-signature-05` which matches to the one used in Drand network,
         // hash function is Sha2 despite the fact that poseidon is used elsewhere to optimize proving performance.
-        let gid = {
-            let qid: E::G2Affine = E::hash(id.as_ref(), H2C_DST)?;
-            E::pairing(master.clone(), qid)
-        }.0;
+        let gid = Self::gid(master, id)?;
 
         // 2. Derive random sigma
         let sigma = Randomness::<E::G1>::rand(rng);
@@ -148,13 +204,29 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
         }))
     }
 
+    /// Computes `Gid = e(master, Q_id)`, the public identity element the ciphertext is bound to.
+    pub fn gid<I: AsRef<[u8]>>(master: &PublicKey<E>, id: I) -> anyhow::Result<E::TargetField> {
+        let qid: E::G2Affine = E::hash(id.as_ref(), H2C_DST)?;
+        Ok(E::pairing(master.clone(), qid).0)
+    }
+
     #[inline]
     pub fn decrypt(
         sk: &SecretKey<E>,
         ct: &Ciphertext<E::G1>,
     ) -> anyhow::Result<Plaintext<E::G1>> {
         let params = Parameters::<E::G1>::default();
+        let (_, msg) = Self::decrypt_inner(sk, ct, &params)?;
+        Ok(msg)
+    }
 
+    /// Decrypts `ct` with the round signature `sk`, returning the recovered sigma along with the
+    /// plaintext, or an error if `U` doesn't match them.
+    pub(crate) fn decrypt_inner(
+        sk: &SecretKey<E>,
+        ct: &Ciphertext<E::G1>,
+        params: &Parameters<E::G1>,
+    ) -> anyhow::Result<(Randomness<E::G1>, Plaintext<E::G1>)> {
         // 1. Compute sigma = V XOR H2(e(rP,private))
         let sigma = {
             let r_gid = E::pairing(ct.u.clone(), sk.clone()).0;
@@ -183,9 +255,11 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
             let r = sponge.squeeze_bytes(R_BYTES_SQUEEZE);
             curve_scalar_mul_le(E::G1::generator(), &r)
         };
-        assert_eq!(ct.u, r_g);
+        if ct.u != r_g {
+            return Err(anyhow!("ciphertext is invalid for the given key"));
+        }
 
-        Ok(msg)
+        Ok((Randomness(sigma), msg))
     }
 
     pub fn get_public_inputs(
@@ -198,6 +272,13 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
     {
         let gid_inputs = gid.to_field_elements().unwrap();
 
+        gid_inputs.into_iter().chain(Self::ciphertext_inputs(cipher)).collect()
+    }
+
+    /// Returns the public inputs of the ciphertext alone, i.e. those of `U`, `V` and `W`.
+    fn ciphertext_inputs(cipher: &Ciphertext<E::G1>) -> Vec<<E::G1 as CurveGroup>::BaseField>
+        where E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+    {
         let mut u_inputs = cipher.u.to_field_elements().unwrap();
         let v_inputs = cipher.v.to_field_elements().unwrap();
         let w_inputs = cipher.w.to_field_elements().unwrap();
@@ -206,7 +287,7 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
         // See: https://github.com/arkworks-rs/r1cs-std/issues/106
         u_inputs[2] = <E::G1 as CurveGroup>::BaseField::one();
 
-        gid_inputs.into_iter().chain(u_inputs).chain(v_inputs).chain(w_inputs).collect()
+        u_inputs.into_iter().chain(v_inputs).chain(w_inputs).collect()
     }
 
     pub(crate) fn verify_encryption(
@@ -216,8 +297,25 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
         msg: &FpVar<<E::G1 as CurveGroup>::BaseField>,
         ct: &(bls12::G1Var<P>, FpVar<<E::G1 as CurveGroup>::BaseField>, FpVar<<E::G1 as CurveGroup>::BaseField>),
     ) -> Result<(), SynthesisError> {
+        // `Gid` is a pairing output, hence in the cyclotomic subgroup.
+        self.verify_encryption_with(cs, msg, ct, |r| gt_pow_le::<P>(&gid, r))
+    }
+
+    /// Same as [`Self::verify_encryption`], with `Gid^r` computed by `gid_pow` from the
+    /// little-endian bits of `r`, e.g. with a fixed-base exponentiation when `Gid` is a constant.
+    pub(crate) fn verify_encryption_with(
+        &self,
+        cs: ConstraintSystemRef<<E::G1 as CurveGroup>::BaseField>,
+        msg: &FpVar<<E::G1 as CurveGroup>::BaseField>,
+        ct: &(bls12::G1Var<P>, FpVar<<E::G1 as CurveGroup>::BaseField>, FpVar<<E::G1 as CurveGroup>::BaseField>),
+        gid_pow: impl FnOnce(&[Boolean<<E::G1 as CurveGroup>::BaseField>]) -> Result<Fp12Var<P::Fp12Config>, SynthesisError>,
+    ) -> Result<(), SynthesisError> {
+        for predicate in &self.predicates {
+            predicate.enforce(cs.clone(), msg)?;
+        }
+
         // 2. Derive random sigma
-        let sigma = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_witness(ns!(cs, "sigma"), || Ok(&self.sigma.0))?;
+        let sigma = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_witness(ns!(cs, "sigma"), || self.sigma.as_ref().map(|s| s.0).ok_or(SynthesisError::AssignmentMissing))?;
 
         // 3. Derive r from sigma and msg
         let r = {
@@ -230,22 +328,12 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
         };
 
         // 4. Compute U = G*r
-        let g = bls12::G1Var::<P>::new_constant(ns!(cs, "generator"), E::G1::generator())?;
-        let u = g.scalar_mul_le(r.iter())?;
+        let u = fixed_base_mul_le::<P::G1Parameters>(P::G1Parameters::GENERATOR.into(), &r)?;
         u.enforce_equal(&ct.0)?;
 
         // 5. Compute V = sigma XOR H(rGid)
         let v = {
-            let r_gid = {
-                let mut res = Fp12Var::<P::Fp12Config>::one();
-                let mut mul = gid;
-                for bit in r.into_iter() {
-                    let tmp = res.clone() * &mul;
-                    res = bit.select(&tmp, &res)?;
-                    mul.square_in_place()?;
-                }
-                res
-            };
+            let r_gid = gid_pow(&r)?;
             let mut sponge = PoseidonSpongeVar::new(cs.clone(), &self.params.poseidon);
             sponge.absorb(&gtvar_to_fqvars::<E, P>(&r_gid))?;
 
@@ -280,14 +368,14 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
     ) -> Result<(bls12::G1Var<P>, FpVar<<E::G1 as CurveGroup>::BaseField>, FpVar<<E::G1 as CurveGroup>::BaseField>), SynthesisError> {
         let u = bls12::G1Var::<P>::new_variable(
             ns!(cs, "ciphertext_u"),
-            || Ok(self.ciphertext.u),
+            || self.ciphertext.as_ref().map(|ct| ct.u).ok_or(SynthesisError::AssignmentMissing),
             mode,
         )?;
 
         let v = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_variable(
             ns!(cs, "ciphertext_v"),
             || {
-                Ok(self.ciphertext.v)
+                self.ciphertext.as_ref().map(|ct| ct.v).ok_or(SynthesisError::AssignmentMissing)
             },
             mode,
         )?;
@@ -295,7 +383,7 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Circ
         let w = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_variable(
             ns!(cs, "ciphertext_w"),
             || {
-                Ok(self.ciphertext.w)
+                self.ciphertext.as_ref().map(|ct| ct.w).ok_or(SynthesisError::AssignmentMissing)
             },
             mode,
         )?;
@@ -314,29 +402,52 @@ impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> Cons
         self,
         cs: ConstraintSystemRef<<E::G1 as CurveGroup>::BaseField>,
     ) -> Result<(), SynthesisError> {
-        let gid = Fp12Var::<P::Fp12Config>::new_input(ns!(cs, "gid"), || Ok(self.gid))?;
+        let gid = Fp12Var::<P::Fp12Config>::new_input(ns!(cs, "gid"), || self.gid.ok_or(SynthesisError::AssignmentMissing))?;
         let ciphertext = self.ciphertext_var(cs.clone(), AllocationMode::Input)?;
         let message = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_witness(ns!(cs, "plaintext"), || {
-            Ok(self.msg)
+            self.msg.ok_or(SynthesisError::AssignmentMissing)
         })?;
 
         self.verify_encryption(cs.clone(), gid, &message, &ciphertext)
     }
 }
 
+/// Proves the encryption of [`Circuit`] over BLS12-381 with BLS12-381 emulated over the constraint
+/// field `PC::BaseField`, e.g. BN254 `Fr` to verify proofs on Ethereum.
+///
+/// Hashes are computed with the Poseidon instance of BLS12-381 `Fq` emulated over the constraint
+/// field, hence ciphertexts are those of `Circuit::<Bls12_381, _>` whatever the proof system.
 pub struct NonnativeCircuit<PC: CurveGroup>
     where PC::BaseField: PrimeField
 {
-    gid: ark_bls12_381::Fq12,
-    sigma: Randomness<ark_bls12_381::G1Projective>,
-    master: PublicKey<Bls12_381>,
-    msg: Plaintext<ark_bls12_381::G1Projective>,
-    pub ciphertext: Ciphertext<ark_bls12_381::G1Projective>,
-    params: Parameters<PC>,
+    pub gid: Option<ark_bls12_381::Fq12>,
+    sigma: Option<Randomness<ark_bls12_381::G1Projective>>,
+    master: Option<PublicKey<Bls12_381>>,
+    msg: Option<Plaintext<ark_bls12_381::G1Projective>>,
+    pub ciphertext: Option<Ciphertext<ark_bls12_381::G1Projective>>,
+    params: Parameters<ark_bls12_381::G1Projective>,
+    predicates: Vec<Rc<dyn PlaintextPredicate<PC::BaseField, FqVar<PC::BaseField>>>>,
 }
 
-impl<PC: CurveGroup> NonnativeCircuit<PC>
+// Predicates are shared between clones, as those of `Circuit`.
+impl<PC: CurveGroup> Clone for NonnativeCircuit<PC>
     where PC::BaseField: PrimeField
+{
+    fn clone(&self) -> Self {
+        Self {
+            gid: self.gid,
+            sigma: self.sigma.as_ref().map(|sigma| Randomness(sigma.0)),
+            master: self.master,
+            msg: self.msg,
+            ciphertext: self.ciphertext.clone(),
+            params: self.params.clone(),
+            predicates: self.predicates.clone(),
+        }
+    }
+}
+
+impl<PC: CurveGroup> NonnativeCircuit<PC>
+    where PC::BaseField: NonNativeConfig + Absorb
 {
     pub fn new<I: AsRef<[u8]>, R: Rng>(
         master: PublicKey<Bls12_381>,
@@ -344,22 +455,62 @@ impl<PC: CurveGroup> NonnativeCircuit<PC>
         msg: Plaintext<ark_bls12_381::G1Projective>,
         rng: &mut R,
     ) -> anyhow::Result<Self> {
-        let pp_381 = Parameters::<ark_bls12_381::G1Projective>::default();
-        let params = Parameters::<PC>::default();
+        let params = Parameters::<ark_bls12_381::G1Projective>::default();
 
-        let (gid, sigma, ct) = Circuit::<Bls12_381, ark_bls12_381::Parameters>::encrypt_inner(&master, id, &msg, &pp_381, rng)
+        let (gid, sigma, ct) = Circuit::<Bls12_381, ark_bls12_381::Parameters>::encrypt_inner(&master, id, &msg, &params, rng)
             .map_err(|e| anyhow!("error encrypting message: {e}"))?;
 
         Ok(Self {
-            gid,
-            sigma,
-            msg,
-            master,
-            ciphertext: ct,
+            gid: Some(gid),
+            sigma: Some(sigma),
+            msg: Some(msg),
+            master: Some(master),
+            ciphertext: Some(ct),
             params,
+            predicates: vec![],
         })
     }
 
+    /// Returns a circuit without any assignments, see [`Circuit::blank`].
+    pub fn blank() -> Self {
+        Self {
+            gid: None,
+            sigma: None,
+            msg: None,
+            master: None,
+            ciphertext: None,
+            params: Parameters::<ark_bls12_381::G1Projective>::default(),
+            predicates: vec![],
+        }
+    }
+
+    /// Attaches `predicate` to the plaintext, see [`Circuit::with_predicate`].
+    pub fn with_predicate(mut self, predicate: impl PlaintextPredicate<PC::BaseField, FqVar<PC::BaseField>> + 'static) -> Self {
+        self.predicates.push(Rc::new(predicate));
+        self
+    }
+
+    /// Returns the public inputs allocated by the predicates of the circuit, which follow those
+    /// of [`Self::get_public_inputs`].
+    pub fn predicate_inputs(&self) -> Vec<PC::BaseField> {
+        self.predicates.iter().flat_map(|predicate| predicate.public_inputs()).collect()
+    }
+
+    /// Encrypts `msg` to `id`, producing the same ciphertext format as `Circuit::<Bls12_381, _>::encrypt`.
+    pub fn encrypt<I: AsRef<[u8]>, R: Rng>(
+        master: &PublicKey<Bls12_381>,
+        id: I,
+        msg: &Plaintext<ark_bls12_381::G1Projective>,
+        rng: &mut R,
+    ) -> anyhow::Result<Ciphertext<ark_bls12_381::G1Projective>> {
+        Circuit::<Bls12_381, ark_bls12_381::Parameters>::encrypt(master, id, msg, rng)
+    }
+
+    /// Computes `Gid = e(master, Q_id)`, the public identity element the ciphertext is bound to.
+    pub fn gid<I: AsRef<[u8]>>(master: &PublicKey<Bls12_381>, id: I) -> anyhow::Result<ark_bls12_381::Fq12> {
+        Circuit::<Bls12_381, ark_bls12_381::Parameters>::gid(master, id)
+    }
+
     pub fn decrypt(
         sk: &SecretKey<Bls12_381>,
         ct: &Ciphertext<ark_bls12_381::G1Projective>,
@@ -367,21 +518,19 @@ impl<PC: CurveGroup> NonnativeCircuit<PC>
         Circuit::<Bls12_381, ark_bls12_381::Parameters>::decrypt(sk, ct)
     }
 
-    // pub fn get_public_input(
-    //     gid: &ark_bls12_381::Fq12,
-    //     cipher: &Ciphertext<ark_bls12_381::G1Projective>,
-    // ) -> Vec<PC::BaseField>
-    // {
-    //     let gid_inputs = gid.to_field_elements().unwrap();
-    //
-    //     let mut u_inputs = cipher.u.to_field_elements().unwrap();
-    //     let v_inputs = cipher.v.to_field_elements().unwrap();
-    //     let w_inputs = cipher.w.to_field_elements().unwrap();
-    //
-    //     u_inputs[2] = ark_bls12_381::Fq::one();
-    //
-    //     gid_inputs.into_iter().chain(u_inputs).chain(v_inputs).chain(w_inputs).collect()
-    // }
+    /// Packs `gid` and the ciphertext into the public inputs of the circuit: the bits of the 12
+    /// coefficients of `gid`, then of `U.x`, `U.y`, `V` and `W`, packed by [`pack_field_elements`].
+    pub fn get_public_inputs(
+        gid: &ark_bls12_381::Fq12,
+        cipher: &Ciphertext<ark_bls12_381::G1Projective>,
+    ) -> Vec<PC::BaseField> {
+        let u = cipher.u.into_affine();
+        let elems = fp12_coefficients(gid).into_iter()
+            .chain([u.x, u.y, cipher.v, cipher.w])
+            .collect::<Vec<_>>();
+
+        pack_field_elements(&elems)
+    }
 
     pub(crate) fn verify_encryption(
         &self,
@@ -390,45 +539,33 @@ impl<PC: CurveGroup> NonnativeCircuit<PC>
         msg: &FqVar<PC::BaseField>,
         ct: &(G1Var<PC::BaseField>, FqVar<PC::BaseField>, FqVar<PC::BaseField>),
     ) -> Result<(), SynthesisError> {
-        let g_x = FqVar::new_constant(
-            ns!(cs, "ciphertext_u_x"),
-            &ark_bls12_381::G1Projective::generator().x
-        )?;
-        let g_y = FqVar::new_constant(
-            ns!(cs, "ciphertext_u_x"),
-            &ark_bls12_381::G1Projective::generator().y
-        )?;
-        let g = G1Var::new(
-            g_x, g_y
-        );
+        for predicate in &self.predicates {
+            predicate.enforce(cs.clone(), msg)?;
+        }
 
         // 2. Derive random sigma
-        let sigma = FqVar::new_witness(ns!(cs, "sigma"), || Ok(&self.sigma.0))?;
+        let sigma = FqVar::new_witness(ns!(cs, "sigma"), || self.sigma.as_ref().map(|s| s.0).ok_or(SynthesisError::AssignmentMissing))?;
 
 
         // 3. Derive r from sigma and msg
         let r = {
-            let mut sponge = PoseidonSpongeVar::new(cs.clone(), &self.params.poseidon);
-            sponge.absorb(&sigma.to_constraint_field().unwrap())?;
-            sponge.absorb(&msg.to_constraint_field().unwrap())?;
+            let mut sponge = NonNativePoseidonSpongeVar::new(&self.params.poseidon);
+            sponge.absorb(&[sigma.clone(), msg.clone()])?;
             sponge
                 .squeeze_bytes(R_BYTES_SQUEEZE)?
                 .into_iter().flat_map(|b| b.to_bits_le().unwrap()).collect::<Vec<_>>()
         };
 
         // 4. Compute U = G*r
-        let u = g.scalar_mul_le(r.iter())?;
+        let u = G1Var::fixed_base_scalar_mul_le(ark_bls12_381::G1Projective::generator(), &r)?;
         u.enforce_equal(&ct.0)?;
 
         // 5. Compute V = sigma XOR H(rGid)
         let v = {
-            let r_gid = gid.scalar_mul_le(r.iter())?;
-            let mut sponge = PoseidonSpongeVar::new(cs.clone(), &self.params.poseidon);
-            sponge.absorb(&r_gid)?;
-
-            let h_r_gid = sponge
-                .squeeze_nonnative_field_elements(1)
-                .and_then(|r| Ok(r.0[0].clone()))?;
+            let r_gid = gid.pow_le(&r)?;
+            let mut sponge = NonNativePoseidonSpongeVar::new(&self.params.poseidon);
+            sponge.absorb(&r_gid.coefficients().into_iter().cloned().collect::<Vec<_>>())?;
+            let h_r_gid = sponge.squeeze_native_field_elements(1)?.remove(0);
 
             &sigma + h_r_gid
         };
@@ -437,11 +574,9 @@ impl<PC: CurveGroup> NonnativeCircuit<PC>
 
         // 6. Compute W = M XOR H(sigma)
         let w = {
-            let mut poseidon = PoseidonSpongeVar::new(cs.clone(), &self.params.poseidon);
-            poseidon.absorb(&sigma.to_constraint_field().unwrap())?;
-            let h_sigma = poseidon
-                .squeeze_nonnative_field_elements::<ark_bls12_381::Fq>(1)
-                .and_then(|r| Ok(r.0[0].clone()))?;
+            let mut poseidon = NonNativePoseidonSpongeVar::new(&self.params.poseidon);
+            poseidon.absorb(&[sigma])?;
+            let h_sigma = poseidon.squeeze_native_field_elements(1)?.remove(0);
 
             msg + h_sigma
         };
@@ -459,14 +594,14 @@ impl<PC: CurveGroup> NonnativeCircuit<PC>
         let u_x = FqVar::new_variable(
             ns!(cs, "ciphertext_u_x"),
             || {
-                Ok(self.ciphertext.u.x)
+                self.ciphertext.as_ref().map(|ct| ct.u.into_affine().x).ok_or(SynthesisError::AssignmentMissing)
             },
             mode,
         )?;
         let u_y = FqVar::new_variable(
-            ns!(cs, "ciphertext_u_x"),
+            ns!(cs, "ciphertext_u_y"),
             || {
-                Ok(self.ciphertext.u.y)
+                self.ciphertext.as_ref().map(|ct| ct.u.into_affine().y).ok_or(SynthesisError::AssignmentMissing)
             },
             mode,
         )?;
@@ -477,7 +612,7 @@ impl<PC: CurveGroup> NonnativeCircuit<PC>
         let v = FqVar::new_variable(
             ns!(cs, "ciphertext_v"),
             || {
-                Ok(self.ciphertext.v)
+                self.ciphertext.as_ref().map(|ct| ct.v).ok_or(SynthesisError::AssignmentMissing)
             },
             mode,
         )?;
@@ -485,7 +620,7 @@ impl<PC: CurveGroup> NonnativeCircuit<PC>
         let w = FqVar::new_variable(
             ns!(cs, "ciphertext_w"),
             || {
-                Ok(self.ciphertext.w)
+                self.ciphertext.as_ref().map(|ct| ct.w).ok_or(SynthesisError::AssignmentMissing)
             },
             mode,
         )?;
@@ -495,46 +630,431 @@ impl<PC: CurveGroup> NonnativeCircuit<PC>
 }
 
 impl<PC: CurveGroup> ConstraintSynthesizer<PC::BaseField> for NonnativeCircuit<PC>
-    where PC::BaseField: PrimeField
+    where PC::BaseField: NonNativeConfig + Absorb
 {
     fn generate_constraints(
         self,
         cs: ConstraintSystemRef<PC::BaseField>,
     ) -> Result<(), SynthesisError> {
-        let gid = new_fp12_variable::<_, PC::BaseField>(ns!(cs, "gid"), || Ok(self.gid), AllocationMode::Input)?;
+        // Selects the limb layout of the non-native variables, must precede any allocation.
+        cs.set_optimization_goal(PC::BaseField::OPTIMIZATION_GOAL);
+
+        let gid = Fq12Var::new_witness(ns!(cs, "gid"), || self.gid.ok_or(SynthesisError::AssignmentMissing))?;
         let message = FqVar::new_witness(ns!(cs, "plaintext"), || {
-            Ok(self.msg)
+            self.msg.ok_or(SynthesisError::AssignmentMissing)
         })?;
-        let ciphertext = self.ciphertext_var(cs.clone(), AllocationMode::Input)?;
+        let ciphertext = self.ciphertext_var(cs.clone(), AllocationMode::Witness)?;
+
+        // Allocating non-native variables as inputs would cost an input per limb, which makes
+        // verification (especially on-chain) expensive. Instead, their bits are packed tightly
+        // into native inputs, see `get_public_inputs`.
+        let public = gid.coefficients().into_iter()
+            .chain([&ciphertext.0.x, &ciphertext.0.y, &ciphertext.1, &ciphertext.2])
+            .collect::<Vec<_>>();
+        for packed in pack_nonnative_field_vars(&public)? {
+            let input = FpVar::new_input(ns!(cs, "packed_input"), || packed.value())?;
+            input.enforce_equal(&packed)?;
+        }
 
         self.verify_encryption(cs.clone(), gid, &message, &ciphertext)
     }
 }
 
 // This is a modified native circuit for experimental use with Gemini proving system.
-// Due to mis-configured (hopefully) padding mechanism of Gemini this circuit intentionally has no input variables.
+// Gemini mis-handles instances whose size (including the constant `1`) is not a power of two,
+// hence the inputs of `Circuit` are followed by zero-valued padding inputs.
 // For more details see: https://github.com/arkworks-rs/gemini/issues/5
+// Plaintext predicates are not supported: the inputs they allocate would follow the padding, and
+// the Gemini verifier re-derives the relation of the blank circuit, see `check_predicates`.
 pub struct GeminiNativeCircuit(pub Circuit<Bls12_381, ark_bls12_381::Parameters>);
 
+impl GeminiNativeCircuit {
+    /// Returns a circuit without any assignments, see [`Circuit::blank`].
+    pub fn blank() -> Self {
+        Self(Circuit::blank())
+    }
+
+    /// Returns [`Circuit::get_public_inputs`] followed by the zero padding.
+    pub fn get_public_inputs(
+        gid: &ark_bls12_381::Fq12,
+        cipher: &Ciphertext<ark_bls12_381::G1Projective>,
+    ) -> Vec<ark_bls12_381::Fq> {
+        let mut inputs = Circuit::<Bls12_381, ark_bls12_381::Parameters>::get_public_inputs(gid, cipher);
+        inputs.resize(Self::padded_num_inputs(inputs.len()), ark_bls12_381::Fq::zero());
+        inputs
+    }
+
+    fn padded_num_inputs(num_inputs: usize) -> usize {
+        (num_inputs + 1).next_power_of_two() - 1
+    }
+
+    /// Fails if predicates are attached to the circuit, which it doesn't support.
+    pub fn check_predicates(&self) -> anyhow::Result<()> {
+        if !self.0.predicates.is_empty() {
+            bail!("plaintext predicates are not supported by GeminiNativeCircuit");
+        }
+        Ok(())
+    }
+}
+
 impl ConstraintSynthesizer<ark_bls12_381::Fq> for GeminiNativeCircuit {
     fn generate_constraints(
         self,
         cs: ConstraintSystemRef<ark_bls12_381::Fq>,
     ) -> Result<(), SynthesisError> {
-        let gid = Fp12Var::<ark_bls12_381::Fq12Config>::new_witness(ns!(cs, "gid"), || Ok(self.0.gid))?;
-        let ciphertext = self.0.ciphertext_var(cs.clone(), AllocationMode::Witness)?;
+        self.check_predicates().map_err(|_| SynthesisError::Unsatisfiable)?;
+
+        let gid = Fp12Var::<ark_bls12_381::Fq12Config>::new_input(ns!(cs, "gid"), || self.0.gid.ok_or(SynthesisError::AssignmentMissing))?;
+        let ciphertext = self.0.ciphertext_var(cs.clone(), AllocationMode::Input)?;
+
+        let num_inputs = cs.num_instance_variables() - 1;
+        for _ in num_inputs..Self::padded_num_inputs(num_inputs) {
+            let padding = FpVar::new_input(ns!(cs, "padding"), || Ok(ark_bls12_381::Fq::zero()))?;
+            padding.enforce_equal(&FpVar::zero())?;
+        }
+
         let message = FpVar::<ark_bls12_381::Fq>::new_witness(ns!(cs, "plaintext"), || {
-            Ok(self.0.msg)
+            self.0.msg.ok_or(SynthesisError::AssignmentMissing)
         })?;
 
         self.0.verify_encryption(cs.clone(), gid, &message, &ciphertext)
     }
 }
 
+/// Variant of [`Circuit`] specialised to a single round, for applications where every user
+/// encrypts to the same round.
+///
+/// `Gid` is a constant of the circuit rather than a public input, so that `Gid^r` is computed
+/// with a fixed-base exponentiation over precomputed powers (see [`gt_fixed_base_pow_le`]).
+/// Since the constraints depend on `Gid`, each round needs its own keys, see
+/// [`crate::prover::MarlinNative::index_round`] to derive them from a shared universal SRS.
+pub struct RoundCircuit<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField
+{
+    pub gid: E::TargetField,
+    pub circuit: Circuit<E, P>,
+}
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> RoundCircuit<E, P>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    pub fn new<I: AsRef<[u8]>, R: Rng>(
+        master: PublicKey<E>,
+        id: I,
+        msg: Plaintext<E::G1>,
+        rng: &mut R,
+    ) -> anyhow::Result<Self> {
+        let circuit = Circuit::new(master, id, msg, rng)?;
+        let gid = circuit.gid.clone()
+            .ok_or_else(|| anyhow!("circuit is missing gid"))?;
+
+        Ok(Self { gid, circuit })
+    }
+
+    /// Returns the circuit of `gid` without any assignments, e.g. to generate its keys.
+    pub fn blank(gid: E::TargetField) -> Self {
+        let mut circuit = Circuit::blank();
+        circuit.gid = Some(gid.clone());
+
+        Self { gid, circuit }
+    }
+
+    /// Returns the public inputs of the circuit, i.e. those of [`Circuit`] without `gid`.
+    pub fn get_public_inputs(cipher: &Ciphertext<E::G1>) -> Vec<<E::G1 as CurveGroup>::BaseField>
+        where E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+    {
+        Circuit::<E, P>::ciphertext_inputs(cipher)
+    }
+}
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> ConstraintSynthesizer<<E::G1 as CurveGroup>::BaseField> for RoundCircuit<E, P>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::TargetField: Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<<E::G1 as CurveGroup>::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        let ciphertext = self.circuit.ciphertext_var(cs.clone(), AllocationMode::Input)?;
+        let message = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_witness(ns!(cs, "plaintext"), || {
+            self.circuit.msg.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let gid: &Fp12<P::Fp12Config> = self.gid.borrow();
+        let tables = gt_fixed_base_tables(*gid, R_BYTES_SQUEEZE * 8);
+        self.circuit.verify_encryption_with(cs.clone(), &message, &ciphertext, |r| {
+            gt_fixed_base_pow_le::<P>(&tables, r)
+        })
+    }
+}
+
+/// Variant of [`Circuit`] whose only public input is a Poseidon digest of `Gid`, which identifies
+/// the round, and of the ciphertext, see [`DigestCircuit::digest`].
+///
+/// `Gid` and the ciphertext are witnesses hashed by the circuit, so that the Groth16 verifier
+/// processes a single public input instead of the 18 of [`Circuit`].
+pub struct DigestCircuit<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>>(pub Circuit<E, P>)
+    where <E::G1 as CurveGroup>::BaseField: PrimeField;
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> DigestCircuit<E, P>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+          E::TargetField: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    /// Returns a circuit without any assignments, see [`Circuit::blank`].
+    pub fn blank() -> Self {
+        Self(Circuit::blank())
+    }
+
+    /// Computes the digest of `gid` and `cipher` exposed by the circuit: the Poseidon hash of the
+    /// coefficients of `gid`, the affine coordinates of `U` with its infinity flag, `V` and `W`.
+    pub fn digest(
+        gid: &E::TargetField,
+        cipher: &Ciphertext<E::G1>,
+    ) -> <E::G1 as CurveGroup>::BaseField {
+        let params = Parameters::<E::G1>::default();
+        let mut sponge = PoseidonSponge::new(&params.poseidon);
+        sponge.absorb(&gid.to_field_elements().unwrap());
+        sponge.absorb(&cipher.u.to_field_elements().unwrap());
+        sponge.absorb(&cipher.v);
+        sponge.absorb(&cipher.w);
+        sponge.squeeze_native_field_elements(1).remove(0)
+    }
+
+    pub fn get_public_inputs(
+        gid: &E::TargetField,
+        cipher: &Ciphertext<E::G1>,
+    ) -> Vec<<E::G1 as CurveGroup>::BaseField> {
+        vec![Self::digest(gid, cipher)]
+    }
+}
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> ConstraintSynthesizer<<E::G1 as CurveGroup>::BaseField> for DigestCircuit<E, P>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+          E::TargetField: ToConstraintField<<E::G1 as CurveGroup>::BaseField> + Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<<E::G1 as CurveGroup>::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        let circuit = self.0;
+        let digest = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_input(ns!(cs, "digest"), || {
+            circuit.gid.as_ref().zip(circuit.ciphertext.as_ref())
+                .map(|(gid, ct)| Self::digest(gid, ct))
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let gid = Fp12Var::<P::Fp12Config>::new_witness(ns!(cs, "gid"), || circuit.gid.ok_or(SynthesisError::AssignmentMissing))?;
+
+        // `U` is constrained to equal `G*r` by `verify_encryption`, so its affine coordinates are
+        // allocated as is, without the curve and subgroup checks of `G1Var::new_witness`.
+        let u = circuit.ciphertext.as_ref().map(|ct| ct.u.to_field_elements().unwrap());
+        let [u_x, u_y] = [0, 1].map(|i| {
+            FpVar::<<E::G1 as CurveGroup>::BaseField>::new_witness(ns!(cs, "ciphertext_u"), || {
+                u.as_ref().map(|u| u[i]).ok_or(SynthesisError::AssignmentMissing)
+            })
+        });
+        let (u_x, u_y) = (u_x?, u_y?);
+        let v = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_witness(ns!(cs, "ciphertext_v"), || {
+            circuit.ciphertext.as_ref().map(|ct| ct.v).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let w = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_witness(ns!(cs, "ciphertext_w"), || {
+            circuit.ciphertext.as_ref().map(|ct| ct.w).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let message = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_witness(ns!(cs, "plaintext"), || {
+            circuit.msg.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let mut sponge = PoseidonSpongeVar::new(cs.clone(), &circuit.params.poseidon);
+        sponge.absorb(&gtvar_to_fqvars::<E, P>(&gid))?;
+        // `G*r` is never the point at infinity, whose flag is hence zero.
+        sponge.absorb(&vec![u_x.clone(), u_y.clone(), FpVar::zero()])?;
+        sponge.absorb(&v)?;
+        sponge.absorb(&w)?;
+        sponge.squeeze_field_elements(1)?[0].enforce_equal(&digest)?;
+
+        let u = bls12::G1Var::<P>::new(u_x, u_y, FpVar::one());
+        circuit.verify_encryption(cs.clone(), gid, &message, &(u, v, w))
+    }
+}
+
+/// Variant of [`Circuit`] over BLS12-381 whose public input is the drand round number in place of
+/// `Gid`, so that verifiers only check the proof and don't compute a pairing, e.g. on-chain.
+///
+/// The circuit derives the identity of the round (see [`round_identity`]), hashes it to G2 with
+/// [`hash_to_g2`] and pairs it with the master key of the network, which is a constant of the
+/// circuit. Hashing takes 20 SHA-256 blocks: 1 for the identity, and 3 for `b_0` plus 2 for each
+/// of `b_1..b_8` in `expand_message_xmd`.
+pub struct RoundNumberCircuit {
+    pub master: PublicKey<Bls12_381>,
+    pub round: Option<u64>,
+    pub circuit: Circuit<Bls12_381, ark_bls12_381::Parameters>,
+}
+
+impl RoundNumberCircuit {
+    pub fn new<R: Rng>(
+        master: PublicKey<Bls12_381>,
+        round: u64,
+        msg: Plaintext<ark_bls12_381::G1Projective>,
+        rng: &mut R,
+    ) -> anyhow::Result<Self> {
+        let circuit = Circuit::new(master, round_identity(round), msg, rng)?;
+
+        Ok(Self { master, round: Some(round), circuit })
+    }
+
+    /// Returns the circuit of the network with key `master` without any assignments, e.g. to
+    /// generate its keys.
+    pub fn blank(master: PublicKey<Bls12_381>) -> Self {
+        Self { master, round: None, circuit: Circuit::blank() }
+    }
+
+    /// Returns the public inputs of the circuit, i.e. the round number followed by the inputs of
+    /// [`Circuit`] without `gid`.
+    pub fn get_public_inputs(round: u64, cipher: &Ciphertext<ark_bls12_381::G1Projective>) -> Vec<ark_bls12_381::Fq> {
+        let ciphertext_inputs = Circuit::<Bls12_381, ark_bls12_381::Parameters>::ciphertext_inputs(cipher);
+
+        iter::once(ark_bls12_381::Fq::from(round)).chain(ciphertext_inputs).collect()
+    }
+}
+
+impl ConstraintSynthesizer<ark_bls12_381::Fq> for RoundNumberCircuit {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ark_bls12_381::Fq>,
+    ) -> Result<(), SynthesisError> {
+        let round = FpVar::<ark_bls12_381::Fq>::new_input(ns!(cs, "round"), || {
+            self.round.map(ark_bls12_381::Fq::from).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let ciphertext = self.circuit.ciphertext_var(cs.clone(), AllocationMode::Input)?;
+        let message = FpVar::<ark_bls12_381::Fq>::new_witness(ns!(cs, "plaintext"), || {
+            self.circuit.msg.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let round_bits = (0..64).map(|i| {
+            Boolean::new_witness(ns!(cs, "round_bit"), || {
+                self.round.map(|round| (round >> i) & 1 == 1).ok_or(SynthesisError::AssignmentMissing)
+            })
+        }).collect::<Result<Vec<_>, _>>()?;
+        Boolean::le_bits_to_fp_var(&round_bits)?.enforce_equal(&round)?;
+
+        // 1. Compute Gid = e(master, H(sha256(round))), with the round in big-endian bytes.
+        let gid = {
+            let round_bytes = round_bits.chunks(8).rev().map(UInt8::from_bits_le).collect::<Vec<_>>();
+            let qid = hash_to_g2(&Sha256Gadget::digest(&round_bytes)?.0, H2C_DST)?;
+            let master = bls12::G1Var::<ark_bls12_381::Parameters>::constant(self.master.into());
+            Bls12PairingVar::<ark_bls12_381::Parameters>::pairing(
+                Bls12PairingVar::<ark_bls12_381::Parameters>::prepare_g1(&master)?,
+                Bls12PairingVar::<ark_bls12_381::Parameters>::prepare_g2(&qid)?,
+            )?
+        };
+
+        self.circuit.verify_encryption(cs.clone(), gid, &message, &ciphertext)
+    }
+}
+
+/// Proves that a ciphertext decrypts to a claimed plaintext once the signature of its round is
+/// published, so that anyone can check the revealed value against a single SNARK.
+///
+/// The signature is a witness, and `Gid`, the ciphertext and the plaintext are public inputs. The
+/// circuit checks the signature with `e(G, sk) == Gid`, recovers `sigma` from `V` and
+/// `e(U, sk)`, which equals `Gid^r`, and enforces `W` and `U == G*r` as in [`Circuit`].
+pub struct DecryptionCircuit<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField
+{
+    pub signature: Option<SecretKey<E>>,
+    pub circuit: Circuit<E, P>,
+}
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> DecryptionCircuit<E, P>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    /// Decrypts `ct` with the round signature `signature` and returns the circuit proving it.
+    pub fn new(signature: SecretKey<E>, ct: Ciphertext<E::G1>) -> anyhow::Result<Self> {
+        let mut circuit = Circuit::blank();
+        let (sigma, msg) = Circuit::<E, P>::decrypt_inner(&signature, &ct, &circuit.params)
+            .map_err(|e| anyhow!("error decrypting ciphertext: {e}"))?;
+
+        circuit.gid = Some(E::pairing(E::G1::generator(), signature.clone()).0);
+        circuit.sigma = Some(sigma);
+        circuit.msg = Some(msg);
+        circuit.ciphertext = Some(ct);
+
+        Ok(Self { signature: Some(signature), circuit })
+    }
+
+    /// Returns a circuit without any assignments, see [`Circuit::blank`].
+    pub fn blank() -> Self {
+        Self { signature: None, circuit: Circuit::blank() }
+    }
+
+    /// Returns the decrypted plaintext, if the circuit was built with [`Self::new`].
+    pub fn plaintext(&self) -> Option<Plaintext<E::G1>> {
+        self.circuit.msg
+    }
+
+    /// Returns the public inputs of the circuit, i.e. those of [`Circuit`] followed by the plaintext.
+    pub fn get_public_inputs(
+        gid: &E::TargetField,
+        cipher: &Ciphertext<E::G1>,
+        msg: &Plaintext<E::G1>,
+    ) -> Vec<<E::G1 as CurveGroup>::BaseField>
+        where
+            E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+            E::TargetField: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+    {
+        Circuit::<E, P>::get_public_inputs(gid, cipher).into_iter().chain(iter::once(*msg)).collect()
+    }
+}
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> ConstraintSynthesizer<<E::G1 as CurveGroup>::BaseField> for DecryptionCircuit<E, P>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::TargetField: Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+          bls12::G2Var<P>: AllocVar<SecretKey<E>, <E::G1 as CurveGroup>::BaseField>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<<E::G1 as CurveGroup>::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        let gid = Fp12Var::<P::Fp12Config>::new_input(ns!(cs, "gid"), || self.circuit.gid.ok_or(SynthesisError::AssignmentMissing))?;
+        let ciphertext = self.circuit.ciphertext_var(cs.clone(), AllocationMode::Input)?;
+        let message = FpVar::<<E::G1 as CurveGroup>::BaseField>::new_input(ns!(cs, "plaintext"), || {
+            self.circuit.msg.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let signature = bls12::G2Var::<P>::new_witness(ns!(cs, "signature"), || {
+            self.signature.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let signature = Bls12PairingVar::<P>::prepare_g2(&signature)?;
+
+        // 1. Check the signature of the round, e(G, sk) = e(master, Q_id) = Gid
+        let generator = bls12::G1Var::<P>::constant(P::G1Parameters::GENERATOR.into());
+        Bls12PairingVar::<P>::pairing(Bls12PairingVar::<P>::prepare_g1(&generator)?, signature.clone())?
+            .enforce_equal(&gid)?;
+
+        // 2. Compute e(U, sk) = Gid^r, from which sigma is recovered
+        let r_gid = Bls12PairingVar::<P>::pairing(Bls12PairingVar::<P>::prepare_g1(&ciphertext.0)?, signature)?;
+
+        self.circuit.verify_encryption_with(cs.clone(), &message, &ciphertext, |_| Ok(r_gid))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ark_std::test_rng;
     use crate::poseidon;
+    use crate::predicate::{Bound, RangePredicate};
     use super::*;
 
     use ark_bls12_377::{G1Projective as ProjectiveEngine, Fq, Fr, Fq12, G1Affine, Bls12_377};
@@ -545,6 +1065,7 @@ mod tests {
     // use ark_groth16::Groth16;
     use ark_serialize::CanonicalSerialize;
 
+    use ark_relations::r1cs::SynthesisMode;
     use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
     use sha2::Digest;
 
@@ -577,4 +1098,182 @@ mod tests {
         let pt = TestCircuit::decrypt(&sk, &ct).unwrap();
         assert_eq!(pt, msg)
     }
+
+    /// Returns a random master key of the network and a random plaintext.
+    fn random_master_and_msg<R: Rng>(rng: &mut R) -> (PublicKey<Bls12_381>, Plaintext<ark_bls12_381::G1Projective>) {
+        let master = (ark_bls12_381::G1Projective::generator() * ark_bls12_381::Fr::rand(rng)).into_affine();
+        (master, ark_bls12_381::Fq::rand(rng))
+    }
+
+    /// Asserts that `real` and `blank` synthesize the same matrices, in Prove and Setup mode respectively.
+    fn assert_same_matrices<F: PrimeField, C: ConstraintSynthesizer<F>>(real: C, blank: C) {
+        let matrices = |circuit: C, mode: SynthesisMode| {
+            let cs = ConstraintSystem::new_ref();
+            cs.set_mode(mode);
+            circuit.generate_constraints(cs.clone()).unwrap();
+            cs.finalize();
+            cs.to_matrices().unwrap()
+        };
+
+        let real = matrices(real, SynthesisMode::Prove { construct_matrices: true });
+        let blank = matrices(blank, SynthesisMode::Setup);
+        assert_eq!(real, blank);
+    }
+
+    #[test]
+    fn test_blank_circuit() {
+        type TestCircuit = Circuit::<Bls12_381, ark_bls12_381::Parameters>;
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        let real = TestCircuit::new(master, b"round-1000", msg, &mut rng).unwrap();
+        assert_same_matrices(real, TestCircuit::blank());
+
+        let real = GeminiNativeCircuit(TestCircuit::new(master, b"round-1000", msg, &mut rng).unwrap());
+        assert_same_matrices(real, GeminiNativeCircuit::blank());
+    }
+
+    // Synthesizes the non-native circuit twice with its matrices, which takes minutes.
+    #[test]
+    #[ignore]
+    fn test_blank_nonnative_circuit() {
+        type TestCircuit = NonnativeCircuit::<ProjectiveEngine>;
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        let real = TestCircuit::new(master, b"round-1000", msg, &mut rng).unwrap();
+        assert_same_matrices(real, TestCircuit::blank());
+    }
+
+    #[test]
+    fn test_round_circuit() {
+        type TestCircuit = RoundCircuit::<Bls12_381, ark_bls12_381::Parameters>;
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        let circuit = TestCircuit::new(master, b"round-1000", msg, &mut rng).unwrap();
+        let ct = circuit.circuit.ciphertext.clone().unwrap();
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_instance_variables() - 1, TestCircuit::get_public_inputs(&ct).len());
+        let round = cs.num_constraints();
+
+        let cs = ConstraintSystem::new_ref();
+        Circuit::<Bls12_381, ark_bls12_381::Parameters>::new(master, b"round-1000", msg, &mut rng).unwrap()
+            .generate_constraints(cs.clone()).unwrap();
+        assert!(round < cs.num_constraints());
+
+        // A ciphertext to another round doesn't satisfy the circuit of this one.
+        let mut other = TestCircuit::new(master, b"round-1001", msg, &mut rng).unwrap();
+        other.gid = TestCircuit::new(master, b"round-1000", msg, &mut rng).unwrap().gid;
+        let cs = ConstraintSystem::new_ref();
+        other.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_digest_circuit() {
+        type TestCircuit = DigestCircuit::<Bls12_381, ark_bls12_381::Parameters>;
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        let circuit = DigestCircuit::<Bls12_381, ark_bls12_381::Parameters>(Circuit::new(master, b"round-1000", msg, &mut rng).unwrap());
+        let gid = circuit.0.gid.clone().unwrap();
+        let ct = circuit.0.ciphertext.clone().unwrap();
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_instance_variables(), 2);
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1], TestCircuit::digest(&gid, &ct));
+
+        cs.borrow_mut().unwrap().instance_assignment[1] += ark_bls12_381::Fq::one();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_plaintext_predicate() {
+        // The plaintext is a vote in {0, 1, 2}.
+        struct IsVote;
+
+        impl PlaintextPredicate<ark_bls12_381::Fq> for IsVote {
+            fn enforce(&self, _: ConstraintSystemRef<ark_bls12_381::Fq>, msg: &FpVar<ark_bls12_381::Fq>) -> Result<(), SynthesisError> {
+                let vote = msg * (msg - ark_bls12_381::Fq::one()) * (msg - ark_bls12_381::Fq::from(2u64));
+                vote.enforce_equal(&FpVar::zero())
+            }
+        }
+
+        let mut rng = test_rng();
+        let (master, _) = random_master_and_msg(&mut rng);
+        for (msg, is_vote) in [(1u64, true), (5, false)] {
+            let circuit = Circuit::<Bls12_381, ark_bls12_381::Parameters>::new(master, b"round-1000", msg.into(), &mut rng).unwrap()
+                .with_predicate(IsVote);
+            let cs = ConstraintSystem::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), is_vote);
+        }
+    }
+
+    #[test]
+    fn test_gemini_circuit_rejects_predicates() {
+        let mut rng = test_rng();
+        let (master, _) = random_master_and_msg(&mut rng);
+        let circuit = Circuit::<Bls12_381, ark_bls12_381::Parameters>::new(master, b"round-1000", 1u64.into(), &mut rng).unwrap()
+            .with_predicate(RangePredicate::new(Bound::Public(ark_bls12_381::Fq::zero()), Bound::Public(ark_bls12_381::Fq::from(10u64)), 8));
+        let circuit = GeminiNativeCircuit(circuit);
+        assert!(circuit.check_predicates().is_err());
+
+        let cs = ConstraintSystem::new_ref();
+        assert!(circuit.generate_constraints(cs.clone()).is_err());
+        assert_eq!(cs.num_instance_variables(), 1);
+    }
+
+    #[test]
+    fn test_round_number_circuit() {
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        let circuit = RoundNumberCircuit::new(master, 1000, msg, &mut rng).unwrap();
+        let ct = circuit.circuit.ciphertext.clone().unwrap();
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], RoundNumberCircuit::get_public_inputs(1000, &ct));
+
+        // A ciphertext to round 1000 doesn't satisfy the circuit for another round.
+        cs.borrow_mut().unwrap().instance_assignment[1] = ark_bls12_381::Fq::from(1001u64);
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_decryption_circuit() {
+        type TestCircuit = DecryptionCircuit::<Bls12_381, ark_bls12_381::Parameters>;
+        let mut rng = test_rng();
+        let sk = ark_bls12_381::Fr::rand(&mut rng);
+        let master = (ark_bls12_381::G1Projective::generator() * sk).into_affine();
+        let signature = (Bls12_381::hash(b"round-1000", H2C_DST).unwrap() * sk).into_affine();
+        let msg = ark_bls12_381::Fq::rand(&mut rng);
+
+        let ct = Circuit::<Bls12_381, ark_bls12_381::Parameters>::encrypt(&master, b"round-1000", &msg, &mut rng).unwrap();
+        let gid = Circuit::<Bls12_381, ark_bls12_381::Parameters>::gid(&master, b"round-1000").unwrap();
+        let circuit = TestCircuit::new(signature, ct.clone()).unwrap();
+        assert_eq!(circuit.plaintext(), Some(msg));
+
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], TestCircuit::get_public_inputs(&gid, &ct, &msg));
+
+        // Another plaintext isn't the decryption of the ciphertext. The circuit is synthesized
+        // again, as the constraint system caches the values of the linear combinations over it.
+        let mut circuit = TestCircuit::new(signature, ct.clone()).unwrap();
+        circuit.circuit.msg = Some(msg + ark_bls12_381::Fq::one());
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // The signature of another round doesn't decrypt it.
+        let other = (Bls12_381::hash(b"round-1001", H2C_DST).unwrap() * sk).into_affine();
+        assert!(TestCircuit::new(other, ct).is_err());
+    }
 }
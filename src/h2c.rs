@@ -0,0 +1,226 @@
+//! In-circuit hash-to-curve to BLS12-381 G2 with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite of
+//! RFC 9380, the one drand uses to derive the point of a round from its identity.
+//!
+//! The gadgets are over the base field of BLS12-381, so that all field arithmetic is native.
+
+use ark_bls12_381::{Fq, Fq2, Parameters};
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use ark_ec::bls12::Bls12Parameters;
+use ark_ff::{BigInteger, BitIteratorBE, Field, MontFp, One, PrimeField, Zero};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::fp2::Fp2Var;
+use ark_r1cs_std::groups::bls12::G2Var;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::SynthesisError;
+
+type Fq2Var = Fp2Var<ark_bls12_381::Fq2Config>;
+
+/// Expands `msg` into `len_in_bytes` pseudo-random bytes with `expand_message_xmd` (RFC 9380, 5.3.1)
+/// instantiated with SHA-256.
+pub fn expand_message_xmd<F: PrimeField>(
+    msg: &[UInt8<F>],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    let ell = (len_in_bytes + 31) / 32;
+    assert!(ell <= 255 && len_in_bytes <= 0xffff && dst.len() <= 255);
+    let dst_prime = UInt8::constant_vec(&[dst, &[dst.len() as u8]].concat());
+
+    let mut b0_input = UInt8::constant_vec(&[0; 64]);
+    b0_input.extend_from_slice(msg);
+    b0_input.extend(UInt8::constant_vec(&(len_in_bytes as u16).to_be_bytes()));
+    b0_input.push(UInt8::constant(0));
+    b0_input.extend_from_slice(&dst_prime);
+    let b0 = Sha256Gadget::digest(&b0_input)?.0;
+
+    let mut bi = Sha256Gadget::digest(&[&b0[..], &[UInt8::constant(1)], &dst_prime].concat())?.0;
+    let mut uniform_bytes = bi.clone();
+    for i in 2..=ell {
+        let mut input = b0.iter().zip(&bi).map(|(x, y)| x.xor(y)).collect::<Result<Vec<_>, _>>()?;
+        input.push(UInt8::constant(i as u8));
+        input.extend_from_slice(&dst_prime);
+        bi = Sha256Gadget::digest(&input)?.0;
+        uniform_bytes.extend_from_slice(&bi);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+
+    Ok(uniform_bytes)
+}
+
+/// Hashes `msg` to two elements of `Fq2` (RFC 9380, 5.2), with `L = 64` bytes per coordinate.
+pub fn hash_to_field(msg: &[UInt8<Fq>], dst: &[u8]) -> Result<[Fq2Var; 2], SynthesisError> {
+    const L: usize = 64;
+    let bytes = expand_message_xmd(msg, dst, 2 * 2 * L)?;
+    // Big-endian integers are reduced modulo `p` by recombining their bits in `Fq` itself.
+    let elements = bytes.chunks(L).map(|chunk| {
+        let mut acc = FpVar::<Fq>::zero();
+        let mut coeff = Fq::one();
+        for bit in chunk.iter().rev().flat_map(|byte| byte.to_bits_le().unwrap()) {
+            acc += FpVar::from(bit) * coeff;
+            coeff.double_in_place();
+        }
+        acc
+    }).collect::<Vec<_>>();
+
+    Ok([
+        Fq2Var::new(elements[0].clone(), elements[1].clone()),
+        Fq2Var::new(elements[2].clone(), elements[3].clone()),
+    ])
+}
+
+/// `A'` and `B'` of the curve `E2'` 3-isogenous to G2, and the non-square `Z` of the SSWU map.
+const ISO_A: Fq2 = Fq2::new(Fq::ZERO, MontFp!("240"));
+const ISO_B: Fq2 = Fq2::new(MontFp!("1012"), MontFp!("1012"));
+const SSWU_Z: Fq2 = Fq2::new(MontFp!("-2"), MontFp!("-1"));
+
+/// Returns `sgn0` of `x` (RFC 9380, 4.1).
+fn sgn0(x: &Fq2Var) -> Result<Boolean<Fq>, SynthesisError> {
+    let sign_0 = x.c0.to_bits_le()?[0].clone();
+    let zero_0 = x.c0.is_zero()?;
+    let sign_1 = x.c1.to_bits_le()?[0].clone();
+    sign_0.or(&zero_0.and(&sign_1)?)
+}
+
+/// Maps `u` to an affine point of `E2'` with the simplified SWU map (RFC 9380, 6.6.2).
+fn map_to_curve_sswu(u: &Fq2Var) -> Result<(Fq2Var, Fq2Var), SynthesisError> {
+    let cs = u.cs();
+    let zu2 = u.square()? * SSWU_Z;
+    // `Z^2 u^4 + Z u^2` only vanishes for `u = 0` or `Z u^2 = -1`, which happen with negligible
+    // probability, hence the exceptional case of the map is omitted and the inverse enforced.
+    let tv1 = (zu2.square()? + &zu2).inverse()?;
+    let x1 = (tv1 + Fq2::one()) * (-ISO_B * ISO_A.inverse().unwrap());
+    let x2 = &zu2 * &x1;
+    let gx = |x: &Fq2Var| -> Result<Fq2Var, SynthesisError> {
+        Ok(x.square()? * x + x * ISO_A + ISO_B)
+    };
+    let (gx1, gx2) = (gx(&x1)?, gx(&x2)?);
+
+    // As `Z` is a non-square, exactly one of `gx1` and `gx2 = Z^3 u^6 gx1` is a square, whose root
+    // is hence a witness and its sign fixed to that of `u`.
+    let is_square = Boolean::new_witness(cs.clone(), || Ok(gx1.value()?.sqrt().is_some()))?;
+    let y = Fq2Var::new_witness(cs.clone(), || {
+        let y = gx1.value()?.sqrt().or(gx2.value()?.sqrt()).ok_or(SynthesisError::Unsatisfiable)?;
+        Ok(if sgn0_native(&y) == sgn0_native(&u.value()?) { y } else { -y })
+    })?;
+    y.square()?.enforce_equal(&is_square.select(&gx1, &gx2)?)?;
+    sgn0(&y)?.enforce_equal(&sgn0(u)?)?;
+
+    Ok((is_square.select(&x1, &x2)?, y))
+}
+
+fn sgn0_native(x: &Fq2) -> bool {
+    let sign_0 = x.c0.into_bigint().is_odd();
+    sign_0 || (x.c0.is_zero() && x.c1.into_bigint().is_odd())
+}
+
+/// Coefficients of the 3-isogeny map from `E2'` to G2 (RFC 9380, E.3), in ascending degree.
+const ISO_X_NUM: [Fq2; 4] = [
+    Fq2::new(MontFp!("889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542"), MontFp!("889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542")),
+    Fq2::new(Fq::ZERO, MontFp!("2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706522")),
+    Fq2::new(MontFp!("2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706526"), MontFp!("1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853261")),
+    Fq2::new(MontFp!("3557697382419259905260257622876359250272784728834673675850718343221361467102966990615722337003569479144794908942033"), Fq::ZERO),
+];
+const ISO_X_DEN: [Fq2; 3] = [
+    Fq2::new(Fq::ZERO, MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559715")),
+    Fq2::new(MontFp!("12"), MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559775")),
+    Fq2::new(MontFp!("1"), Fq::ZERO),
+];
+const ISO_Y_NUM: [Fq2; 4] = [
+    Fq2::new(MontFp!("3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558"), MontFp!("3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558")),
+    Fq2::new(Fq::ZERO, MontFp!("889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235518")),
+    Fq2::new(MontFp!("2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706524"), MontFp!("1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853263")),
+    Fq2::new(MontFp!("2816510427748580758331037284777117739799287910327449993381818688383577828123182200904113516794492504322962636245776"), Fq::ZERO),
+];
+const ISO_Y_DEN: [Fq2; 4] = [
+    Fq2::new(MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355"), MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355")),
+    Fq2::new(Fq::ZERO, MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559571")),
+    Fq2::new(MontFp!("18"), MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559769")),
+    Fq2::new(MontFp!("1"), Fq::ZERO),
+];
+
+/// Maps an affine point of `E2'` to G2 with the 3-isogeny, as a projective point to avoid
+/// inverting the denominators.
+fn iso_map(x: &Fq2Var, y: &Fq2Var) -> Result<G2Var<Parameters>, SynthesisError> {
+    let powers = [Fq2Var::one(), x.clone(), x.square()?, x.square()? * x];
+    let eval = |coeffs: &[Fq2]| {
+        coeffs.iter().zip(&powers).fold(Fq2Var::zero(), |acc, (c, p)| acc + p * *c)
+    };
+    let (x_num, x_den, y_num, y_den) = (eval(&ISO_X_NUM), eval(&ISO_X_DEN), eval(&ISO_Y_NUM), eval(&ISO_Y_DEN));
+
+    Ok(G2Var::<Parameters>::new(&x_num * &y_den, y * &y_num * &x_den, x_den * y_den))
+}
+
+/// Coefficients of the untwist-Frobenius-twist endomorphism `psi(x, y) = (PSI_X x^p, PSI_Y y^p)`
+/// of G2, i.e. `1/(u+1)^((p-1)/3)` and `1/(u+1)^((p-1)/2)`, and of `psi^2(x, y) = (PSI_2_X x, -y)`.
+const PSI_X: Fq2 = Fq2::new(Fq::ZERO, MontFp!("4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939437"));
+const PSI_Y: Fq2 = Fq2::new(
+    MontFp!("2973677408986561043442465346520108879172042883009249989176415018091420807192182638567116318576472649347015917690530"),
+    MontFp!("1028732146235106349975324479215795277384839936929757896155643118032610843298655225875571310552543014690878354869257"),
+);
+const PSI_2_X: Fq2 = Fq2::new(MontFp!("4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939436"), Fq::ZERO);
+
+fn psi(p: &G2Var<Parameters>) -> Result<G2Var<Parameters>, SynthesisError> {
+    Ok(G2Var::<Parameters>::new(
+        p.x.frobenius_map(1)? * PSI_X,
+        p.y.frobenius_map(1)? * PSI_Y,
+        p.z.frobenius_map(1)?,
+    ))
+}
+
+fn psi2(p: &G2Var<Parameters>) -> Result<G2Var<Parameters>, SynthesisError> {
+    Ok(G2Var::<Parameters>::new(&p.x * PSI_2_X, p.y.negate()?, p.z.clone()))
+}
+
+/// Returns `[x]p` for the BLS parameter `x`, whose absolute value has a Hamming weight of 6.
+fn mul_by_x(p: &G2Var<Parameters>) -> Result<G2Var<Parameters>, SynthesisError> {
+    let mut res = p.clone();
+    for bit in BitIteratorBE::without_leading_zeros(Parameters::X).skip(1) {
+        res.double_in_place()?;
+        if bit {
+            res += p;
+        }
+    }
+    // `x` is negative for BLS12-381.
+    res.negate()
+}
+
+/// Clears the cofactor of `p` by multiplying it with `h_eff` (RFC 9380, G.3).
+fn clear_cofactor(p: &G2Var<Parameters>) -> Result<G2Var<Parameters>, SynthesisError> {
+    let t1 = mul_by_x(p)?;
+    let t2 = psi(p)?;
+    let t3 = psi2(&p.double()?)? - &t2;
+    let t2 = mul_by_x(&(&t1 + &t2))?;
+    Ok(t3 + t2 - t1 - p)
+}
+
+/// Hashes `msg` to G2 with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite of RFC 9380 and `dst`.
+pub fn hash_to_g2(msg: &[UInt8<Fq>], dst: &[u8]) -> Result<G2Var<Parameters>, SynthesisError> {
+    let [u0, u1] = hash_to_field(msg, dst)?;
+    let (x0, y0) = map_to_curve_sswu(&u0)?;
+    let (x1, y1) = map_to_curve_sswu(&u1)?;
+    let q = iso_map(&x0, &y0)? + iso_map(&x1, &y1)?;
+    clear_cofactor(&q)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Hash2Curve;
+    use crate::H2C_DST;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::CurveGroup;
+    use ark_relations::r1cs::ConstraintSystem;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn test_hash_to_g2() {
+        for round in [1, 1000] {
+            let id = Sha256::digest(&(round as u64).to_be_bytes()).to_vec();
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let id_var = UInt8::new_witness_vec(cs.clone(), &id).unwrap();
+            let q = hash_to_g2(&id_var, H2C_DST).unwrap();
+            assert_eq!(q.value().unwrap().into_affine(), Bls12_381::hash(&id, H2C_DST).unwrap());
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+}
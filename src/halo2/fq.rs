@@ -0,0 +1,732 @@
+//! Non-native arithmetic over the base field of BLS12-381.
+//!
+//! Elements are represented by four 96-bit limbs. Sums of products are reduced lazily: the limb
+//! products of all terms are accumulated into seven positions, then a single reduction row
+//! witnesses the quotient `Q` and remainder `r` of the accumulated value and checks, position by
+//! position with range-checked carries, that `sum + SHIFT = Q * q + r` holds over the integers.
+//! Since all positions are far below the native modulus, no equation can wrap around.
+
+use ark_ff::{BigInteger, PrimeField};
+use halo2_proofs::circuit::{AssignedCell, Layouter, Region};
+use halo2_proofs::pasta::group::ff;
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector};
+use halo2_proofs::poly::Rotation;
+use lazy_static::lazy_static;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use super::range::RangeConfig;
+
+/// Number of bits of a limb.
+pub(crate) const LIMB_BITS: usize = 96;
+/// Number of limbs of an element.
+pub(crate) const NUM_LIMBS: usize = 4;
+/// Number of limbs of the quotient of a reduction.
+const NUM_QUOTIENT_LIMBS: usize = 5;
+/// Number of positions of a product of two elements.
+const NUM_POSITIONS: usize = 2 * NUM_LIMBS - 1;
+/// Number of positions checked by a reduction, i.e. of a product of the quotient and modulus.
+const NUM_REDUCED_POSITIONS: usize = NUM_QUOTIENT_LIMBS + NUM_LIMBS - 1;
+/// Number of bits of the (offset) carries between positions.
+const CARRY_BITS: usize = 112;
+/// Bound on the sum of the absolute coefficients of a reduction, which keeps the shift, the
+/// quotient and the carries within their ranges.
+const MAX_COEFFICIENTS: u64 = 1 << 10;
+/// Number of bits decomposed per row by [`FqChip::to_bits`].
+const BITS_PER_ROW: usize = 12;
+
+lazy_static! {
+    /// Modulus of the native field.
+    static ref NATIVE_MODULUS: BigUint = fp_to_big(&-Fp::one()) + 1u32;
+    /// Modulus `q` of BLS12-381 base field.
+    pub(crate) static ref MODULUS: BigUint = BigUint::from_bytes_le(&ark_bls12_381::Fq::MODULUS.to_bytes_le());
+    /// Multiple of `q` added to every reduction so that negative coefficients can't make the sum negative.
+    static ref SHIFT: BigUint = {
+        let bound = BigUint::one() << (2 * NUM_LIMBS * LIMB_BITS + 11);
+        bound.div_ceil(&MODULUS) * &*MODULUS
+    };
+    /// Offset that makes the signed carries non-negative.
+    static ref CARRY_OFFSET: BigUint = BigUint::one() << (CARRY_BITS - 1);
+}
+
+/// Converts a native field element into an integer.
+pub(crate) fn fp_to_big(fe: &Fp) -> BigUint {
+    BigUint::from_bytes_le(&ff::PrimeField::to_repr(fe))
+}
+
+/// Converts an integer into a native field element, reducing it modulo the native modulus.
+pub(crate) fn big_to_fp(n: &BigUint) -> Fp {
+    let mut bytes = (n % &*NATIVE_MODULUS).to_bytes_le();
+    bytes.resize(32, 0);
+    ff::PrimeField::from_repr(bytes.try_into().unwrap()).unwrap()
+}
+
+/// Converts a signed integer into a native field element.
+fn bigint_to_fp(n: &BigInt) -> Fp {
+    let fe = big_to_fp(n.magnitude());
+    if n.sign() == Sign::Minus { -fe } else { fe }
+}
+
+/// Converts an element of BLS12-381 base field into an integer.
+pub(crate) fn fq_to_big(fe: &ark_bls12_381::Fq) -> BigUint {
+    BigUint::from_bytes_le(&fe.into_bigint().to_bytes_le())
+}
+
+/// Converts an integer into an element of BLS12-381 base field, reducing it modulo `q`.
+pub(crate) fn big_to_fq(n: &BigUint) -> ark_bls12_381::Fq {
+    ark_bls12_381::Fq::from_le_bytes_mod_order(&n.to_bytes_le())
+}
+
+/// Splits `n` into `num_limbs` limbs of `LIMB_BITS` bits.
+pub(crate) fn to_limbs(n: &BigUint, num_limbs: usize) -> Vec<BigUint> {
+    let mask = (BigUint::one() << LIMB_BITS) - 1u32;
+    (0..num_limbs).map(|i| (n >> (i * LIMB_BITS)) & &mask).collect()
+}
+
+/// Returns the limbs of `fe`, i.e. how it is represented in the circuit and in the public inputs.
+pub fn fq_limbs(fe: &ark_bls12_381::Fq) -> [Fp; NUM_LIMBS] {
+    to_limbs(&fq_to_big(fe), NUM_LIMBS).iter().map(big_to_fp).collect::<Vec<_>>().try_into().unwrap()
+}
+
+/// Converts a native field element into an element of BLS12-381 base field. It's the identity on
+/// integers, as the native modulus is smaller than `q`.
+pub fn fp_to_fq(fe: &Fp) -> ark_bls12_381::Fq {
+    big_to_fq(&fp_to_big(fe))
+}
+
+/// An element of BLS12-381 base field, whose limbs are less than `2^LIMB_BITS`, but which isn't
+/// necessarily reduced modulo `q`.
+#[derive(Clone, Debug)]
+pub struct AssignedFq {
+    pub(crate) limbs: [AssignedCell<Fp, Fp>; NUM_LIMBS],
+}
+
+impl AssignedFq {
+    /// Returns the integer represented by the limbs, if assigned.
+    pub fn value(&self) -> Option<BigUint> {
+        self.limbs.iter().rev().try_fold(BigUint::zero(), |acc, limb| {
+            limb.value().map(|l| (acc << LIMB_BITS) + fp_to_big(l))
+        })
+    }
+}
+
+/// Which modulus [`FqChip`] compares elements against to check that they are canonical.
+#[derive(Clone, Copy, Debug)]
+enum Bound {
+    /// BLS12-381 base field modulus.
+    Fq,
+    /// Native field modulus.
+    Fp,
+}
+
+#[derive(Clone, Debug)]
+pub struct FqConfig {
+    advice: [Column<Advice>; 16],
+    coef: Column<Fixed>,
+    lin: Column<Fixed>,
+    q_first: Selector,
+    q_term: Selector,
+    q_reduce: Selector,
+    q_reduce_zero: Selector,
+    q_lt_q: Selector,
+    q_lt_p: Selector,
+    q_select: Selector,
+    q_bits: Selector,
+    pub(crate) range: RangeConfig,
+}
+
+impl FqConfig {
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        advice: [Column<Advice>; 16],
+        coef: Column<Fixed>,
+        lin: Column<Fixed>,
+    ) -> Self {
+        let range = RangeConfig::configure(meta, &advice);
+        let q_first = meta.selector();
+        let q_term = meta.selector();
+        let q_reduce = meta.selector();
+        let q_reduce_zero = meta.selector();
+        let q_lt_q = meta.selector();
+        let q_lt_p = meta.selector();
+        let q_select = meta.selector();
+        let q_bits = meta.selector();
+
+        let constant = |n: &BigUint| Expression::Constant(big_to_fp(n));
+        let modulus_limbs = to_limbs(&MODULUS, NUM_LIMBS);
+
+        meta.create_gate("accumulator initialization", |meta| {
+            let q_first = meta.query_selector(q_first);
+            (0..NUM_POSITIONS)
+                .map(|k| q_first.clone() * meta.query_advice(advice[8 + k], Rotation::cur()))
+                .collect::<Vec<_>>()
+        });
+
+        // acc' = acc + coef * a * b + lin * a, over the positions of the limb products.
+        meta.create_gate("product term", |meta| {
+            let q_term = meta.query_selector(q_term);
+            let coef = meta.query_fixed(coef, Rotation::cur());
+            let lin = meta.query_fixed(lin, Rotation::cur());
+            let a = (0..NUM_LIMBS).map(|i| meta.query_advice(advice[i], Rotation::cur())).collect::<Vec<_>>();
+            let b = (0..NUM_LIMBS).map(|i| meta.query_advice(advice[4 + i], Rotation::cur())).collect::<Vec<_>>();
+
+            (0..NUM_POSITIONS).map(|k| {
+                let acc = meta.query_advice(advice[8 + k], Rotation::cur());
+                let next = meta.query_advice(advice[8 + k], Rotation::next());
+                let product = (0..NUM_LIMBS)
+                    .filter(|i| k >= *i && k - i < NUM_LIMBS)
+                    .map(|i| a[i].clone() * b[k - i].clone())
+                    .reduce(|x, y| x + y)
+                    .unwrap();
+                let mut term = coef.clone() * product;
+                if k < NUM_LIMBS {
+                    term = term + lin.clone() * a[k].clone();
+                }
+                q_term.clone() * (next - acc - term)
+            }).collect::<Vec<_>>()
+        });
+
+        // acc + SHIFT = Q * q + r, position by position: the reduction row holds the accumulator,
+        // `r` and the first limbs of `Q`, the next row the last limb of `Q` and the carries.
+        let reduction = |meta: &mut halo2_proofs::plonk::VirtualCells<'_, Fp>, with_remainder: bool| {
+            let shift = to_limbs(&SHIFT, NUM_REDUCED_POSITIONS);
+            let top_shift = &*SHIFT >> ((NUM_REDUCED_POSITIONS - 1) * LIMB_BITS);
+            let quotient = (0..NUM_QUOTIENT_LIMBS).map(|i| if i < 4 {
+                meta.query_advice(advice[4 + i], Rotation::cur())
+            } else {
+                meta.query_advice(advice[0], Rotation::next())
+            }).collect::<Vec<_>>();
+            let carries = (0..NUM_REDUCED_POSITIONS - 1)
+                .map(|k| meta.query_advice(advice[1 + k], Rotation::next()) - constant(&CARRY_OFFSET))
+                .collect::<Vec<_>>();
+
+            (0..NUM_REDUCED_POSITIONS).map(|k| {
+                let mut value = if k == NUM_REDUCED_POSITIONS - 1 { constant(&top_shift) } else { constant(&shift[k]) };
+                if k < NUM_POSITIONS {
+                    value = value + meta.query_advice(advice[8 + k], Rotation::cur());
+                }
+                for i in 0..NUM_QUOTIENT_LIMBS {
+                    if k >= i && k - i < NUM_LIMBS {
+                        value = value - quotient[i].clone() * constant(&modulus_limbs[k - i]);
+                    }
+                }
+                if with_remainder && k < NUM_LIMBS {
+                    value = value - meta.query_advice(advice[k], Rotation::cur());
+                }
+                if k > 0 {
+                    value = value + carries[k - 1].clone();
+                }
+                if k < NUM_REDUCED_POSITIONS - 1 {
+                    value = value - carries[k].clone() * constant(&(BigUint::one() << LIMB_BITS));
+                }
+                value
+            }).collect::<Vec<_>>()
+        };
+        meta.create_gate("reduction", |meta| {
+            let q_reduce = meta.query_selector(q_reduce);
+            reduction(meta, true).into_iter().map(|e| q_reduce.clone() * e).collect::<Vec<_>>()
+        });
+        meta.create_gate("reduction to zero", |meta| {
+            let q_reduce_zero = meta.query_selector(q_reduce_zero);
+            reduction(meta, false).into_iter().map(|e| q_reduce_zero.clone() * e).collect::<Vec<_>>()
+        });
+
+        // a + d = m, with d computed limb by limb with borrows, shows that a <= m.
+        for (selector, bound) in [(q_lt_q, Bound::Fq), (q_lt_p, Bound::Fp)] {
+            meta.create_gate("canonical", |meta| {
+                let selector = meta.query_selector(selector);
+                let m = to_limbs(&(bound.modulus() - 1u32), NUM_LIMBS);
+                let a = (0..NUM_LIMBS).map(|i| meta.query_advice(advice[i], Rotation::cur())).collect::<Vec<_>>();
+                let d = (0..NUM_LIMBS).map(|i| meta.query_advice(advice[4 + i], Rotation::cur())).collect::<Vec<_>>();
+                let borrows = (0..NUM_LIMBS - 1).map(|i| meta.query_advice(advice[8 + i], Rotation::cur())).collect::<Vec<_>>();
+
+                let mut constraints = (0..NUM_LIMBS).map(|i| {
+                    let mut value = constant(&m[i]) - a[i].clone() - d[i].clone();
+                    if i > 0 {
+                        value = value - borrows[i - 1].clone();
+                    }
+                    if i < NUM_LIMBS - 1 {
+                        value = value + borrows[i].clone() * constant(&(BigUint::one() << LIMB_BITS));
+                    }
+                    value
+                }).collect::<Vec<_>>();
+                constraints.extend(borrows.iter().map(|b| b.clone() * (Expression::Constant(Fp::one()) - b.clone())));
+                // Decompositions of native elements also check that the limbs compose to it.
+                if let Bound::Fp = bound {
+                    let native = meta.query_advice(advice[11], Rotation::cur());
+                    let composed = a.iter().rev().fold(Expression::Constant(Fp::zero()), |acc, limb| {
+                        acc * constant(&(BigUint::one() << LIMB_BITS)) + limb.clone()
+                    });
+                    constraints.push(native - composed);
+                }
+                constraints.into_iter().map(|c| selector.clone() * c).collect::<Vec<_>>()
+            });
+        }
+
+        meta.create_gate("select", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let bit = meta.query_advice(advice[0], Rotation::cur());
+            (0..NUM_LIMBS).map(|i| {
+                let a = meta.query_advice(advice[1 + i], Rotation::cur());
+                let b = meta.query_advice(advice[5 + i], Rotation::cur());
+                let out = meta.query_advice(advice[9 + i], Rotation::cur());
+                q_select.clone() * (out - b.clone() - bit.clone() * (a - b))
+            }).collect::<Vec<_>>()
+        });
+
+        // z = bits + 2^BITS_PER_ROW * z', i.e. a running sum that ends at zero.
+        meta.create_gate("bits", |meta| {
+            let q_bits = meta.query_selector(q_bits);
+            let z = meta.query_advice(advice[0], Rotation::cur());
+            let next = meta.query_advice(advice[0], Rotation::next());
+            let bits = (0..BITS_PER_ROW).map(|i| meta.query_advice(advice[1 + i], Rotation::cur())).collect::<Vec<_>>();
+            let composed = bits.iter().rev().fold(next, |acc, bit| acc * Expression::Constant(Fp::from(2)) + bit.clone());
+
+            bits.iter()
+                .map(|b| b.clone() * (Expression::Constant(Fp::one()) - b.clone()))
+                .chain([z - composed])
+                .map(|c| q_bits.clone() * c)
+                .collect::<Vec<_>>()
+        });
+
+        Self { advice, coef, lin, q_first, q_term, q_reduce, q_reduce_zero, q_lt_q, q_lt_p, q_select, q_bits, range }
+    }
+}
+
+impl Bound {
+    fn modulus(&self) -> &'static BigUint {
+        match self {
+            Bound::Fq => &MODULUS,
+            Bound::Fp => &NATIVE_MODULUS,
+        }
+    }
+}
+
+/// Chip for the arithmetic of BLS12-381 base field.
+#[derive(Clone, Debug)]
+pub struct FqChip {
+    config: FqConfig,
+}
+
+impl FqChip {
+    pub fn new(config: FqConfig) -> Self {
+        Self { config }
+    }
+
+    /// Assigns `value` as a canonical element, i.e. whose limbs are those of [`fq_limbs`].
+    pub fn witness(&self, layouter: &mut impl Layouter<Fp>, value: Option<ark_bls12_381::Fq>) -> Result<AssignedFq, Error> {
+        let advice = self.config.advice;
+        layouter.assign_region(|| "witness", |mut region| {
+            let limbs = (0..NUM_LIMBS).map(|i| {
+                let limb = value.as_ref().map(|v| fq_limbs(v)[i]);
+                region.assign_advice(|| "limb", advice[i], 0, || limb.ok_or(Error::Synthesis))
+            }).collect::<Result<Vec<_>, _>>()?;
+            let assigned = AssignedFq { limbs: limbs.try_into().unwrap() };
+
+            let offset = self.assign_lt(&mut region, 0, &assigned, Bound::Fq)?;
+            self.range_check_limbs(&mut region, offset, &assigned.limbs)?;
+            Ok(assigned)
+        })
+    }
+
+    /// Assigns the constant `value`.
+    pub fn constant(&self, layouter: &mut impl Layouter<Fp>, value: &ark_bls12_381::Fq) -> Result<AssignedFq, Error> {
+        let advice = self.config.advice;
+        layouter.assign_region(|| "constant", |mut region| {
+            let limbs = fq_limbs(value).iter().enumerate()
+                .map(|(i, limb)| region.assign_advice_from_constant(|| "limb", advice[i], 0, *limb))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(AssignedFq { limbs: limbs.try_into().unwrap() })
+        })
+    }
+
+    /// Assigns the element whose limbs are public inputs `index * NUM_LIMBS..(index + 1) * NUM_LIMBS`.
+    ///
+    /// The limbs aren't range-checked, since the verifier computes them.
+    pub fn from_instance(&self, layouter: &mut impl Layouter<Fp>, instance: Column<Instance>, index: usize) -> Result<AssignedFq, Error> {
+        let advice = self.config.advice;
+        layouter.assign_region(|| "public input", |mut region| {
+            let limbs = (0..NUM_LIMBS)
+                .map(|i| region.assign_advice_from_instance(|| "limb", instance, index * NUM_LIMBS + i, advice[i], 0))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(AssignedFq { limbs: limbs.try_into().unwrap() })
+        })
+    }
+
+    /// Computes `sum(c * a * b) + sum(c * a)` for the given products and linear terms.
+    pub fn inner_product(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        products: &[(i64, &AssignedFq, &AssignedFq)],
+        linear: &[(i64, &AssignedFq)],
+    ) -> Result<AssignedFq, Error> {
+        self.reduce(layouter, products, linear, true).map(Option::unwrap)
+    }
+
+    /// Constrains `sum(c * a * b) + sum(c * a)` to be zero.
+    pub fn assert_zero(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        products: &[(i64, &AssignedFq, &AssignedFq)],
+        linear: &[(i64, &AssignedFq)],
+    ) -> Result<(), Error> {
+        self.reduce(layouter, products, linear, false).map(|_| ())
+    }
+
+    pub fn mul(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq, b: &AssignedFq) -> Result<AssignedFq, Error> {
+        self.inner_product(layouter, &[(1, a, b)], &[])
+    }
+
+    pub fn add(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq, b: &AssignedFq) -> Result<AssignedFq, Error> {
+        self.inner_product(layouter, &[], &[(1, a), (1, b)])
+    }
+
+    pub fn sub(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq, b: &AssignedFq) -> Result<AssignedFq, Error> {
+        self.inner_product(layouter, &[], &[(1, a), (-1, b)])
+    }
+
+    /// Constrains `a` and `b` to be equal modulo `q`.
+    pub fn assert_equal(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq, b: &AssignedFq) -> Result<(), Error> {
+        self.assert_zero(layouter, &[], &[(1, a), (-1, b)])
+    }
+
+    /// Reduces `a` to its canonical representation, so that it can be hashed.
+    pub fn canonical(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq) -> Result<AssignedFq, Error> {
+        let reduced = self.inner_product(layouter, &[], &[(1, a)])?;
+        let advice = self.config.advice;
+        layouter.assign_region(|| "canonical", |mut region| {
+            for (limb, column) in reduced.limbs.iter().zip(advice) {
+                limb.copy_advice(|| "limb", &mut region, column, 0)?;
+            }
+            self.assign_lt(&mut region, 0, &reduced, Bound::Fq)?;
+            Ok(())
+        })?;
+
+        Ok(reduced)
+    }
+
+    /// Converts a native element into an element of BLS12-381 base field, i.e. decomposes it into
+    /// the limbs of its (unique) integer representative.
+    pub fn from_native(&self, layouter: &mut impl Layouter<Fp>, native: &AssignedCell<Fp, Fp>) -> Result<AssignedFq, Error> {
+        let advice = self.config.advice;
+        layouter.assign_region(|| "from native", |mut region| {
+            native.copy_advice(|| "native", &mut region, advice[11], 0)?;
+            let value = native.value().map(fp_to_big);
+            let limbs = (0..NUM_LIMBS).map(|i| {
+                let limb = value.as_ref().map(|v| big_to_fp(&to_limbs(v, NUM_LIMBS)[i]));
+                region.assign_advice(|| "limb", advice[i], 0, || limb.ok_or(Error::Synthesis))
+            }).collect::<Result<Vec<_>, _>>()?;
+            let assigned = AssignedFq { limbs: limbs.try_into().unwrap() };
+
+            let offset = self.assign_lt(&mut region, 0, &assigned, Bound::Fp)?;
+            self.range_check_limbs(&mut region, offset, &assigned.limbs)?;
+            Ok(assigned)
+        })
+    }
+
+    /// Returns the `num_bits` little-endian bits of `a`, which must be less than `2^num_bits`.
+    pub fn to_bits(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq, num_bits: usize) -> Result<Vec<AssignedCell<Fp, Fp>>, Error> {
+        let advice = self.config.advice;
+        let mut bits = vec![];
+        for (i, limb) in a.limbs.iter().enumerate().take(num_bits.div_ceil(LIMB_BITS)) {
+            let rows = (num_bits - i * LIMB_BITS).min(LIMB_BITS).div_ceil(BITS_PER_ROW);
+            let limb_bits = layouter.assign_region(|| "bits", |mut region| {
+                let mut z = limb.copy_advice(|| "z", &mut region, advice[0], 0)?;
+                let mut limb_bits = vec![];
+                for row in 0..rows {
+                    self.config.q_bits.enable(&mut region, row)?;
+                    let value = z.value().map(fp_to_big);
+                    for j in 0..BITS_PER_ROW {
+                        let bit = value.as_ref().map(|v| Fp::from(v.bit(j as u64)));
+                        limb_bits.push(region.assign_advice(|| "bit", advice[1 + j], row, || bit.ok_or(Error::Synthesis))?);
+                    }
+                    let next = value.map(|v| big_to_fp(&(v >> BITS_PER_ROW)));
+                    z = region.assign_advice(|| "z", advice[0], row + 1, || next.ok_or(Error::Synthesis))?;
+                }
+                region.constrain_constant(z.cell(), Fp::zero())?;
+                Ok(limb_bits)
+            })?;
+            bits.extend(limb_bits);
+        }
+        bits.truncate(num_bits);
+
+        Ok(bits)
+    }
+
+    /// Returns `a` if `bit` is set and `b` otherwise, `bit` must be boolean.
+    pub fn select(&self, layouter: &mut impl Layouter<Fp>, bit: &AssignedCell<Fp, Fp>, a: &AssignedFq, b: &AssignedFq) -> Result<AssignedFq, Error> {
+        let advice = self.config.advice;
+        layouter.assign_region(|| "select", |mut region| {
+            self.config.q_select.enable(&mut region, 0)?;
+            bit.copy_advice(|| "bit", &mut region, advice[0], 0)?;
+            let limbs = (0..NUM_LIMBS).map(|i| {
+                a.limbs[i].copy_advice(|| "a", &mut region, advice[1 + i], 0)?;
+                b.limbs[i].copy_advice(|| "b", &mut region, advice[5 + i], 0)?;
+                let out = bit.value().and_then(|bit| if *bit == Fp::one() { a.limbs[i].value() } else { b.limbs[i].value() });
+                region.assign_advice(|| "out", advice[9 + i], 0, || out.copied().ok_or(Error::Synthesis))
+            }).collect::<Result<Vec<_>, _>>()?;
+            Ok(AssignedFq { limbs: limbs.try_into().unwrap() })
+        })
+    }
+
+    fn reduce(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        products: &[(i64, &AssignedFq, &AssignedFq)],
+        linear: &[(i64, &AssignedFq)],
+        with_remainder: bool,
+    ) -> Result<Option<AssignedFq>, Error> {
+        assert!(products.iter().map(|(c, _, _)| c.unsigned_abs()).sum::<u64>() <= MAX_COEFFICIENTS);
+        assert!(linear.iter().map(|(c, _)| c.unsigned_abs()).sum::<u64>() <= MAX_COEFFICIENTS);
+        let config = &self.config;
+        let advice = config.advice;
+
+        // Accumulated positions of the sum, before each term.
+        let limbs = |a: &AssignedFq| a.limbs.iter().map(|l| l.value().map(fp_to_big).map(BigInt::from)).collect::<Option<Vec<_>>>();
+        let mut acc = vec![Some(vec![BigInt::zero(); NUM_POSITIONS])];
+        for (coef, a, b) in products {
+            let next = acc.last().unwrap().clone().zip(limbs(a)).zip(limbs(b)).map(|((mut acc, a), b)| {
+                for i in 0..NUM_LIMBS {
+                    for j in 0..NUM_LIMBS {
+                        acc[i + j] += &a[i] * &b[j] * coef;
+                    }
+                }
+                acc
+            });
+            acc.push(next);
+        }
+        for (coef, a) in linear {
+            let next = acc.last().unwrap().clone().zip(limbs(a)).map(|(mut acc, a)| {
+                for i in 0..NUM_LIMBS {
+                    acc[i] += &a[i] * coef;
+                }
+                acc
+            });
+            acc.push(next);
+        }
+
+        // Quotient, remainder and carries of the reduction.
+        let witness = acc.last().unwrap().as_ref().map(|acc| {
+            let sum = acc.iter().rev().fold(BigInt::zero(), |s, p| (s << LIMB_BITS) + p) + BigInt::from(SHIFT.clone());
+            let sum = sum.to_biguint().expect("shifted sum is non-negative");
+            let (quotient, remainder) = sum.div_rem(&MODULUS);
+            assert!(quotient.bits() <= (NUM_QUOTIENT_LIMBS * LIMB_BITS) as u64);
+            let remainder = if with_remainder { remainder } else { BigUint::zero() };
+
+            let quotient_limbs = to_limbs(&quotient, NUM_QUOTIENT_LIMBS);
+            let modulus_limbs = to_limbs(&MODULUS, NUM_LIMBS);
+            let remainder_limbs = to_limbs(&remainder, NUM_LIMBS);
+            let shift_limbs = to_limbs(&SHIFT, NUM_REDUCED_POSITIONS - 1);
+            let mut carry = BigInt::zero();
+            let mut carries = vec![];
+            for k in 0..NUM_REDUCED_POSITIONS - 1 {
+                let mut value = BigInt::from(shift_limbs[k].clone()) + &carry;
+                if k < NUM_POSITIONS {
+                    value += &acc[k];
+                }
+                for i in 0..NUM_QUOTIENT_LIMBS {
+                    if k >= i && k - i < NUM_LIMBS {
+                        value -= BigInt::from(&quotient_limbs[i] * &modulus_limbs[k - i]);
+                    }
+                }
+                if k < NUM_LIMBS {
+                    value -= BigInt::from(remainder_limbs[k].clone());
+                }
+                // Only exact when the reduction holds, otherwise the constraints won't be satisfied.
+                carry = value.div_floor(&(BigInt::one() << LIMB_BITS));
+                carries.push(bigint_to_fp(&(&carry + BigInt::from(CARRY_OFFSET.clone()))));
+            }
+            (quotient_limbs, remainder_limbs, carries)
+        });
+
+        layouter.assign_region(|| "inner product", |mut region| {
+            let zero = || Ok(Fp::zero());
+            let terms = products.iter().map(|(c, a, b)| (*c, 0, *a, Some(*b)))
+                .chain(linear.iter().map(|(c, a)| (0, *c, *a, None)));
+            let mut row = 0;
+            for (coef, lin, a, b) in terms {
+                config.q_term.enable(&mut region, row)?;
+                region.assign_fixed(|| "coef", config.coef, row, || Ok(Fp::from(coef.unsigned_abs()) * sign(coef)))?;
+                region.assign_fixed(|| "lin", config.lin, row, || Ok(Fp::from(lin.unsigned_abs()) * sign(lin)))?;
+                for i in 0..NUM_LIMBS {
+                    a.limbs[i].copy_advice(|| "a", &mut region, advice[i], row)?;
+                    match b {
+                        Some(b) => { b.limbs[i].copy_advice(|| "b", &mut region, advice[4 + i], row)?; }
+                        None => { region.assign_advice(|| "b", advice[4 + i], row, zero)?; }
+                    }
+                }
+                for k in 0..NUM_POSITIONS {
+                    let value = acc[row].as_ref().map(|acc| bigint_to_fp(&acc[k]));
+                    region.assign_advice(|| "acc", advice[8 + k], row, || value.ok_or(Error::Synthesis))?;
+                }
+                row += 1;
+            }
+            config.q_first.enable(&mut region, 0)?;
+
+            // Reduction rows.
+            if with_remainder {
+                config.q_reduce.enable(&mut region, row)?;
+            } else {
+                config.q_reduce_zero.enable(&mut region, row)?;
+            }
+            for k in 0..NUM_POSITIONS {
+                let value = acc[row].as_ref().map(|acc| bigint_to_fp(&acc[k]));
+                region.assign_advice(|| "acc", advice[8 + k], row, || value.ok_or(Error::Synthesis))?;
+            }
+            let cell = |region: &mut Region<'_, Fp>, column: usize, offset: usize, value: Option<&BigUint>| {
+                region.assign_advice(|| "reduction", advice[column], offset, || value.map(big_to_fp).ok_or(Error::Synthesis))
+            };
+            let remainder = (0..NUM_LIMBS)
+                .map(|i| cell(&mut region, i, row, witness.as_ref().map(|w| &w.1[i])))
+                .collect::<Result<Vec<_>, _>>()?;
+            let quotient = (0..NUM_QUOTIENT_LIMBS)
+                .map(|i| if i < 4 {
+                    cell(&mut region, 4 + i, row, witness.as_ref().map(|w| &w.0[i]))
+                } else {
+                    cell(&mut region, 0, row + 1, witness.as_ref().map(|w| &w.0[i]))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let carries = (0..NUM_REDUCED_POSITIONS - 1)
+                .map(|k| {
+                    let carry = witness.as_ref().map(|w| w.2[k]);
+                    region.assign_advice(|| "carry", advice[1 + k], row + 1, || carry.ok_or(Error::Synthesis))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            row += 2;
+
+            // Range checks of the reduction witnesses.
+            let remainder = with_remainder.then_some(remainder);
+            for limb in remainder.iter().flatten().chain(&quotient) {
+                config.range.assign(&mut region, row, limb, LIMB_BITS)?;
+                row += 1;
+            }
+            for carry in &carries {
+                config.range.assign(&mut region, row, carry, CARRY_BITS)?;
+                row += 1;
+            }
+
+            Ok(remainder.map(|limbs| AssignedFq { limbs: limbs.try_into().unwrap() }))
+        })
+    }
+
+    /// Checks that `a`, placed in the first columns of the row at `offset`, is less than the
+    /// modulus of `bound`. Returns the offset of the first row after the check.
+    fn assign_lt(&self, region: &mut Region<'_, Fp>, offset: usize, a: &AssignedFq, bound: Bound) -> Result<usize, Error> {
+        let config = &self.config;
+        match bound {
+            Bound::Fq => config.q_lt_q.enable(region, offset)?,
+            Bound::Fp => config.q_lt_p.enable(region, offset)?,
+        }
+
+        let m = to_limbs(&(bound.modulus() - 1u32), NUM_LIMBS);
+        let value = a.limbs.iter().map(|l| l.value().map(fp_to_big)).collect::<Option<Vec<_>>>();
+        let (difference, borrows) = match value {
+            Some(a) => {
+                let mut borrow = false;
+                let mut difference = vec![];
+                let mut borrows = vec![];
+                for i in 0..NUM_LIMBS {
+                    let subtrahend = &a[i] + u32::from(borrow);
+                    borrow = subtrahend > m[i];
+                    difference.push(Some(big_to_fp(&((BigUint::from(borrow) << LIMB_BITS) + &m[i] - subtrahend))));
+                    borrows.push(Some(Fp::from(borrow)));
+                }
+                (difference, borrows)
+            }
+            None => (vec![None; NUM_LIMBS], vec![None; NUM_LIMBS]),
+        };
+
+        let difference = difference.iter().enumerate()
+            .map(|(i, d)| region.assign_advice(|| "difference", config.advice[4 + i], offset, || d.ok_or(Error::Synthesis)))
+            .collect::<Result<Vec<_>, _>>()?;
+        for i in 0..NUM_LIMBS - 1 {
+            region.assign_advice(|| "borrow", config.advice[8 + i], offset, || borrows[i].ok_or(Error::Synthesis))?;
+        }
+        self.range_check_limbs(region, offset + 1, &difference)?;
+
+        Ok(offset + 1 + NUM_LIMBS)
+    }
+
+    fn range_check_limbs(&self, region: &mut Region<'_, Fp>, offset: usize, limbs: &[AssignedCell<Fp, Fp>]) -> Result<usize, Error> {
+        for (i, limb) in limbs.iter().enumerate() {
+            self.config.range.assign(region, offset + i, limb, LIMB_BITS)?;
+        }
+        Ok(offset + limbs.len())
+    }
+}
+
+fn sign(n: i64) -> Fp {
+    if n < 0 { -Fp::one() } else { Fp::one() }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::{test_rng, UniformRand};
+    use ark_bls12_381::Fq;
+    use halo2_proofs::circuit::SimpleFloorPlanner;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::Circuit;
+    use super::*;
+
+    #[derive(Default)]
+    struct TestCircuit {
+        values: Option<[Fq; 4]>,
+        expected: Option<Fq>,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = (FqConfig, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 16].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            for column in advice {
+                meta.enable_equality(column);
+            }
+            let (coef, lin) = (meta.fixed_column(), meta.fixed_column());
+            (FqConfig::configure(meta, advice, coef, lin), advice[0])
+        }
+
+        fn synthesize(&self, (config, column): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.range.load_table(&mut layouter)?;
+            let fq = FqChip::new(config);
+            let [a, b, c, d] = [0, 1, 2, 3].map(|i| fq.witness(&mut layouter, self.values.map(|v| v[i])).unwrap());
+
+            // a * b - 2 * c * d + a - d
+            let res = fq.inner_product(&mut layouter, &[(1, &a, &b), (-2, &c, &d)], &[(1, &a), (-1, &d)])?;
+            let res = fq.canonical(&mut layouter, &res)?;
+            let expected = fq.witness(&mut layouter, self.expected)?;
+            fq.assert_equal(&mut layouter, &res, &expected)?;
+
+            let native = layouter.assign_region(|| "native", |mut region| {
+                region.assign_advice(|| "native", column, 0, || Ok(-Fp::one()))
+            })?;
+            let limbs = fq.from_native(&mut layouter, &native)?;
+            let bits = fq.to_bits(&mut layouter, &limbs, 255)?;
+            assert_eq!(bits.len(), 255);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fq_inner_product() {
+        let mut rng = test_rng();
+        let [a, b, c, d] = [(); 4].map(|_| Fq::rand(&mut rng));
+        let expected = a * b - c * d * Fq::from(2u64) + a - d;
+
+        let circuit = TestCircuit { values: Some([a, b, c, d]), expected: Some(expected) };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let circuit = TestCircuit { values: Some([a, b, c, d]), expected: Some(expected + Fq::from(1u64)) };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
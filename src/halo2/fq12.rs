@@ -0,0 +1,235 @@
+//! Arithmetic of BLS12-381 target group, on top of [`FqChip`].
+//!
+//! `Fq12` is viewed as `Fq2[w] / (w^6 - (1 + u))` with `Fq2 = Fq[u] / (u^2 + 1)`, so that each of
+//! the twelve base field coefficients of a product is a single inner product of the coefficients
+//! of the factors, reduced once.
+
+use std::collections::BTreeMap;
+use halo2_proofs::circuit::{AssignedCell, Layouter};
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::plonk::{Column, Error, Instance};
+use lazy_static::lazy_static;
+use super::fq::{AssignedFq, FqChip};
+
+/// Number of base field coefficients of an `Fq12` element.
+pub(crate) const DEGREE: usize = 12;
+
+/// Base field terms `(coefficient, i, j)` of each coefficient of a product `a * b`, which sums
+/// `coefficient * a[i] * b[j]`.
+type ProductTerms = Vec<Vec<(i64, usize, usize)>>;
+
+lazy_static! {
+    static ref MUL_TERMS: ProductTerms = product_terms(false);
+    static ref SQUARE_TERMS: ProductTerms = product_terms(true);
+}
+
+/// Maps the index of a coefficient, in the order of `fp12_coefficients`, to its power of `w` and `u`.
+fn powers(index: usize) -> (usize, usize) {
+    let (c1, c, e) = (index / 6, (index % 6) / 2, index % 2);
+    // `Fq12 = Fq6[w] / (w^2 - v)` and `Fq6 = Fq2[v] / (v^3 - (1 + u))`, hence `v = w^2`.
+    (2 * c + c1, e)
+}
+
+fn index(w: usize, u: usize) -> usize {
+    6 * (w % 2) + 2 * (w / 2) + u
+}
+
+fn product_terms(square: bool) -> ProductTerms {
+    let mut terms = vec![BTreeMap::new(); DEGREE];
+    for i in 0..DEGREE {
+        for j in 0..DEGREE {
+            let ((w1, u1), (w2, u2)) = (powers(i), powers(j));
+            let (mut w, mut u, mut sign) = (w1 + w2, u1 + u2, 1);
+            if u == 2 {
+                u = 0;
+                sign = -1;
+            }
+            let key = if square { (i.min(j), i.max(j)) } else { (i, j) };
+            let mut add = |w, u, c| *terms[index(w, u)].entry(key).or_insert(0) += c;
+            if w >= 6 {
+                // w^6 = 1 + u
+                w -= 6;
+                add(w, u, sign);
+                if u == 0 { add(w, 1, sign) } else { add(w, 0, -sign) }
+            } else {
+                add(w, u, sign);
+            }
+        }
+    }
+
+    terms.into_iter()
+        .map(|t| t.into_iter().filter(|(_, c)| *c != 0).map(|((i, j), c)| (c, i, j)).collect())
+        .collect()
+}
+
+/// An element of `Fq12` as its coefficients, see `fp12_coefficients`.
+#[derive(Clone, Debug)]
+pub struct AssignedFq12(pub [AssignedFq; DEGREE]);
+
+/// Chip for the arithmetic of `Fq12`.
+#[derive(Clone, Debug)]
+pub struct Fq12Chip {
+    fq: FqChip,
+}
+
+impl Fq12Chip {
+    pub fn new(fq: FqChip) -> Self {
+        Self { fq }
+    }
+
+    /// Assigns the constant `value`.
+    pub fn constant(&self, layouter: &mut impl Layouter<Fp>, value: &ark_bls12_381::Fq12) -> Result<AssignedFq12, Error> {
+        let coefficients = crate::nonnative::fp12_coefficients(value).iter()
+            .map(|c| self.fq.constant(layouter, c))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AssignedFq12(coefficients.try_into().unwrap()))
+    }
+
+    /// Assigns the element whose coefficients are public inputs `index..index + DEGREE`, see
+    /// [`FqChip::from_instance`].
+    pub fn from_instance(&self, layouter: &mut impl Layouter<Fp>, instance: Column<Instance>, index: usize) -> Result<AssignedFq12, Error> {
+        let coefficients = (0..DEGREE)
+            .map(|i| self.fq.from_instance(layouter, instance, index + i))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AssignedFq12(coefficients.try_into().unwrap()))
+    }
+
+    pub fn mul(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq12, b: &AssignedFq12) -> Result<AssignedFq12, Error> {
+        self.product(layouter, &MUL_TERMS, a, b)
+    }
+
+    pub fn square(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq12) -> Result<AssignedFq12, Error> {
+        self.product(layouter, &SQUARE_TERMS, a, a)
+    }
+
+    /// Returns `a` if `bit` is set and `b` otherwise.
+    pub fn select(&self, layouter: &mut impl Layouter<Fp>, bit: &AssignedCell<Fp, Fp>, a: &AssignedFq12, b: &AssignedFq12) -> Result<AssignedFq12, Error> {
+        let coefficients = a.0.iter().zip(&b.0)
+            .map(|(a, b)| self.fq.select(layouter, bit, a, b))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AssignedFq12(coefficients.try_into().unwrap()))
+    }
+
+    /// Computes `a^e`, given the little-endian bits of `e`, by square-and-multiply.
+    pub fn pow(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq12, bits: &[AssignedCell<Fp, Fp>]) -> Result<AssignedFq12, Error> {
+        let one = self.constant(layouter, &ark_ff::One::one())?;
+        let (top, rest) = bits.split_last().expect("exponent has at least one bit");
+
+        let mut res = self.select(layouter, top, a, &one)?;
+        for bit in rest.iter().rev() {
+            res = self.square(layouter, &res)?;
+            let factor = self.select(layouter, bit, a, &one)?;
+            res = self.mul(layouter, &res, &factor)?;
+        }
+
+        Ok(res)
+    }
+
+    /// Reduces the coefficients of `a` to their canonical representations.
+    pub fn canonical(&self, layouter: &mut impl Layouter<Fp>, a: &AssignedFq12) -> Result<AssignedFq12, Error> {
+        let coefficients = a.0.iter()
+            .map(|c| self.fq.canonical(layouter, c))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AssignedFq12(coefficients.try_into().unwrap()))
+    }
+
+    fn product(&self, layouter: &mut impl Layouter<Fp>, terms: &ProductTerms, a: &AssignedFq12, b: &AssignedFq12) -> Result<AssignedFq12, Error> {
+        let coefficients = terms.iter()
+            .map(|terms| {
+                let products = terms.iter().map(|(c, i, j)| (*c, &a.0[*i], &b.0[*j])).collect::<Vec<_>>();
+                self.fq.inner_product(layouter, &products, &[])
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AssignedFq12(coefficients.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::Field;
+    use ark_std::{test_rng, UniformRand};
+    use ark_bls12_381::{Fq, Fq12};
+    use halo2_proofs::circuit::SimpleFloorPlanner;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::{Advice, Circuit, ConstraintSystem};
+    use crate::nonnative::fp12_coefficients;
+    use super::super::fq::FqConfig;
+    use super::*;
+
+    fn product(terms: &ProductTerms, a: &[Fq], b: &[Fq]) -> Vec<Fq> {
+        terms.iter()
+            .map(|t| t.iter().map(|(c, i, j)| Fq::from(*c) * a[*i] * b[*j]).sum())
+            .collect()
+    }
+
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Option<Fq12>,
+        exponent: Option<u8>,
+        expected: Option<Fq12>,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = (FqConfig, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 16].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            for column in advice {
+                meta.enable_equality(column);
+            }
+            let (coef, lin) = (meta.fixed_column(), meta.fixed_column());
+            (FqConfig::configure(meta, advice, coef, lin), advice[0])
+        }
+
+        fn synthesize(&self, (config, column): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.range.load_table(&mut layouter)?;
+            let fq = FqChip::new(config);
+            let fq12 = Fq12Chip::new(fq.clone());
+            let witness = |layouter: &mut _, value: Option<Fq12>| -> Result<AssignedFq12, Error> {
+                let coefficients = (0..DEGREE)
+                    .map(|i| fq.witness(layouter, value.map(|v| fp12_coefficients(&v)[i])))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(AssignedFq12(coefficients.try_into().unwrap()))
+            };
+            let a = witness(&mut layouter, self.a)?;
+            let expected = witness(&mut layouter, self.expected)?;
+            let bits = layouter.assign_region(|| "bits", |mut region| {
+                (0..3).map(|i| {
+                    let bit = self.exponent.map(|e| Fp::from(((e >> i) & 1) as u64));
+                    region.assign_advice(|| "bit", column, i, || bit.ok_or(Error::Synthesis))
+                }).collect::<Result<Vec<_>, _>>()
+            })?;
+
+            let res = fq12.pow(&mut layouter, &a, &bits)?;
+            for (res, expected) in res.0.iter().zip(&expected.0) {
+                fq.assert_equal(&mut layouter, res, expected)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fq12_pow() {
+        let mut rng = test_rng();
+        let a = Fq12::rand(&mut rng);
+        let b = Fq12::rand(&mut rng);
+        let (a_coefficients, b_coefficients) = (fp12_coefficients(&a), fp12_coefficients(&b));
+        assert_eq!(product(&MUL_TERMS, &a_coefficients, &b_coefficients), fp12_coefficients(&(a * b)));
+        assert_eq!(product(&SQUARE_TERMS, &a_coefficients, &a_coefficients), fp12_coefficients(&a.square()));
+
+        let circuit = TestCircuit { a: Some(a), exponent: Some(5), expected: Some(a.pow([5])) };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let circuit = TestCircuit { a: Some(a), exponent: Some(6), expected: Some(a.pow([5])) };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Arithmetic of BLS12-381 `G1`, on top of [`FqChip`].
+
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_bls12_381::{Fq, G1Affine};
+use halo2_proofs::circuit::{AssignedCell, Layouter};
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::plonk::Error;
+use lazy_static::lazy_static;
+use super::fq::{big_to_fq, AssignedFq, FqChip};
+
+lazy_static! {
+    /// Offset of the accumulator of [`G1Chip::fixed_base_mul`], the 3-torsion point `(0, 2)`.
+    ///
+    /// As it is outside of the prime-order subgroup, the accumulator `OFFSET + k * G` can never
+    /// equal (the negation of) a multiple of `G`, which makes incomplete additions safe.
+    static ref OFFSET: G1Affine = G1Affine::new_unchecked(Fq::from(0u64), Fq::from(2u64));
+}
+
+/// An affine point of `G1`, which can't be the point at infinity.
+#[derive(Clone, Debug)]
+pub struct AssignedG1 {
+    pub x: AssignedFq,
+    pub y: AssignedFq,
+}
+
+/// Chip for the arithmetic of BLS12-381 `G1`.
+#[derive(Clone, Debug)]
+pub struct G1Chip {
+    fq: FqChip,
+}
+
+impl G1Chip {
+    pub fn new(fq: FqChip) -> Self {
+        Self { fq }
+    }
+
+    /// Assigns the constant `point`.
+    pub fn constant(&self, layouter: &mut impl Layouter<Fp>, point: &G1Affine) -> Result<AssignedG1, Error> {
+        Ok(AssignedG1 {
+            x: self.fq.constant(layouter, &point.x)?,
+            y: self.fq.constant(layouter, &point.y)?,
+        })
+    }
+
+    /// Computes `p + q` with the incomplete addition law, the caller must ensure `p != ±q`.
+    pub fn add_incomplete(&self, layouter: &mut impl Layouter<Fp>, p: &AssignedG1, q: &AssignedG1) -> Result<AssignedG1, Error> {
+        let fq = &self.fq;
+        let lambda = p.x.value().zip(p.y.value()).zip(q.x.value().zip(q.y.value()))
+            .map(|((px, py), (qx, qy))| {
+                let (px, py, qx, qy) = (big_to_fq(&px), big_to_fq(&py), big_to_fq(&qx), big_to_fq(&qy));
+                (qy - py) / (qx - px)
+            });
+        let lambda = fq.witness(layouter, lambda)?;
+
+        // lambda * (qx - px) = qy - py
+        fq.assert_zero(layouter, &[(1, &lambda, &q.x), (-1, &lambda, &p.x)], &[(-1, &q.y), (1, &p.y)])?;
+        // x = lambda^2 - px - qx
+        let x = fq.inner_product(layouter, &[(1, &lambda, &lambda)], &[(-1, &p.x), (-1, &q.x)])?;
+        // y = lambda * (px - x) - py
+        let y = fq.inner_product(layouter, &[(1, &lambda, &p.x), (-1, &lambda, &x)], &[(-1, &p.y)])?;
+
+        Ok(AssignedG1 { x, y })
+    }
+
+    /// Returns `p` if `bit` is set and `q` otherwise.
+    pub fn select(&self, layouter: &mut impl Layouter<Fp>, bit: &AssignedCell<Fp, Fp>, p: &AssignedG1, q: &AssignedG1) -> Result<AssignedG1, Error> {
+        Ok(AssignedG1 {
+            x: self.fq.select(layouter, bit, &p.x, &q.x)?,
+            y: self.fq.select(layouter, bit, &p.y, &q.y)?,
+        })
+    }
+
+    /// Computes `e * base`, given the little-endian bits of `e`, by adding the precomputed
+    /// multiples `2^i * base` to an accumulator offset by [`OFFSET`].
+    ///
+    /// `e * base` must not be the point at infinity, i.e. `e` must not be a multiple of the group order.
+    pub fn fixed_base_mul(&self, layouter: &mut impl Layouter<Fp>, base: &G1Affine, bits: &[AssignedCell<Fp, Fp>]) -> Result<AssignedG1, Error> {
+        let mut acc = self.constant(layouter, &OFFSET)?;
+        let mut multiple = base.into_group();
+        for bit in bits {
+            let addend = self.constant(layouter, &multiple.into_affine())?;
+            let sum = self.add_incomplete(layouter, &acc, &addend)?;
+            acc = self.select(layouter, bit, &sum, &acc)?;
+            multiple = multiple.double();
+        }
+
+        let offset = self.constant(layouter, &(-*OFFSET))?;
+        self.add_incomplete(layouter, &acc, &offset)
+    }
+
+    /// Constrains `p` and `q` to be equal.
+    pub fn assert_equal(&self, layouter: &mut impl Layouter<Fp>, p: &AssignedG1, q: &AssignedG1) -> Result<(), Error> {
+        self.fq.assert_equal(layouter, &p.x, &q.x)?;
+        self.fq.assert_equal(layouter, &p.y, &q.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::G1Projective;
+    use halo2_proofs::circuit::SimpleFloorPlanner;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem};
+    use super::super::fq::FqConfig;
+    use super::*;
+
+    #[derive(Default)]
+    struct TestCircuit {
+        scalar: Option<u8>,
+        expected: Option<G1Affine>,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = (FqConfig, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 16].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            for column in advice {
+                meta.enable_equality(column);
+            }
+            let (coef, lin) = (meta.fixed_column(), meta.fixed_column());
+            (FqConfig::configure(meta, advice, coef, lin), advice[0])
+        }
+
+        fn synthesize(&self, (config, column): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.range.load_table(&mut layouter)?;
+            let fq = FqChip::new(config);
+            let g1 = G1Chip::new(fq.clone());
+            let bits = layouter.assign_region(|| "bits", |mut region| {
+                (0..8).map(|i| {
+                    let bit = self.scalar.map(|e| Fp::from(((e >> i) & 1) as u64));
+                    region.assign_advice(|| "bit", column, i, || bit.ok_or(Error::Synthesis))
+                }).collect::<Result<Vec<_>, _>>()
+            })?;
+            let expected = AssignedG1 {
+                x: fq.witness(&mut layouter, self.expected.map(|p| p.x))?,
+                y: fq.witness(&mut layouter, self.expected.map(|p| p.y))?,
+            };
+
+            let res = g1.fixed_base_mul(&mut layouter, &G1Projective::generator().into_affine(), &bits)?;
+            g1.assert_equal(&mut layouter, &res, &expected)
+        }
+    }
+
+    #[test]
+    fn test_g1_fixed_base_mul() {
+        let scalar = 0b10110101u8;
+        let expected = (G1Projective::generator() * ark_bls12_381::Fr::from(scalar)).into_affine();
+
+        let circuit = TestCircuit { scalar: Some(scalar), expected: Some(expected) };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let circuit = TestCircuit { scalar: Some(scalar + 1), expected: Some(expected) };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
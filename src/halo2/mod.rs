@@ -0,0 +1,255 @@
+//! Halo2 circuit for the timelock encryption over BLS12-381, proven with the IPA commitment
+//! scheme over the Pasta curves.
+//!
+//! [`TimelockCircuit`] proves the same statement as `NonnativeCircuit::verify_encryption`: BLS12-381
+//! base field is simulated with 96-bit limbs over Pallas' base field, whose products are reduced
+//! lazily (see [`fq`]), and range checks use a lookup table instead of bit decompositions. Hashes
+//! are computed with the BLS12-381 Poseidon instance of the other circuits, emulated by
+//! [`poseidon`], hence ciphertexts are those of `Circuit::<Bls12_381, _>`.
+
+pub mod fq;
+pub mod fq12;
+pub mod g1;
+pub mod poseidon;
+mod range;
+
+use ark_ec::{CurveGroup, Group};
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner};
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::plonk::{self, Column, ConstraintSystem, Error, Instance};
+use crate::circuits::R_BYTES_SQUEEZE;
+use crate::{Bls12_381, Ciphertext, Circuit, Parameters, Plaintext, PublicKey, SecretKey};
+use self::fq::{fq_limbs, FqChip, FqConfig};
+use self::fq12::{Fq12Chip, DEGREE};
+use self::g1::G1Chip;
+use self::poseidon::PoseidonChip;
+
+/// Base-2 logarithm of the number of rows of [`TimelockCircuit`].
+pub const K: u32 = 19;
+
+/// Number of bits of BLS12-381 base field elements.
+const FQ_BITS: usize = ark_bls12_381::Fq::MODULUS_BIT_SIZE as usize;
+/// Number of bits of the randomness `r`, i.e. of the bytes squeezed by `Circuit::encrypt`.
+const R_BITS: usize = 8 * R_BYTES_SQUEEZE;
+
+/// Native circuit whose encryption [`TimelockCircuit`] proves.
+type NativeCircuit = Circuit<Bls12_381, ark_bls12_381::Parameters>;
+
+/// Halo2 circuit proving that a ciphertext is a valid encryption to a public `Gid`.
+#[derive(Clone, Debug)]
+pub struct TimelockCircuit {
+    pub gid: Option<ark_bls12_381::Fq12>,
+    sigma: Option<ark_bls12_381::Fq>,
+    msg: Option<Plaintext<ark_bls12_381::G1Projective>>,
+    pub ciphertext: Option<Ciphertext<ark_bls12_381::G1Projective>>,
+}
+
+impl TimelockCircuit {
+    pub fn new<I: AsRef<[u8]>, R: Rng>(
+        master: PublicKey<Bls12_381>,
+        id: I,
+        msg: Plaintext<ark_bls12_381::G1Projective>,
+        rng: &mut R,
+    ) -> anyhow::Result<Self> {
+        let params = Parameters::<ark_bls12_381::G1Projective>::default();
+        let (gid, sigma, ct) = NativeCircuit::encrypt_inner(&master, id, &msg, &params, rng)?;
+
+        Ok(Self {
+            gid: Some(gid),
+            sigma: Some(sigma.0),
+            msg: Some(msg),
+            ciphertext: Some(ct),
+        })
+    }
+
+    /// Returns a circuit without any assignments, e.g. to generate keys.
+    pub fn blank() -> Self {
+        Self {
+            gid: None,
+            sigma: None,
+            msg: None,
+            ciphertext: None,
+        }
+    }
+
+    /// Encrypts `msg` to `id`, see `Circuit::encrypt`.
+    pub fn encrypt<I: AsRef<[u8]>, R: Rng>(
+        master: &PublicKey<Bls12_381>,
+        id: I,
+        msg: &Plaintext<ark_bls12_381::G1Projective>,
+        rng: &mut R,
+    ) -> anyhow::Result<Ciphertext<ark_bls12_381::G1Projective>> {
+        NativeCircuit::encrypt(master, id, msg, rng)
+    }
+
+    /// Computes `Gid = e(master, Q_id)`, see `Circuit::gid`.
+    pub fn gid<I: AsRef<[u8]>>(master: &PublicKey<Bls12_381>, id: I) -> anyhow::Result<ark_bls12_381::Fq12> {
+        NativeCircuit::gid(master, id)
+    }
+
+    pub fn decrypt(
+        sk: &SecretKey<Bls12_381>,
+        ct: &Ciphertext<ark_bls12_381::G1Projective>,
+    ) -> anyhow::Result<Plaintext<ark_bls12_381::G1Projective>> {
+        NativeCircuit::decrypt(sk, ct)
+    }
+
+    /// Packs `gid` and the ciphertext into the public inputs of the circuit: the limbs of the 12
+    /// coefficients of `gid`, then of `U.x`, `U.y`, `V` and `W`.
+    pub fn get_public_inputs(
+        gid: &ark_bls12_381::Fq12,
+        cipher: &Ciphertext<ark_bls12_381::G1Projective>,
+    ) -> Vec<Fp> {
+        let u = cipher.u.into_affine();
+        crate::nonnative::fp12_coefficients(gid).iter()
+            .chain([&u.x, &u.y, &cipher.v, &cipher.w])
+            .flat_map(fq_limbs)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TimelockConfig {
+    fq: FqConfig,
+    instance: Column<Instance>,
+}
+
+impl plonk::Circuit<Fp> for TimelockCircuit {
+    type Config = TimelockConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::blank()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let advice = [(); 16].map(|_| meta.advice_column());
+        let fixed = [(); 2].map(|_| meta.fixed_column());
+        let constants = meta.fixed_column();
+        let instance = meta.instance_column();
+
+        meta.enable_constant(constants);
+        meta.enable_equality(instance);
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        TimelockConfig {
+            fq: FqConfig::configure(meta, advice, fixed[0], fixed[1]),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.fq.range.load_table(&mut layouter)?;
+        let fq = FqChip::new(config.fq.clone());
+        let fq12 = Fq12Chip::new(fq.clone());
+        let g1 = G1Chip::new(fq.clone());
+        let poseidon = PoseidonChip::new(fq.clone());
+
+        let gid = fq12.from_instance(&mut layouter, config.instance, 0)?;
+        let u = g1::AssignedG1 {
+            x: fq.from_instance(&mut layouter, config.instance, DEGREE)?,
+            y: fq.from_instance(&mut layouter, config.instance, DEGREE + 1)?,
+        };
+        let v = fq.from_instance(&mut layouter, config.instance, DEGREE + 2)?;
+        let w = fq.from_instance(&mut layouter, config.instance, DEGREE + 3)?;
+
+        // 2. Derive random sigma
+        let sigma = fq.witness(&mut layouter, self.sigma)?;
+        let msg = fq.witness(&mut layouter, self.msg)?;
+
+        // 3. Derive r from sigma and msg, i.e. the low bytes of the canonical hash
+        let r = {
+            let h = poseidon.hash(&mut layouter, &[sigma.clone(), msg.clone()])?;
+            let h = fq.canonical(&mut layouter, &h)?;
+            let mut bits = fq.to_bits(&mut layouter, &h, FQ_BITS)?;
+            bits.truncate(R_BITS);
+            bits
+        };
+
+        // 4. Compute U = G*r
+        let u_r = g1.fixed_base_mul(&mut layouter, &ark_bls12_381::G1Projective::generator().into_affine(), &r)?;
+        g1.assert_equal(&mut layouter, &u_r, &u)?;
+
+        // 5. Compute V = sigma XOR H(rGid)
+        let h_r_gid = {
+            let r_gid = fq12.pow(&mut layouter, &gid, &r)?;
+            poseidon.hash(&mut layouter, &r_gid.0)?
+        };
+        fq.assert_zero(&mut layouter, &[], &[(1, &v), (-1, &sigma), (-1, &h_r_gid)])?;
+
+        // 6. Compute W = M XOR H(sigma)
+        let h_sigma = poseidon.hash(&mut layouter, &[sigma.clone()])?;
+        fq.assert_zero(&mut layouter, &[], &[(1, &w), (-1, &msg), (-1, &h_sigma)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_std::{test_rng, UniformRand};
+    use halo2_proofs::dev::MockProver;
+    use crate::utils::Hash2Curve;
+    use crate::H2C_DST;
+    use super::*;
+
+    const ID: &[u8] = b"round 1000";
+
+    /// Returns a circuit encrypting a random plaintext to `ID`, the master key and the key of `ID`.
+    fn setup() -> (TimelockCircuit, PublicKey<Bls12_381>, SecretKey<Bls12_381>) {
+        let mut rng = test_rng();
+        let sk = ark_bls12_381::Fr::rand(&mut rng);
+        let master = (ark_bls12_381::G1Projective::generator() * sk).into_affine();
+        let msg = ark_bls12_381::Fq::rand(&mut rng);
+        let qid: ark_bls12_381::G2Affine = Bls12_381::hash(ID, H2C_DST).unwrap();
+        let round_key = (qid * sk).into_affine();
+
+        (TimelockCircuit::new(master, ID, msg, &mut rng).unwrap(), master, round_key)
+    }
+
+    #[test]
+    fn test_decrypt() {
+        let (circuit, _, round_key) = setup();
+        let ct = circuit.ciphertext.clone().unwrap();
+        assert_eq!(TimelockCircuit::decrypt(&round_key, &ct).unwrap(), circuit.msg.unwrap());
+
+        let tampered = Ciphertext { w: ct.w + ark_bls12_381::Fq::from(1u64), ..ct };
+        assert!(TimelockCircuit::decrypt(&round_key, &tampered).is_err());
+    }
+
+    #[test]
+    fn test_circuit() {
+        let (circuit, _, _) = setup();
+        let gid = circuit.gid.unwrap();
+        let ct = circuit.ciphertext.clone().unwrap();
+
+        let public_inputs = TimelockCircuit::get_public_inputs(&gid, &ct);
+        let prover = MockProver::run(K, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let tampered = Ciphertext { v: ct.v + ark_bls12_381::Fq::from(1u64), ..ct };
+        let public_inputs = TimelockCircuit::get_public_inputs(&gid, &tampered);
+        let prover = MockProver::run(K, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_native_ciphertext() {
+        let (circuit, master, round_key) = setup();
+        let msg = circuit.msg.unwrap();
+
+        // A ciphertext of the native circuit, whose sigma is recovered by decrypting it.
+        let ct = NativeCircuit::encrypt(&master, ID, &msg, &mut test_rng()).unwrap();
+        let params = Parameters::<ark_bls12_381::G1Projective>::default();
+        let (sigma, pt) = NativeCircuit::decrypt_inner(&round_key, &ct, &params).unwrap();
+        assert_eq!(pt, msg);
+
+        let gid = circuit.gid.unwrap();
+        let circuit = TimelockCircuit { gid: Some(gid), sigma: Some(sigma.0), msg: Some(msg), ciphertext: Some(ct.clone()) };
+        let public_inputs = TimelockCircuit::get_public_inputs(&gid, &ct);
+        let prover = MockProver::run(K, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
@@ -0,0 +1,198 @@
+//! Poseidon over BLS12-381 base field, emulated on top of [`FqChip`].
+//!
+//! Uses the parameters of the other circuits, `Parameters::<G1Projective>::default().poseidon`,
+//! and absorbs and squeezes as `ark_sponge`'s `PoseidonSponge` does, so that hashes, and hence
+//! ciphertexts, are those of `Circuit::<Bls12_381, _>`. Each S-box input is the result of a single
+//! reduction: the round constants are added along with the MDS products of the previous round.
+
+use ark_ff::Zero;
+use ark_sponge::poseidon::PoseidonConfig;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::plonk::Error;
+use crate::Parameters;
+use super::fq::{AssignedFq, FqChip};
+
+/// Round constants and MDS matrix, assigned once per hash.
+struct Constants {
+    ark: Vec<Vec<AssignedFq>>,
+    mds: Vec<Vec<AssignedFq>>,
+}
+
+/// Chip hashing elements of BLS12-381 base field.
+#[derive(Clone, Debug)]
+pub struct PoseidonChip {
+    fq: FqChip,
+    params: PoseidonConfig<ark_bls12_381::Fq>,
+}
+
+impl PoseidonChip {
+    pub fn new(fq: FqChip) -> Self {
+        Self {
+            fq,
+            params: Parameters::<ark_bls12_381::G1Projective>::default().poseidon,
+        }
+    }
+
+    /// Absorbs `inputs` into a new sponge and squeezes a single element, which isn't necessarily
+    /// reduced modulo `q`.
+    pub fn hash(&self, layouter: &mut impl Layouter<Fp>, inputs: &[AssignedFq]) -> Result<AssignedFq, Error> {
+        let (rate, capacity) = (self.params.rate, self.params.capacity);
+        let constants = self.constants(layouter)?;
+        let zero = self.fq.constant(layouter, &ark_bls12_381::Fq::zero())?;
+
+        // The sponge permutes once a chunk of `rate` elements is absorbed, or before squeezing.
+        let mut state = vec![zero; rate + capacity];
+        for chunk in inputs.chunks(rate) {
+            state = self.permute(layouter, &state, chunk, &constants)?;
+        }
+
+        Ok(state.swap_remove(capacity))
+    }
+
+    /// Adds `inputs` to the rate part of `state`, then applies the permutation.
+    fn permute(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        state: &[AssignedFq],
+        inputs: &[AssignedFq],
+        constants: &Constants,
+    ) -> Result<Vec<AssignedFq>, Error> {
+        let params = &self.params;
+        let width = params.rate + params.capacity;
+        let half_full_rounds = params.full_rounds / 2;
+
+        let mut sbox_in = (0..width)
+            .map(|i| {
+                let mut linear = vec![(1, &state[i]), (1, &constants.ark[0][i])];
+                if let Some(input) = i.checked_sub(params.capacity).and_then(|j| inputs.get(j)) {
+                    linear.push((1, input));
+                }
+                self.fq.inner_product(layouter, &[], &linear)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let rounds = params.full_rounds + params.partial_rounds;
+        for round in 0..rounds {
+            let full = round < half_full_rounds || round >= half_full_rounds + params.partial_rounds;
+            let sbox_out = sbox_in.iter().enumerate()
+                .map(|(i, x)| if full || i == 0 { self.pow_alpha(layouter, x) } else { Ok(x.clone()) })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // The MDS products, plus the round constants of the next round.
+            sbox_in = (0..width)
+                .map(|i| {
+                    let products = (0..width).map(|j| (1, &constants.mds[i][j], &sbox_out[j])).collect::<Vec<_>>();
+                    let linear = constants.ark.get(round + 1).map(|ark| vec![(1, &ark[i])]).unwrap_or_default();
+                    self.fq.inner_product(layouter, &products, &linear)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+
+        Ok(sbox_in)
+    }
+
+    /// Computes `x^alpha` by square-and-multiply.
+    fn pow_alpha(&self, layouter: &mut impl Layouter<Fp>, x: &AssignedFq) -> Result<AssignedFq, Error> {
+        let alpha = self.params.alpha;
+        let mut acc = x.clone();
+        for i in (0..63 - alpha.leading_zeros()).rev() {
+            acc = self.fq.mul(layouter, &acc, &acc)?;
+            if (alpha >> i) & 1 == 1 {
+                acc = self.fq.mul(layouter, &acc, x)?;
+            }
+        }
+
+        Ok(acc)
+    }
+
+    fn constants(&self, layouter: &mut impl Layouter<Fp>) -> Result<Constants, Error> {
+        Ok(Constants {
+            ark: self.assign_constants(layouter, &self.params.ark)?,
+            mds: self.assign_constants(layouter, &self.params.mds)?,
+        })
+    }
+
+    fn assign_constants(&self, layouter: &mut impl Layouter<Fp>, rows: &[Vec<ark_bls12_381::Fq>]) -> Result<Vec<Vec<AssignedFq>>, Error> {
+        let mut assigned = vec![];
+        for row in rows {
+            let mut assigned_row = vec![];
+            for c in row {
+                assigned_row.push(self.fq.constant(layouter, c)?);
+            }
+            assigned.push(assigned_row);
+        }
+
+        Ok(assigned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_sponge::poseidon::PoseidonSponge;
+    use ark_sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
+    use ark_std::{test_rng, UniformRand};
+    use halo2_proofs::circuit::SimpleFloorPlanner;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::{Circuit, ConstraintSystem};
+    use super::super::fq::FqConfig;
+    use super::*;
+
+    #[derive(Default)]
+    struct TestCircuit {
+        inputs: Vec<Option<ark_bls12_381::Fq>>,
+        expected: Option<ark_bls12_381::Fq>,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = FqConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { inputs: vec![None; self.inputs.len()], expected: None }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 16].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            for column in advice {
+                meta.enable_equality(column);
+            }
+            let (coef, lin) = (meta.fixed_column(), meta.fixed_column());
+            FqConfig::configure(meta, advice, coef, lin)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.range.load_table(&mut layouter)?;
+            let fq = FqChip::new(config);
+            let poseidon = PoseidonChip::new(fq.clone());
+
+            let inputs = self.inputs.iter()
+                .map(|x| fq.witness(&mut layouter, *x))
+                .collect::<Result<Vec<_>, _>>()?;
+            let expected = fq.witness(&mut layouter, self.expected)?;
+            let digest = poseidon.hash(&mut layouter, &inputs)?;
+            fq.assert_equal(&mut layouter, &digest, &expected)
+        }
+    }
+
+    #[test]
+    fn test_poseidon_hash() {
+        let mut rng = test_rng();
+        let inputs = (0..3).map(|_| ark_bls12_381::Fq::rand(&mut rng)).collect::<Vec<_>>();
+
+        let mut sponge = PoseidonSponge::new(&Parameters::<ark_bls12_381::G1Projective>::default().poseidon);
+        sponge.absorb(&inputs[0]);
+        sponge.absorb(&inputs[1..].to_vec());
+        let digest = sponge.squeeze_native_field_elements(1).remove(0);
+
+        let circuit = TestCircuit { inputs: inputs.iter().copied().map(Some).collect(), expected: Some(digest) };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let circuit = TestCircuit { inputs: inputs.iter().copied().map(Some).collect(), expected: Some(digest + ark_bls12_381::Fq::from(1u64)) };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,105 @@
+//! Range checks against a lookup table of 16-bit chunks.
+
+use halo2_proofs::circuit::{AssignedCell, Layouter, Region};
+use halo2_proofs::pasta::Fp;
+use halo2_proofs::plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn};
+use halo2_proofs::poly::Rotation;
+use num_bigint::BigUint;
+use super::fq::{big_to_fp, fp_to_big};
+
+/// Number of bits of a chunk, i.e. of an entry of the lookup table.
+pub(crate) const CHUNK_BITS: usize = 16;
+/// Number of chunks in a range check row, which bounds the widths that can be checked.
+const NUM_CHUNKS: usize = 7;
+
+/// Checks that a cell fits in 64, 96 or 112 bits by decomposing it into [`CHUNK_BITS`]-bit chunks
+/// in a single row, each of which is looked up in the table.
+#[derive(Clone, Debug)]
+pub struct RangeConfig {
+    value: Column<Advice>,
+    chunks: [Column<Advice>; NUM_CHUNKS],
+    q_range: Selector,
+    q_64: Selector,
+    q_96: Selector,
+    table: TableColumn,
+}
+
+impl RangeConfig {
+    /// Configures the range check over the first 8 columns of `advice`.
+    pub fn configure(meta: &mut ConstraintSystem<Fp>, advice: &[Column<Advice>]) -> Self {
+        let value = advice[0];
+        let chunks: [Column<Advice>; NUM_CHUNKS] = advice[1..=NUM_CHUNKS].try_into().unwrap();
+        let q_range = meta.complex_selector();
+        let q_64 = meta.selector();
+        let q_96 = meta.selector();
+        let table = meta.lookup_table_column();
+
+        meta.create_gate("range decomposition", |meta| {
+            let q_range = meta.query_selector(q_range);
+            let q_64 = meta.query_selector(q_64);
+            let q_96 = meta.query_selector(q_96);
+            let value = meta.query_advice(value, Rotation::cur());
+            let chunks = chunks.map(|c| meta.query_advice(c, Rotation::cur()));
+
+            let composed = chunks.iter().rev().fold(Expression::Constant(Fp::zero()), |acc, chunk| {
+                acc * Expression::Constant(Fp::from(1 << CHUNK_BITS)) + chunk.clone()
+            });
+
+            // Narrower checks force the top chunks to be zero.
+            vec![q_range * (value - composed)]
+                .into_iter()
+                .chain(chunks[4..].iter().map(|c| q_64.clone() * c.clone()))
+                .chain([q_96 * chunks[6].clone()])
+                .collect::<Vec<_>>()
+        });
+
+        for chunk in chunks {
+            meta.lookup(|meta| {
+                let q_range = meta.query_selector(q_range);
+                let chunk = meta.query_advice(chunk, Rotation::cur());
+                vec![(q_range * chunk, table)]
+            });
+        }
+
+        Self { value, chunks, q_range, q_64, q_96, table }
+    }
+
+    /// Fills the lookup table, must be called once per synthesis.
+    pub fn load_table(&self, layouter: &mut impl Layouter<Fp>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "range table",
+            |mut table| {
+                for i in 0..1 << CHUNK_BITS {
+                    table.assign_cell(|| "chunk", self.table, i, || Ok(Fp::from(i as u64)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Constrains `cell` to fit in `bits` bits, using the row at `offset` of `region`.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, Fp>,
+        offset: usize,
+        cell: &AssignedCell<Fp, Fp>,
+        bits: usize,
+    ) -> Result<(), Error> {
+        match bits {
+            64 => self.q_64.enable(region, offset)?,
+            96 => self.q_96.enable(region, offset)?,
+            112 => {}
+            _ => panic!("unsupported range check of {bits} bits"),
+        }
+        self.q_range.enable(region, offset)?;
+
+        cell.copy_advice(|| "value", region, self.value, offset)?;
+        let value = cell.value().map(fp_to_big);
+        for (i, column) in self.chunks.iter().enumerate() {
+            let chunk = value.as_ref().map(|v| big_to_fp(&((v >> (i * CHUNK_BITS)) & BigUint::from((1u64 << CHUNK_BITS) - 1))));
+            region.assign_advice(|| "chunk", *column, offset, || chunk.ok_or(Error::Synthesis))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,439 @@
+//! Persistence of proving and verifying keys.
+//!
+//! Keys are written with `CanonicalSerialize` behind a [`KeyHeader`] that identifies the circuit
+//! they were generated for: the circuit kind, the curve pair, a hash of the Poseidon parameters
+//! and a digest of the constraint system. Loading compares it with the header of the current
+//! circuit and refuses keys that don't match it, so that a stale key is reported instead of
+//! producing invalid proofs.
+//!
+//! The header is computed from the circuit passed to the setup, e.g. with its predicates, by
+//! synthesizing it, hence it should be computed once with [`KeyHeader::new`] and reused for all
+//! the keys of that circuit.
+//!
+//! Proving keys of the non-native circuits take gigabytes, hence they are streamed from a reader
+//! ([`read_proving_key`]) without buffering the whole file first, or deserialized from a memory
+//! mapping of the file ([`mmap_proving_key`]).
+
+use std::borrow::Borrow;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+use anyhow::{anyhow, bail};
+use ark_ec::bls12::Bls12Parameters;
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_ff::{Fp12ConfigWrapper, PrimeField, QuadExtField};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::curves::short_weierstrass::ProjectiveVar;
+use ark_r1cs_std::prelude::{AllocVar, CurveVar};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_sponge::poseidon::PoseidonConfig;
+use ark_sponge::Absorb;
+use memmap2::Mmap;
+use sha2::{Digest, Sha256};
+use crate::nonnative::NonNativeConfig;
+use crate::utils::{GtAbsorbable, Hash2Curve};
+use crate::yt6_776::YT6_776;
+use crate::{Bls12_381, Circuit, GeminiNativeCircuit, NonnativeCircuit, Parameters};
+
+const MAGIC: &[u8; 8] = b"ZKTLOCK\0";
+const VERSION: u8 = 2;
+
+/// Curves that keys are generated for, identified in [`KeyHeader`] by a name that stays the same
+/// across compilers and crate versions.
+pub trait CurveId {
+    /// Name of the curve, e.g. `"bls12-381"`.
+    const CURVE_ID: &'static str;
+}
+
+impl CurveId for Bls12_381 {
+    const CURVE_ID: &'static str = "bls12-381";
+}
+
+impl CurveId for ark_bls12_377::Bls12_377 {
+    const CURVE_ID: &'static str = "bls12-377";
+}
+
+impl CurveId for ark_bw6_761::BW6_761 {
+    const CURVE_ID: &'static str = "bw6-761";
+}
+
+impl CurveId for ark_bn254::Bn254 {
+    const CURVE_ID: &'static str = "bn254";
+}
+
+impl CurveId for YT6_776 {
+    const CURVE_ID: &'static str = "yt6-776";
+}
+
+/// Circuits whose keys can be persisted by this module.
+pub trait KeyedCircuit<F: PrimeField>: ConstraintSynthesizer<F> + Sized {
+    /// Name of the circuit kind, e.g. `"nonnative"`.
+    const KIND: &'static str;
+
+    /// [`CurveId::CURVE_ID`] of the pairing-friendly curve that messages are encrypted over.
+    const ENCRYPTION_CURVE: &'static str;
+
+    /// Field of the Poseidon instance of the circuit, which isn't the constraint field `F` when
+    /// the hashes are emulated, as in [`NonnativeCircuit`].
+    type PoseidonField: PrimeField;
+
+    /// Poseidon parameters used by the circuit.
+    fn poseidon() -> PoseidonConfig<Self::PoseidonField>;
+}
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>> KeyedCircuit<<E::G1 as CurveGroup>::BaseField> for Circuit<E, P>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable + CurveId,
+          E::TargetField: Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    const KIND: &'static str = "native";
+    const ENCRYPTION_CURVE: &'static str = E::CURVE_ID;
+    type PoseidonField = <E::G1 as CurveGroup>::BaseField;
+
+    fn poseidon() -> PoseidonConfig<<E::G1 as CurveGroup>::BaseField> {
+        Parameters::<E::G1>::default().poseidon
+    }
+}
+
+impl<PC: CurveGroup> KeyedCircuit<PC::BaseField> for NonnativeCircuit<PC>
+    where PC::BaseField: NonNativeConfig + Absorb
+{
+    const KIND: &'static str = "nonnative";
+    const ENCRYPTION_CURVE: &'static str = Bls12_381::CURVE_ID;
+    type PoseidonField = ark_bls12_381::Fq;
+
+    fn poseidon() -> PoseidonConfig<ark_bls12_381::Fq> {
+        Parameters::<ark_bls12_381::G1Projective>::default().poseidon
+    }
+}
+
+impl KeyedCircuit<ark_bls12_381::Fq> for GeminiNativeCircuit {
+    const KIND: &'static str = "gemini-native";
+    const ENCRYPTION_CURVE: &'static str = Bls12_381::CURVE_ID;
+    type PoseidonField = ark_bls12_381::Fq;
+
+    fn poseidon() -> PoseidonConfig<ark_bls12_381::Fq> {
+        Parameters::<ark_bls12_381::G1Projective>::default().poseidon
+    }
+}
+
+/// Metadata written in front of every key.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KeyHeader {
+    /// Kind of the circuit, see [`KeyedCircuit::KIND`].
+    pub circuit: String,
+    /// Encryption curve and the curve of the proof system, see [`CurveId`].
+    pub curves: (String, String),
+    /// SHA-256 of the Poseidon parameters.
+    pub poseidon: Vec<u8>,
+    /// SHA-256 of the constraint matrices.
+    pub constraint_system: Vec<u8>,
+}
+
+impl KeyHeader {
+    /// Computes the header of `circuit` proven over `E`, i.e. the blank circuit passed to the
+    /// setup, synthesized with the optimization goal `goal` of the proof system, see
+    /// [`TimelockProver::OPTIMIZATION_GOAL`](crate::prover::TimelockProver::OPTIMIZATION_GOAL).
+    ///
+    /// This synthesizes the circuit, which costs about as much as the constraint synthesis done
+    /// by the setup itself.
+    pub fn new<E: Pairing + CurveId, C: KeyedCircuit<E::ScalarField>>(circuit: C, goal: OptimizationGoal) -> anyhow::Result<Self> {
+        Ok(Self {
+            circuit: C::KIND.to_string(),
+            curves: (C::ENCRYPTION_CURVE.to_string(), E::CURVE_ID.to_string()),
+            poseidon: poseidon_digest(&C::poseidon())?,
+            constraint_system: constraint_system_digest(circuit, goal)?,
+        })
+    }
+
+    /// Fails with a description of the first field of `self` that differs from `expected`.
+    pub fn check(&self, expected: &Self) -> anyhow::Result<()> {
+        if self.circuit != expected.circuit {
+            bail!("key is for the {} circuit, expected {}", self.circuit, expected.circuit);
+        }
+        if self.curves != expected.curves {
+            bail!("key is for curves {:?}, expected {:?}", self.curves, expected.curves);
+        }
+        if self.poseidon != expected.poseidon {
+            bail!("key was generated with different Poseidon parameters");
+        }
+        if self.constraint_system != expected.constraint_system {
+            bail!("key was generated for a different constraint system");
+        }
+
+        Ok(())
+    }
+
+    fn write<W: Write>(&self, mut writer: W) -> anyhow::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        self.serialize_uncompressed(&mut writer)
+            .map_err(|e| anyhow!("error serializing key header: {e}"))
+    }
+
+    fn read<R: Read>(mut reader: R) -> anyhow::Result<Self> {
+        let mut magic = [0u8; 9];
+        reader.read_exact(&mut magic)
+            .map_err(|e| anyhow!("error reading key header: {e}"))?;
+        if &magic[..8] != MAGIC {
+            bail!("not a key file");
+        }
+        if magic[8] != VERSION {
+            bail!("unsupported key file version {}", magic[8]);
+        }
+
+        Self::deserialize_uncompressed(&mut reader)
+            .map_err(|e| anyhow!("error deserializing key header: {e}"))
+    }
+}
+
+/// Writes `key`, preceded by `header`.
+///
+/// Works for the keys of any proof system, e.g. the KZG keys of Gemini.
+pub fn write_key<K: CanonicalSerialize, W: Write>(
+    header: &KeyHeader,
+    key: &K,
+    mut writer: W,
+) -> anyhow::Result<()> {
+    header.write(&mut writer)?;
+    key.serialize_uncompressed(&mut writer)
+        .map_err(|e| anyhow!("error serializing key: {e}"))
+}
+
+/// Reads a key, failing if its header doesn't match `expected`, i.e. if it was written for
+/// another circuit.
+pub fn read_key<K: CanonicalDeserialize, R: Read>(
+    expected: &KeyHeader,
+    reader: R,
+) -> anyhow::Result<K> {
+    let mut reader = BufReader::new(reader);
+    KeyHeader::read(&mut reader)?.check(expected)?;
+    K::deserialize_uncompressed(&mut reader)
+        .map_err(|e| anyhow!("error deserializing key: {e}"))
+}
+
+/// Writes `pk`, preceded by `header`.
+pub fn write_proving_key<E: Pairing, W: Write>(
+    header: &KeyHeader,
+    pk: &ProvingKey<E>,
+    writer: W,
+) -> anyhow::Result<()> {
+    write_key(header, pk, writer)
+}
+
+/// Writes `vk`, preceded by `header`.
+pub fn write_verifying_key<E: Pairing, W: Write>(
+    header: &KeyHeader,
+    vk: &VerifyingKey<E>,
+    writer: W,
+) -> anyhow::Result<()> {
+    write_key(header, vk, writer)
+}
+
+/// Reads a verifying key, failing if its header doesn't match `expected`.
+pub fn read_verifying_key<E: Pairing, R: Read>(
+    expected: &KeyHeader,
+    reader: R,
+) -> anyhow::Result<VerifyingKey<E>> {
+    read_key(expected, reader)
+}
+
+/// Streams a proving key from `reader`, failing if its header doesn't match `expected`.
+///
+/// Points are not checked to be in the prime-order subgroup, which would dominate the loading
+/// time of large keys: proving keys are expected to come from a trusted location, and a
+/// malformed one can only produce proofs that don't verify.
+pub fn read_proving_key<E: Pairing, R: Read>(
+    expected: &KeyHeader,
+    reader: R,
+) -> anyhow::Result<ProvingKey<E>> {
+    let mut reader = BufReader::new(reader);
+    KeyHeader::read(&mut reader)?.check(expected)?;
+    ProvingKey::deserialize_uncompressed_unchecked(&mut reader)
+        .map_err(|e| anyhow!("error deserializing proving key: {e}"))
+}
+
+/// Deserializes a proving key from a memory mapping of the file at `path`, failing if its header
+/// doesn't match `expected`.
+///
+/// Like [`read_proving_key`], points are not checked to be in the prime-order subgroup. Pages of
+/// the file are read on demand by the OS rather than copied through a buffer.
+pub fn mmap_proving_key<E: Pairing, P: AsRef<Path>>(
+    expected: &KeyHeader,
+    path: P,
+) -> anyhow::Result<ProvingKey<E>> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .map_err(|e| anyhow!("error opening {}: {e}", path.display()))?;
+    // Safety: the mapping is only read while deserializing, and the file is not expected to be
+    // modified concurrently; if it is, deserialization fails or the header check catches it.
+    let mmap = unsafe { Mmap::map(&file) }
+        .map_err(|e| anyhow!("error mapping {}: {e}", path.display()))?;
+
+    let mut bytes = &mmap[..];
+    KeyHeader::read(&mut bytes)?.check(expected)?;
+    ProvingKey::deserialize_uncompressed_unchecked(&mut bytes)
+        .map_err(|e| anyhow!("error deserializing proving key: {e}"))
+}
+
+fn poseidon_digest<F: PrimeField>(params: &PoseidonConfig<F>) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    for n in [params.full_rounds, params.partial_rounds, params.rate, params.capacity] {
+        bytes.extend_from_slice(&(n as u64).to_le_bytes());
+    }
+    bytes.extend_from_slice(&params.alpha.to_le_bytes());
+    params.mds.serialize_uncompressed(&mut bytes)
+        .and_then(|_| params.ark.serialize_uncompressed(&mut bytes))
+        .map_err(|e| anyhow!("error serializing Poseidon parameters: {e}"))?;
+
+    Ok(Sha256::digest(&bytes).to_vec())
+}
+
+fn constraint_system_digest<F: PrimeField, C: ConstraintSynthesizer<F>>(circuit: C, goal: OptimizationGoal) -> anyhow::Result<Vec<u8>> {
+    // Same configuration as the setup of the proof system.
+    let cs = ConstraintSystem::<F>::new_ref();
+    cs.set_optimization_goal(goal);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone())
+        .map_err(|e| anyhow!("error synthesizing circuit: {e}"))?;
+    cs.finalize();
+    let matrices = cs.to_matrices()
+        .ok_or_else(|| anyhow!("constraint matrices are unavailable"))?;
+
+    let mut hash = Sha256::new();
+    for n in [matrices.num_instance_variables, matrices.num_witness_variables, matrices.num_constraints] {
+        hash.update(&(n as u64).to_le_bytes());
+    }
+    let mut bytes = vec![];
+    for matrix in [&matrices.a, &matrices.b, &matrices.c] {
+        for row in matrix {
+            bytes.clear();
+            bytes.extend_from_slice(&(row.len() as u64).to_le_bytes());
+            for (coeff, index) in row {
+                coeff.serialize_uncompressed(&mut bytes)
+                    .map_err(|e| anyhow!("error serializing constraint: {e}"))?;
+                bytes.extend_from_slice(&(*index as u64).to_le_bytes());
+            }
+            hash.update(&bytes);
+        }
+    }
+
+    Ok(hash.finalize().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Bn254, Fr};
+    use ark_bw6_761::BW6_761;
+    use ark_ff::Zero;
+    use ark_groth16::Groth16;
+    use ark_r1cs_std::eq::EqGadget;
+    use ark_relations::ns;
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+    use ark_snark::CircuitSpecificSetupSNARK;
+    use ark_std::test_rng;
+    use crate::poseidon::get_poseidon_params;
+    use crate::predicate::{Bound, RangePredicate};
+    use super::*;
+
+    /// Proves knowledge of `x` such that `x^N = y`.
+    struct Power<const N: usize>;
+
+    impl<const N: usize> ConstraintSynthesizer<Fr> for Power<N> {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let x = FpVar::new_witness(ns!(cs, "x"), || Err::<Fr, _>(SynthesisError::AssignmentMissing))?;
+            let y = FpVar::new_input(ns!(cs, "y"), || Err::<Fr, _>(SynthesisError::AssignmentMissing))?;
+            let mut acc = x.clone();
+            for _ in 1..N {
+                acc *= &x;
+            }
+            acc.enforce_equal(&y)
+        }
+    }
+
+    impl<const N: usize> KeyedCircuit<Fr> for Power<N> {
+        const KIND: &'static str = "power";
+        const ENCRYPTION_CURVE: &'static str = "none";
+        type PoseidonField = Fr;
+
+        fn poseidon() -> PoseidonConfig<Fr> {
+            get_poseidon_params::<ark_ed_on_bn254::EdwardsProjective>(2)
+        }
+    }
+
+    fn header<const N: usize>() -> KeyHeader {
+        KeyHeader::new::<Bn254, _>(Power::<N>, OptimizationGoal::Constraints).unwrap()
+    }
+
+    #[test]
+    fn test_keys_roundtrip() {
+        let mut rng = test_rng();
+        let (pk, vk) = Groth16::<Bn254>::setup(Power::<3>, &mut rng).unwrap();
+        let header = header::<3>();
+
+        let mut bytes = vec![];
+        write_verifying_key(&header, &vk, &mut bytes).unwrap();
+        assert_eq!(read_verifying_key::<Bn254, _>(&header, &bytes[..]).unwrap(), vk);
+
+        let mut bytes = vec![];
+        write_proving_key(&header, &pk, &mut bytes).unwrap();
+        assert_eq!(read_proving_key::<Bn254, _>(&header, &bytes[..]).unwrap(), pk);
+    }
+
+    #[test]
+    fn test_mmap_proving_key() {
+        let mut rng = test_rng();
+        let (pk, _) = Groth16::<Bn254>::setup(Power::<3>, &mut rng).unwrap();
+        let path = std::env::temp_dir().join(format!("zk-tlock-test-{}.pk", std::process::id()));
+
+        write_proving_key(&header::<3>(), &pk, File::create(&path).unwrap()).unwrap();
+        let loaded = mmap_proving_key::<Bn254, _>(&header::<3>(), &path);
+        let mismatch = mmap_proving_key::<Bn254, _>(&header::<4>(), &path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), pk);
+        assert!(mismatch.is_err());
+    }
+
+    #[test]
+    fn test_header_curve_ids() {
+        assert_eq!(header::<3>().curves, ("none".to_string(), "bn254".to_string()));
+    }
+
+    #[test]
+    fn test_keys_refuse_mismatch() {
+        let mut rng = test_rng();
+        let (pk, vk) = Groth16::<Bn254>::setup(Power::<3>, &mut rng).unwrap();
+
+        let mut bytes = vec![];
+        write_verifying_key(&header::<3>(), &vk, &mut bytes).unwrap();
+        let err = read_verifying_key::<Bn254, _>(&header::<4>(), &bytes[..]).unwrap_err();
+        assert!(err.to_string().contains("constraint system"));
+
+        let mut bytes = vec![];
+        write_proving_key(&header::<3>(), &pk, &mut bytes).unwrap();
+        assert!(read_proving_key::<Bn254, _>(&header::<4>(), &bytes[..]).is_err());
+
+        bytes[0] ^= 1;
+        let err = read_proving_key::<Bn254, _>(&header::<3>(), &bytes[..]).unwrap_err();
+        assert!(err.to_string().contains("not a key file"));
+    }
+
+    #[test]
+    fn test_header_covers_predicates() {
+        type TestCircuit = Circuit<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters>;
+        let predicate = || RangePredicate::new(Bound::Constant(ark_bls12_377::Fq::zero()), Bound::Public(ark_bls12_377::Fq::from(10u64)), 8);
+
+        let plain = KeyHeader::new::<BW6_761, _>(TestCircuit::blank(), OptimizationGoal::Constraints).unwrap();
+        let with_predicate = KeyHeader::new::<BW6_761, _>(TestCircuit::blank().with_predicate(predicate()), OptimizationGoal::Constraints).unwrap();
+        let err = with_predicate.check(&plain).unwrap_err();
+        assert!(err.to_string().contains("constraint system"));
+
+        let again = KeyHeader::new::<BW6_761, _>(TestCircuit::blank().with_predicate(predicate()), OptimizationGoal::Constraints).unwrap();
+        assert!(with_predicate.check(&again).is_ok());
+    }
+}
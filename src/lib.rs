@@ -1,10 +1,18 @@
 #![feature(inherent_associated_types)]
 
+pub mod bn254;
+pub mod bundle;
 pub mod circuits;
+pub mod h2c;
+pub mod halo2;
+pub mod keys;
 mod parameters;
 pub mod poseidon;
+pub mod predicate;
+pub mod prover;
+pub mod solidity;
 pub mod utils;
-mod nonnative;
+pub mod nonnative;
 pub mod yt6_776;
 
 pub use crate::circuits::*;
@@ -13,6 +21,7 @@ pub use ark_bls12_381::Bls12_381;
 use ark_ec::{CurveGroup};
 use ark_ec::pairing::Pairing;
 use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_sponge::poseidon::PoseidonConfig;
 use ark_std::rand::Rng;
 use ark_std::UniformRand;
@@ -52,7 +61,7 @@ impl<C: CurveGroup> UniformRand for Randomness<C> {
 
 pub type Plaintext<C: CurveGroup> = C::BaseField;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Ciphertext<C: CurveGroup> {
     u: C,
     v: C::BaseField,
@@ -1,32 +1,86 @@
 use std::borrow::Borrow;
+use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
-use std::ops::{Add, AddAssign};
-use ark_ec::{bls12, short_weierstrass::SWCurveConfig};
-use ark_ec::bls12::Bls12Parameters;
-use ark_ff::{Field, Fp12, One, PrimeField, QuadExtField, Zero};
+use std::ops::Add;
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ec::{CurveGroup, Group};
+use ark_ff::{BigInteger, BitIteratorBE, BitIteratorLE, Field, Fp2, Fp2Config, Fp12, Fp12Config, One, PrimeField, Zero};
+use ark_ff::fields::fp6_3over2::{Fp6, Fp6Config};
 use ark_r1cs_std::boolean::Boolean;
 use ark_r1cs_std::eq::EqGadget;
 use ark_r1cs_std::fields::{FieldOpsBounds, FieldVar};
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::prelude::*;
-use ark_r1cs_std::{R1CSVar, ToConstraintFieldGadget};
-use ark_r1cs_std::fields::nonnative::NonNativeFieldVar;
-use ark_relations::r1cs::{Namespace, SynthesisError};
+use ark_r1cs_std::{impl_bounded_ops, R1CSVar, ToConstraintFieldGadget};
+use ark_r1cs_std::fields::nonnative::{AllocatedNonNativeFieldVar, NonNativeFieldMulResultVar, NonNativeFieldVar};
+use ark_r1cs_std::fields::nonnative::params::OptimizationType;
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, OptimizationGoal, SynthesisError};
 use ark_sponge::constraints::AbsorbGadget;
+use ark_sponge::poseidon::PoseidonConfig;
+use ark_sponge::DuplexSpongeMode;
 
 pub type G1Var<CF> = NonNativeAffineVar<ark_bls12_381::g1::Parameters, CF>;
 pub type FqVar<CF> = NonNativeFieldVar<ark_bls12_381::Fq, CF>;
-pub type Fq2Var<CF> = Fp2Var<ark_bls12_381::Fq, CF>;
-pub type Fq6Var<CF> = Fp6Var<ark_bls12_381::Fq, CF>;
-pub type Fq12Var<CF> = Fp12Var<ark_bls12_381::Fq, CF>;
+pub type Fq2Var<CF> = Fp2Var<ark_bls12_381::Fq2Config, CF>;
+pub type Fq6Var<CF> = Fp6Var<ark_bls12_381::Fq6Config, CF>;
+pub type Fq12Var<CF> = Fp12Var<ark_bls12_381::Fq12Config, CF>;
+
+type Fp12BaseField<P> = <<<P as Fp12Config>::Fp6Config as Fp6Config>::Fp2Config as Fp2Config>::Fp;
+
+/// Optimization goal of the non-native gadgets over a constraint field.
+///
+/// `NonNativeFieldVar` derives its limb layout from the bit sizes of both fields and the
+/// optimization goal of the constraint system, and doesn't accept any other layout, hence the
+/// goal is the only setting there is. Circuits set it before allocating any variables.
+///
+/// Per field limb layouts aren't supported on purpose: every `AllocatedNonNativeFieldVar`
+/// operation recomputes the layout with `get_params`, so a wrapper type couldn't override it
+/// without reimplementing the whole non-native field gadget.
+pub trait NonNativeConfig: PrimeField {
+    /// Goal set on the constraint system before synthesis: `Constraints` for R1CS-count
+    /// driven backends like Groth16, `Weight` for backends that pay for matrix density.
+    const OPTIMIZATION_GOAL: OptimizationGoal;
+}
+
+impl NonNativeConfig for ark_bls12_377::Fq {
+    const OPTIMIZATION_GOAL: OptimizationGoal = OptimizationGoal::Constraints;
+}
+
+impl NonNativeConfig for ark_bn254::Fr {
+    const OPTIMIZATION_GOAL: OptimizationGoal = OptimizationGoal::Constraints;
+}
+
+/// Native counterpart of `NonNativeFieldVar::to_constraint_field`, which always
+/// uses the weight-optimized limb layout.
+pub fn nonnative_to_constraint_field<TF: PrimeField, CF: PrimeField>(elem: &TF) -> Vec<CF> {
+    AllocatedNonNativeFieldVar::<TF, CF>::get_limbs_representations(elem, OptimizationType::Weight)
+        .expect("limb decomposition of a field element is infallible")
+}
+
+/// Multiplies `fe` by a constant, skipping the reduction when the constant is `0` or `±1`,
+/// which is the case for most of the nonresidues and Frobenius coefficients of the tower.
+fn mul_by_small_constant<TF: PrimeField, CF: PrimeField>(
+    fe: &NonNativeFieldVar<TF, CF>,
+    c: TF,
+) -> Result<NonNativeFieldVar<TF, CF>, SynthesisError> {
+    if c.is_zero() {
+        Ok(NonNativeFieldVar::zero())
+    } else if c.is_one() {
+        Ok(fe.clone())
+    } else if (-c).is_one() {
+        fe.negate()
+    } else {
+        Ok(fe * c)
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct NonNativeAffineVar<P: SWCurveConfig, CF: PrimeField>
     where P::BaseField: PrimeField
 {
-    x: NonNativeFieldVar<P::BaseField, CF>,
-    y: NonNativeFieldVar<P::BaseField, CF>,
-    z: NonNativeFieldVar<P::BaseField, CF>,
+    pub(crate) x: NonNativeFieldVar<P::BaseField, CF>,
+    pub(crate) y: NonNativeFieldVar<P::BaseField, CF>,
+    pub(crate) z: NonNativeFieldVar<P::BaseField, CF>,
 }
 
 impl<P: SWCurveConfig + Clone, CF: PrimeField> NonNativeAffineVar<P, CF>
@@ -44,11 +98,15 @@ impl<P: SWCurveConfig + Clone, CF: PrimeField> NonNativeAffineVar<P, CF>
         y: NonNativeFieldVar<P::BaseField, CF>,
         z: NonNativeFieldVar<P::BaseField, CF>,
     ) -> Self {
-        Self { x, y, z: NonNativeFieldVar::one() }
+        Self { x, y, z }
     }
 
     fn zero() -> Self {
-        Self::new_inner(NonNativeFieldVar::zero(), NonNativeFieldVar::one(), NonNativeFieldVar::one())
+        Self::new_inner(NonNativeFieldVar::zero(), NonNativeFieldVar::one(), NonNativeFieldVar::zero())
+    }
+
+    fn constant(point: Affine<P>) -> Self {
+        Self::new(NonNativeFieldVar::constant(point.x), NonNativeFieldVar::constant(point.y))
     }
 
     fn is_zero(&self) -> Result<Boolean<CF>, SynthesisError> {
@@ -60,7 +118,7 @@ impl<P: SWCurveConfig + Clone, CF: PrimeField> NonNativeAffineVar<P, CF>
         bits: impl Iterator<Item = &'a Boolean<CF>>,
     ) -> Result<Self, SynthesisError> {
         let mut res = Self::zero();
-        let mut mul = Self::new(self.x.clone(), self.y.clone());
+        let mut mul = self.clone();
 
         for bit in bits {
             let tmp = res.clone() + &mul;
@@ -70,6 +128,51 @@ impl<P: SWCurveConfig + Clone, CF: PrimeField> NonNativeAffineVar<P, CF>
         Ok(res)
     }
 
+    /// Multiplies the constant `base` by the little-endian `bits` with 2-bit windows.
+    ///
+    /// The multiples of `base` for each window are precomputed and selected with lookups over
+    /// constants, so that the multiplication costs a single addition per window, instead of an
+    /// addition and a selection of points per bit in [`Self::scalar_mul_le`].
+    pub fn fixed_base_scalar_mul_le(
+        base: Projective<P>,
+        bits: &[Boolean<CF>],
+    ) -> Result<Self, SynthesisError> {
+        let mut res: Option<Self> = None;
+        let mut power = base;
+        let mut offset = Projective::<P>::zero();
+        for window in bits.chunks(2) {
+            // Entries are the `j + 1` rather than `j` multiples of the window's power, so that
+            // none of them is the point at infinity. The excess is subtracted once at the end.
+            let mut table = vec![power; 4];
+            for j in 1..4 {
+                table[j] = table[j - 1] + power;
+            }
+            let table = Projective::normalize_batch(&table);
+
+            let (lo, hi) = (&window[0], window.get(1).unwrap_or(&Boolean::FALSE));
+            let lookup = |coords: [P::BaseField; 4]| -> Result<NonNativeFieldVar<P::BaseField, CF>, SynthesisError> {
+                let [c0, c1, c2, c3] = coords.map(NonNativeFieldVar::constant);
+                hi.select(&lo.select(&c3, &c2)?, &lo.select(&c1, &c0)?)
+            };
+            let x = lookup([0, 1, 2, 3].map(|j| table[j].x))?;
+            let y = lookup([0, 1, 2, 3].map(|j| table[j].y))?;
+
+            let point = Self::new(x, y);
+            res = Some(match res {
+                Some(res) => res + &point,
+                None => point,
+            });
+            offset += power;
+            power = power.double().double();
+        }
+
+        let res = res.unwrap_or_else(Self::zero);
+        if offset.is_zero() {
+            return Ok(res);
+        }
+        Ok(res + &Self::constant((-offset).into_affine()))
+    }
+
     pub(crate) fn double(&self) -> Result<Self, SynthesisError> {
         Ok(self.clone().add(&self))
     }
@@ -95,6 +198,16 @@ fn mul_by_coeff_a<
     }
 }
 
+/// Computes `a * b + c * d` with a single reduction.
+fn sum_of_products<TF: PrimeField, CF: PrimeField>(
+    a: &NonNativeFieldVar<TF, CF>,
+    b: &NonNativeFieldVar<TF, CF>,
+    c: &NonNativeFieldVar<TF, CF>,
+    d: &NonNativeFieldVar<TF, CF>,
+) -> Result<NonNativeFieldVar<TF, CF>, SynthesisError> {
+    (a.mul_without_reduce(b)? + &c.mul_without_reduce(d)?).reduce()
+}
+
 impl<P: SWCurveConfig + Clone, CF: PrimeField> Add<&Self> for NonNativeAffineVar<P, CF>
     where P::BaseField: PrimeField
 {
@@ -133,9 +246,11 @@ impl<P: SWCurveConfig + Clone, CF: PrimeField> Add<&Self> for NonNativeAffineVar
         let bxz3 = &xz_pairs * three_b; // 28
         let b3_xz_pairs = mul_by_coeff_a::<P, CF>(&(&xx - &azz)) + &bxz3; // 30, 31, 32
 
-        let x = (&yy_m_bzz3 * &xy_pairs) - &yz_pairs * &b3_xz_pairs; // 35, 39, 40
-        let y = (&yy_p_bzz3 * &yy_m_bzz3) + &xx3_p_azz * b3_xz_pairs; // 24, 36, 37, 38
-        let z = (&yy_p_bzz3 * &yz_pairs) + xy_pairs * xx3_p_azz; // 41, 42, 43
+        // The products of the last steps are summed up unreduced, so that each coordinate
+        // is reduced only once.
+        let x = sum_of_products(&yy_m_bzz3, &xy_pairs, &yz_pairs.negate().unwrap(), &b3_xz_pairs).unwrap(); // 35, 39, 40
+        let y = sum_of_products(&yy_p_bzz3, &yy_m_bzz3, &xx3_p_azz, &b3_xz_pairs).unwrap(); // 24, 36, 37, 38
+        let z = sum_of_products(&yy_p_bzz3, &yz_pairs, &xy_pairs, &xx3_p_azz).unwrap(); // 41, 42, 43
 
         Self::new_inner(x, y, z)
     }
@@ -146,8 +261,9 @@ impl<P: SWCurveConfig, CF: PrimeField> EqGadget<CF> for NonNativeAffineVar<P, CF
     where P::BaseField: PrimeField
 {
     fn is_eq(&self, other: &Self) -> Result<Boolean<CF>, SynthesisError> {
-        let is_x_eq = self.x.is_eq(&other.x)?;
-        let is_y_eq = self.y.is_eq(&other.y)?;
+        // Points are kept in projective coordinates, so compare x1/z1 == x2/z2 and y1/z1 == y2/z2.
+        let is_x_eq = (&self.x * &other.z).is_eq(&(&other.x * &self.z))?;
+        let is_y_eq = (&self.y * &other.z).is_eq(&(&other.y * &self.z))?;
         is_x_eq.and(&is_y_eq)
     }
 }
@@ -167,83 +283,369 @@ impl<P: SWCurveConfig + Clone, CF: PrimeField> CondSelectGadget<CF> for NonNativ
         )?;
         let y = NonNativeFieldVar::<P::BaseField, CF>::conditionally_select(
             cond,
-            &true_value.x,
-            &false_value.x,
+            &true_value.y,
+            &false_value.y,
+        )?;
+        let z = NonNativeFieldVar::<P::BaseField, CF>::conditionally_select(
+            cond,
+            &true_value.z,
+            &false_value.z,
         )?;
 
-        Ok(NonNativeAffineVar::<P, CF>::new(x, y))
+        Ok(NonNativeAffineVar::<P, CF>::new_inner(x, y, z))
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct Fp2Var<T: PrimeField, B: PrimeField>
+/// This struct is the non-native equivalent of the quadratic extension field type
+/// in `ark-ff`, i.e. `ark_ff::Fp2<P>`, emulated over the constraint field `CF`.
+#[must_use]
+pub struct Fp2Var<P: Fp2Config, CF: PrimeField>
 {
     /// The zero-th coefficient of this field element.
-    pub c0: NonNativeFieldVar<T, B>,
+    pub c0: NonNativeFieldVar<P::Fp, CF>,
     /// The first coefficient of this field element.
-    pub c1: NonNativeFieldVar<T, B>,
+    pub c1: NonNativeFieldVar<P::Fp, CF>,
+    _params: PhantomData<P>,
 }
 
+impl<P: Fp2Config, CF: PrimeField> Fp2Var<P, CF>
+{
+    pub fn new(c0: NonNativeFieldVar<P::Fp, CF>, c1: NonNativeFieldVar<P::Fp, CF>) -> Self {
+        Self {
+            c0,
+            c1,
+            _params: PhantomData,
+        }
+    }
+
+    /// Multiplies a variable of the base field by the quadratic nonresidue
+    /// `P::NONRESIDUE` that is used to construct the extension field.
+    #[inline]
+    pub fn mul_base_field_by_nonresidue(fe: &NonNativeFieldVar<P::Fp, CF>) -> Result<NonNativeFieldVar<P::Fp, CF>, SynthesisError> {
+        mul_by_small_constant(fe, P::NONRESIDUE)
+    }
+
+    /// Multiplies `self` by a constant from the base field.
+    #[inline]
+    pub fn mul_by_base_field_constant(&self, fe: P::Fp) -> Result<Self, SynthesisError> {
+        let c0 = mul_by_small_constant(&self.c0, fe)?;
+        let c1 = mul_by_small_constant(&self.c1, fe)?;
+        Ok(Self::new(c0, c1))
+    }
 
-impl<T: PrimeField, B: PrimeField> Fp2Var<T, B>
+    /// Multiplies `self` by a constant, avoiding reductions for coefficients that are `0` or `±1`.
+    pub fn mul_by_constant(&self, fe: Fp2<P>) -> Result<Self, SynthesisError> {
+        let c0 = mul_by_small_constant(&self.c0, fe.c0)? + mul_by_small_constant(&self.c1, fe.c1 * P::NONRESIDUE)?;
+        let c1 = mul_by_small_constant(&self.c0, fe.c1)? + mul_by_small_constant(&self.c1, fe.c0)?;
+        Ok(Self::new(c0, c1))
+    }
+
+    /// Computes `self * other` without reducing the result, so that several products can be
+    /// summed up before a single reduction. Uses schoolbook multiplication, as unreduced
+    /// results can't be subtracted, which Karatsuba would require.
+    #[tracing::instrument(target = "r1cs", skip(self, other))]
+    pub fn mul_without_reduce(&self, other: &Self) -> Result<Fp2MulResultVar<P, CF>, SynthesisError> {
+        let nr_a1 = Self::mul_base_field_by_nonresidue(&self.c1)?;
+
+        let c0 = self.c0.mul_without_reduce(&other.c0)? + &nr_a1.mul_without_reduce(&other.c1)?;
+        let c1 = self.c0.mul_without_reduce(&other.c1)? + &self.c1.mul_without_reduce(&other.c0)?;
+        Ok(Fp2MulResultVar::new(c0, c1))
+    }
+
+    /// Computes `self * self` without reducing the result.
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    pub fn square_without_reduce(&self) -> Result<Fp2MulResultVar<P, CF>, SynthesisError> {
+        let nr_a1 = Self::mul_base_field_by_nonresidue(&self.c1)?;
+
+        let c0 = self.c0.mul_without_reduce(&self.c0)? + &nr_a1.mul_without_reduce(&self.c1)?;
+        let c1 = self.c0.double()?.mul_without_reduce(&self.c1)?;
+        Ok(Fp2MulResultVar::new(c0, c1))
+    }
+}
+
+/// Unreduced result of an `Fp2Var` multiplication. Results can be added together
+/// and then reduced back to an `Fp2Var` once.
+#[must_use]
+pub struct Fp2MulResultVar<P: Fp2Config, CF: PrimeField>
 {
-    pub fn new(c0: NonNativeFieldVar<T, B>, c1: NonNativeFieldVar<T, B>) -> Self {
+    /// The zero-th coefficient of this element.
+    pub c0: NonNativeFieldMulResultVar<P::Fp, CF>,
+    /// The first coefficient of this element.
+    pub c1: NonNativeFieldMulResultVar<P::Fp, CF>,
+    _params: PhantomData<P>,
+}
+
+impl<P: Fp2Config, CF: PrimeField> Fp2MulResultVar<P, CF>
+{
+    pub fn new(c0: NonNativeFieldMulResultVar<P::Fp, CF>, c1: NonNativeFieldMulResultVar<P::Fp, CF>) -> Self {
         Self {
             c0,
             c1,
+            _params: PhantomData,
+        }
+    }
+
+    /// Reduces the result back to an `Fp2Var`.
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    pub fn reduce(&self) -> Result<Fp2Var<P, CF>, SynthesisError> {
+        Ok(Fp2Var::new(self.c0.reduce()?, self.c1.reduce()?))
+    }
+}
+
+impl<P: Fp2Config, CF: PrimeField> Add<&Self> for Fp2MulResultVar<P, CF>
+{
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self::Output {
+        Self::new(self.c0 + &other.c0, self.c1 + &other.c1)
+    }
+}
+
+impl<P: Fp2Config, CF: PrimeField> Clone for Fp2Var<P, CF>
+{
+    fn clone(&self) -> Self {
+        Self::new(self.c0.clone(), self.c1.clone())
+    }
+}
+
+impl<P: Fp2Config, CF: PrimeField> Debug for Fp2Var<P, CF>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fp2Var")
+            .field("c0", &self.c0)
+            .field("c1", &self.c1)
+            .finish()
+    }
+}
+
+impl<P: Fp2Config, CF: PrimeField> R1CSVar<CF> for Fp2Var<P, CF>
+{
+    type Value = Fp2<P>;
+
+    fn cs(&self) -> ConstraintSystemRef<CF> {
+        [&self.c0, &self.c1].cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        match (self.c0.value(), self.c1.value()) {
+            (Ok(c0), Ok(c1)) => Ok(Fp2::new(c0, c1)),
+            (..) => Err(SynthesisError::AssignmentMissing),
         }
     }
+}
+
+impl<P: Fp2Config, CF: PrimeField> From<Boolean<CF>> for Fp2Var<P, CF>
+{
+    fn from(other: Boolean<CF>) -> Self {
+        let c0 = NonNativeFieldVar::from(other);
+        let c1 = NonNativeFieldVar::zero();
+        Self::new(c0, c1)
+    }
+}
+
+impl<'a, P: Fp2Config, CF: PrimeField> FieldOpsBounds<'a, Fp2<P>, Fp2Var<P, CF>> for Fp2Var<P, CF> {}
+impl<'a, P: Fp2Config, CF: PrimeField> FieldOpsBounds<'a, Fp2<P>, Fp2Var<P, CF>> for &'a Fp2Var<P, CF> {}
 
+impl<P: Fp2Config, CF: PrimeField> FieldVar<Fp2<P>, CF> for Fp2Var<P, CF>
+{
     fn zero() -> Self {
-        let c0 = NonNativeFieldVar::<T, B>::zero();
-        let c1 = NonNativeFieldVar::<T, B>::zero();
+        let c0 = NonNativeFieldVar::zero();
+        let c1 = NonNativeFieldVar::zero();
         Self::new(c0, c1)
     }
 
     fn one() -> Self {
-        let c0 = NonNativeFieldVar::<T, B>::one();
-        let c1 = NonNativeFieldVar::<T, B>::zero();
+        let c0 = NonNativeFieldVar::one();
+        let c1 = NonNativeFieldVar::zero();
+        Self::new(c0, c1)
+    }
+
+    fn constant(other: Fp2<P>) -> Self {
+        let c0 = NonNativeFieldVar::constant(other.c0);
+        let c1 = NonNativeFieldVar::constant(other.c1);
         Self::new(c0, c1)
     }
 
+    #[tracing::instrument(target = "r1cs", skip(self))]
     fn double(&self) -> Result<Self, SynthesisError> {
         let c0 = self.c0.double()?;
         let c1 = self.c1.double()?;
         Ok(Self::new(c0, c1))
     }
 
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn negate(&self) -> Result<Self, SynthesisError> {
+        let c0 = self.c0.negate()?;
+        let c1 = self.c1.negate()?;
+        Ok(Self::new(c0, c1))
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(self))]
     fn square(&self) -> Result<Self, SynthesisError> {
-        let c0 = self.c0.square()?;
-        let c1 = self.c1.square()?;
+        self.square_without_reduce()?.reduce()
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn frobenius_map(&self, power: usize) -> Result<Self, SynthesisError> {
+        let c0 = self.c0.frobenius_map(power)?;
+        let c1 = mul_by_small_constant(&self.c1.frobenius_map(power)?, P::FROBENIUS_COEFF_FP2_C1[power % 2])?;
         Ok(Self::new(c0, c1))
     }
 
-    fn add(&self, other: &Self) -> Self {
-        let mut c0 = &self.c0 + &other.c0;
-        let mut c1 = &self.c1 + &other.c1;
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn inverse(&self) -> Result<Self, SynthesisError> {
+        let mode = if self.is_constant() {
+            AllocationMode::Constant
+        } else {
+            AllocationMode::Witness
+        };
+        let inverse = Self::new_variable(
+            self.cs(),
+            || self.value().map(|f| f.inverse().unwrap_or_else(Fp2::zero)),
+            mode,
+        )?;
+        self.mul_equals(&inverse, &Self::one())?;
+        Ok(inverse)
+    }
+}
+
+impl_bounded_ops!(
+    Fp2Var<P, CF>,
+    Fp2<P>,
+    Add,
+    add,
+    AddAssign,
+    add_assign,
+    |this: &'a Fp2Var<P, CF>, other: &'a Fp2Var<P, CF>| {
+        let c0 = &this.c0 + &other.c0;
+        let c1 = &this.c1 + &other.c1;
         Fp2Var::new(c0, c1)
+    },
+    |this: &'a Fp2Var<P, CF>, other: Fp2<P>| {
+        this + Fp2Var::constant(other)
+    },
+    (P: Fp2Config, CF: PrimeField),
+);
+
+impl_bounded_ops!(
+    Fp2Var<P, CF>,
+    Fp2<P>,
+    Sub,
+    sub,
+    SubAssign,
+    sub_assign,
+    |this: &'a Fp2Var<P, CF>, other: &'a Fp2Var<P, CF>| {
+        let c0 = &this.c0 - &other.c0;
+        let c1 = &this.c1 - &other.c1;
+        Fp2Var::new(c0, c1)
+    },
+    |this: &'a Fp2Var<P, CF>, other: Fp2<P>| {
+        this - Fp2Var::constant(other)
+    },
+    (P: Fp2Config, CF: PrimeField),
+);
+
+impl_bounded_ops!(
+    Fp2Var<P, CF>,
+    Fp2<P>,
+    Mul,
+    mul,
+    MulAssign,
+    mul_assign,
+    |this: &'a Fp2Var<P, CF>, other: &'a Fp2Var<P, CF>| {
+        this.mul_without_reduce(other).and_then(|r| r.reduce()).unwrap()
+    },
+    |this: &'a Fp2Var<P, CF>, other: Fp2<P>| {
+        this.mul_by_constant(other).unwrap()
+    },
+    (P: Fp2Config, CF: PrimeField),
+);
+
+impl<P: Fp2Config, CF: PrimeField> EqGadget<CF> for Fp2Var<P, CF>
+{
+    fn is_eq(&self, other: &Self) -> Result<Boolean<CF>, SynthesisError> {
+        let b0 = self.c0.is_eq(&other.c0)?;
+        let b1 = self.c1.is_eq(&other.c1)?;
+        b0.and(&b1)
     }
 
-    pub fn mul(&self, other: &Self) -> Self {
-        let mut c0 = &self.c0 * &other.c0;
-        let mut c1 = &self.c1 * &other.c1;
-        Self::new(c0, c1)
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        condition: &Boolean<CF>,
+    ) -> Result<(), SynthesisError> {
+        self.c0.conditional_enforce_equal(&other.c0, condition)?;
+        self.c1.conditional_enforce_equal(&other.c1, condition)
+    }
+
+    fn conditional_enforce_not_equal(
+        &self,
+        other: &Self,
+        condition: &Boolean<CF>,
+    ) -> Result<(), SynthesisError> {
+        let is_equal = self.is_eq(other)?;
+        is_equal
+            .and(condition)?
+            .enforce_equal(&Boolean::Constant(false))
+    }
+}
+
+impl<P: Fp2Config, CF: PrimeField> ToBitsGadget<CF> for Fp2Var<P, CF>
+{
+    fn to_bits_le(&self) -> Result<Vec<Boolean<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_bits_le()?;
+        let mut c1 = self.c1.to_bits_le()?;
+        c0.append(&mut c1);
+        Ok(c0)
+    }
+
+    fn to_non_unique_bits_le(&self) -> Result<Vec<Boolean<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_non_unique_bits_le()?;
+        let mut c1 = self.c1.to_non_unique_bits_le()?;
+        c0.append(&mut c1);
+        Ok(c0)
+    }
+}
+
+impl<P: Fp2Config, CF: PrimeField> ToBytesGadget<CF> for Fp2Var<P, CF>
+{
+    fn to_bytes(&self) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_bytes()?;
+        let mut c1 = self.c1.to_bytes()?;
+        c0.append(&mut c1);
+        Ok(c0)
+    }
+
+    fn to_non_unique_bytes(&self) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_non_unique_bytes()?;
+        let mut c1 = self.c1.to_non_unique_bytes()?;
+        c0.append(&mut c1);
+        Ok(c0)
+    }
+}
+
+impl<P: Fp2Config, CF: PrimeField> ToConstraintFieldGadget<CF> for Fp2Var<P, CF>
+{
+    fn to_constraint_field(&self) -> Result<Vec<FpVar<CF>>, SynthesisError> {
+        let mut res = self.c0.to_constraint_field()?;
+        res.extend(self.c1.to_constraint_field()?);
+        Ok(res)
     }
 }
 
-impl<TF: PrimeField, BF: PrimeField> CondSelectGadget<BF> for Fp2Var<TF, BF>
+impl<P: Fp2Config, CF: PrimeField> CondSelectGadget<CF> for Fp2Var<P, CF>
 {
     fn conditionally_select(
-        cond: &Boolean<BF>,
+        cond: &Boolean<CF>,
         true_value: &Self,
         false_value: &Self,
     ) -> Result<Self, SynthesisError> {
-        let c0 = NonNativeFieldVar::<TF, BF>::conditionally_select(
+        let c0 = NonNativeFieldVar::<P::Fp, CF>::conditionally_select(
             cond,
             &true_value.c0,
             &false_value.c0,
         )?;
-        let c1 = NonNativeFieldVar::<TF, BF>::conditionally_select(
+        let c1 = NonNativeFieldVar::<P::Fp, CF>::conditionally_select(
             cond,
             &true_value.c1,
             &false_value.c1,
@@ -253,41 +655,216 @@ impl<TF: PrimeField, BF: PrimeField> CondSelectGadget<BF> for Fp2Var<TF, BF>
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct Fp6Var<T: PrimeField, B: PrimeField>
+impl<P: Fp2Config, CF: PrimeField> AllocVar<Fp2<P>, CF> for Fp2Var<P, CF>
+{
+    fn new_variable<T: Borrow<Fp2<P>>>(
+        cs: impl Into<Namespace<CF>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let (c0, c1) = match f() {
+            Ok(fe) => (Ok(fe.borrow().c0), Ok(fe.borrow().c1)),
+            Err(_) => (
+                Err(SynthesisError::AssignmentMissing),
+                Err(SynthesisError::AssignmentMissing),
+            ),
+        };
+
+        let c0 = NonNativeFieldVar::new_variable(ark_relations::ns!(cs, "c0"), || c0, mode)?;
+        let c1 = NonNativeFieldVar::new_variable(ark_relations::ns!(cs, "c1"), || c1, mode)?;
+        Ok(Self::new(c0, c1))
+    }
+}
+
+/// This struct is the non-native equivalent of the sextic extension field type
+/// in `ark-ff`, i.e. `ark_ff::fp6_3over2::Fp6<P>`, emulated over the constraint field `CF`.
+#[must_use]
+pub struct Fp6Var<P: Fp6Config, CF: PrimeField>
 {
     /// The zero-th coefficient of this field element.
-    pub c0: Fp2Var<T, B>,
+    pub c0: Fp2Var<P::Fp2Config, CF>,
     /// The first coefficient of this field element.
-    pub c1: Fp2Var<T, B>,
+    pub c1: Fp2Var<P::Fp2Config, CF>,
     /// The second coefficient of this field element.
-    pub c2: Fp2Var<T, B>,
+    pub c2: Fp2Var<P::Fp2Config, CF>,
+    _params: PhantomData<P>,
 }
 
-impl<T: PrimeField, B: PrimeField> Fp6Var<T, B>
+impl<P: Fp6Config, CF: PrimeField> Fp6Var<P, CF>
 {
-    pub fn new(c0: Fp2Var<T, B>, c1: Fp2Var<T, B>, c2: Fp2Var<T, B>) -> Self {
+    pub fn new(c0: Fp2Var<P::Fp2Config, CF>, c1: Fp2Var<P::Fp2Config, CF>, c2: Fp2Var<P::Fp2Config, CF>) -> Self {
         Self {
             c0,
             c1,
             c2,
+            _params: PhantomData,
+        }
+    }
+
+    /// Multiplies a variable of the base field by the cubic nonresidue
+    /// `P::NONRESIDUE` that is used to construct the extension field.
+    #[inline]
+    pub fn mul_base_field_by_nonresidue(fe: &Fp2Var<P::Fp2Config, CF>) -> Result<Fp2Var<P::Fp2Config, CF>, SynthesisError> {
+        fe.mul_by_constant(P::NONRESIDUE)
+    }
+
+    /// Multiplies `self` by a constant from the base field.
+    #[inline]
+    pub fn mul_by_base_field_constant(&self, fe: Fp2<P::Fp2Config>) -> Result<Self, SynthesisError> {
+        let c0 = self.c0.mul_by_constant(fe)?;
+        let c1 = self.c1.mul_by_constant(fe)?;
+        let c2 = self.c2.mul_by_constant(fe)?;
+        Ok(Self::new(c0, c1, c2))
+    }
+
+    /// Computes `self * other` without reducing the result:
+    ///     result.c0 = a0 b0 + β(a1 b2 + a2 b1)
+    ///     result.c1 = a0 b1 + a1 b0 + β a2 b2
+    ///     result.c2 = a0 b2 + a1 b1 + a2 b0
+    #[tracing::instrument(target = "r1cs", skip(self, other))]
+    pub fn mul_without_reduce(&self, other: &Self) -> Result<Fp6MulResultVar<P, CF>, SynthesisError> {
+        let (a0, a1, a2) = (&self.c0, &self.c1, &self.c2);
+        let (b0, b1, b2) = (&other.c0, &other.c1, &other.c2);
+        let nr_a1 = Self::mul_base_field_by_nonresidue(a1)?;
+        let nr_a2 = Self::mul_base_field_by_nonresidue(a2)?;
+
+        let c0 = a0.mul_without_reduce(b0)? + &nr_a1.mul_without_reduce(b2)? + &nr_a2.mul_without_reduce(b1)?;
+        let c1 = a0.mul_without_reduce(b1)? + &a1.mul_without_reduce(b0)? + &nr_a2.mul_without_reduce(b2)?;
+        let c2 = a0.mul_without_reduce(b2)? + &a1.mul_without_reduce(b1)? + &a2.mul_without_reduce(b0)?;
+        Ok(Fp6MulResultVar::new(c0, c1, c2))
+    }
+
+    /// Computes `self * self` without reducing the result.
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    pub fn square_without_reduce(&self) -> Result<Fp6MulResultVar<P, CF>, SynthesisError> {
+        let (a0, a1, a2) = (&self.c0, &self.c1, &self.c2);
+        let a0_double = a0.double()?;
+        let nr_a2 = Self::mul_base_field_by_nonresidue(a2)?;
+
+        let c0 = a0.square_without_reduce()? + &nr_a2.double()?.mul_without_reduce(a1)?;
+        let c1 = a0_double.mul_without_reduce(a1)? + &nr_a2.mul_without_reduce(a2)?;
+        let c2 = a0_double.mul_without_reduce(a2)? + &a1.square_without_reduce()?;
+        Ok(Fp6MulResultVar::new(c0, c1, c2))
+    }
+}
+
+/// Unreduced result of an `Fp6Var` multiplication, see [`Fp2MulResultVar`].
+#[must_use]
+pub struct Fp6MulResultVar<P: Fp6Config, CF: PrimeField>
+{
+    /// The zero-th coefficient of this element.
+    pub c0: Fp2MulResultVar<P::Fp2Config, CF>,
+    /// The first coefficient of this element.
+    pub c1: Fp2MulResultVar<P::Fp2Config, CF>,
+    /// The second coefficient of this element.
+    pub c2: Fp2MulResultVar<P::Fp2Config, CF>,
+    _params: PhantomData<P>,
+}
+
+impl<P: Fp6Config, CF: PrimeField> Fp6MulResultVar<P, CF>
+{
+    pub fn new(
+        c0: Fp2MulResultVar<P::Fp2Config, CF>,
+        c1: Fp2MulResultVar<P::Fp2Config, CF>,
+        c2: Fp2MulResultVar<P::Fp2Config, CF>,
+    ) -> Self {
+        Self {
+            c0,
+            c1,
+            c2,
+            _params: PhantomData,
+        }
+    }
+
+    /// Reduces the result back to an `Fp6Var`.
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    pub fn reduce(&self) -> Result<Fp6Var<P, CF>, SynthesisError> {
+        Ok(Fp6Var::new(self.c0.reduce()?, self.c1.reduce()?, self.c2.reduce()?))
+    }
+}
+
+impl<P: Fp6Config, CF: PrimeField> Add<&Self> for Fp6MulResultVar<P, CF>
+{
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self::Output {
+        Self::new(self.c0 + &other.c0, self.c1 + &other.c1, self.c2 + &other.c2)
+    }
+}
+
+impl<P: Fp6Config, CF: PrimeField> Clone for Fp6Var<P, CF>
+{
+    fn clone(&self) -> Self {
+        Self::new(self.c0.clone(), self.c1.clone(), self.c2.clone())
+    }
+}
+
+impl<P: Fp6Config, CF: PrimeField> Debug for Fp6Var<P, CF>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fp6Var")
+            .field("c0", &self.c0)
+            .field("c1", &self.c1)
+            .field("c2", &self.c2)
+            .finish()
+    }
+}
+
+impl<P: Fp6Config, CF: PrimeField> R1CSVar<CF> for Fp6Var<P, CF>
+{
+    type Value = Fp6<P>;
+
+    fn cs(&self) -> ConstraintSystemRef<CF> {
+        [&self.c0, &self.c1, &self.c2].cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        match (self.c0.value(), self.c1.value(), self.c2.value()) {
+            (Ok(c0), Ok(c1), Ok(c2)) => Ok(Fp6::new(c0, c1, c2)),
+            (..) => Err(SynthesisError::AssignmentMissing),
         }
     }
+}
+
+impl<P: Fp6Config, CF: PrimeField> From<Boolean<CF>> for Fp6Var<P, CF>
+{
+    fn from(other: Boolean<CF>) -> Self {
+        let c0 = Fp2Var::from(other);
+        let c1 = Fp2Var::zero();
+        let c2 = Fp2Var::zero();
+        Self::new(c0, c1, c2)
+    }
+}
+
+impl<'a, P: Fp6Config, CF: PrimeField> FieldOpsBounds<'a, Fp6<P>, Fp6Var<P, CF>> for Fp6Var<P, CF> {}
+impl<'a, P: Fp6Config, CF: PrimeField> FieldOpsBounds<'a, Fp6<P>, Fp6Var<P, CF>> for &'a Fp6Var<P, CF> {}
 
+impl<P: Fp6Config, CF: PrimeField> FieldVar<Fp6<P>, CF> for Fp6Var<P, CF>
+{
     fn zero() -> Self {
-        let c0 = Fp2Var::<T, B>::zero();
-        let c1 = Fp2Var::<T, B>::zero();
-        let c2 = Fp2Var::<T, B>::zero();
+        let c0 = Fp2Var::zero();
+        let c1 = Fp2Var::zero();
+        let c2 = Fp2Var::zero();
         Self::new(c0, c1, c2)
     }
 
     fn one() -> Self {
-        let c0 = Fp2Var::<T, B>::one();
-        let c1 = Fp2Var::<T, B>::zero();
-        let c2 = Fp2Var::<T, B>::zero();
+        let c0 = Fp2Var::one();
+        let c1 = Fp2Var::zero();
+        let c2 = Fp2Var::zero();
         Self::new(c0, c1, c2)
     }
 
+    fn constant(other: Fp6<P>) -> Self {
+        let c0 = Fp2Var::constant(other.c0);
+        let c1 = Fp2Var::constant(other.c1);
+        let c2 = Fp2Var::constant(other.c2);
+        Self::new(c0, c1, c2)
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(self))]
     fn double(&self) -> Result<Self, SynthesisError> {
         let c0 = self.c0.double()?;
         let c1 = self.c1.double()?;
@@ -295,46 +872,199 @@ impl<T: PrimeField, B: PrimeField> Fp6Var<T, B>
         Ok(Self::new(c0, c1, c2))
     }
 
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn negate(&self) -> Result<Self, SynthesisError> {
+        let c0 = self.c0.negate()?;
+        let c1 = self.c1.negate()?;
+        let c2 = self.c2.negate()?;
+        Ok(Self::new(c0, c1, c2))
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(self))]
     fn square(&self) -> Result<Self, SynthesisError> {
-        let c0 = self.c0.square()?;
-        let c1 = self.c1.square()?;
-        let c2 = self.c2.square()?;
+        self.square_without_reduce()?.reduce()
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn frobenius_map(&self, power: usize) -> Result<Self, SynthesisError> {
+        let c0 = self.c0.frobenius_map(power)?;
+        let c1 = self.c1.frobenius_map(power)?.mul_by_constant(P::FROBENIUS_COEFF_FP6_C1[power % 6])?;
+        let c2 = self.c2.frobenius_map(power)?.mul_by_constant(P::FROBENIUS_COEFF_FP6_C2[power % 6])?;
         Ok(Self::new(c0, c1, c2))
     }
 
-    fn add(&self, other: &Self) -> Self {
-        let mut c0 = self.c0.add(&other.c0);
-        let mut c1 = self.c1.add(&other.c1);
-        let mut c2 = self.c2.add(&other.c2);
-        Self::new(c0, c1, c2)
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn inverse(&self) -> Result<Self, SynthesisError> {
+        let mode = if self.is_constant() {
+            AllocationMode::Constant
+        } else {
+            AllocationMode::Witness
+        };
+        let inverse = Self::new_variable(
+            self.cs(),
+            || self.value().map(|f| f.inverse().unwrap_or_else(Fp6::zero)),
+            mode,
+        )?;
+        self.mul_equals(&inverse, &Self::one())?;
+        Ok(inverse)
     }
+}
 
-    pub fn mul(&self, other: &Self) -> Self {
-        let mut c0 = self.c0.mul(&other.c0);
-        let mut c1 = self.c1.mul(&other.c1);
-        let mut c2 = self.c2.mul(&other.c2);
-        Self::new(c0, c1, c2)
+impl_bounded_ops!(
+    Fp6Var<P, CF>,
+    Fp6<P>,
+    Add,
+    add,
+    AddAssign,
+    add_assign,
+    |this: &'a Fp6Var<P, CF>, other: &'a Fp6Var<P, CF>| {
+        let c0 = &this.c0 + &other.c0;
+        let c1 = &this.c1 + &other.c1;
+        let c2 = &this.c2 + &other.c2;
+        Fp6Var::new(c0, c1, c2)
+    },
+    |this: &'a Fp6Var<P, CF>, other: Fp6<P>| {
+        this + Fp6Var::constant(other)
+    },
+    (P: Fp6Config, CF: PrimeField),
+);
+
+impl_bounded_ops!(
+    Fp6Var<P, CF>,
+    Fp6<P>,
+    Sub,
+    sub,
+    SubAssign,
+    sub_assign,
+    |this: &'a Fp6Var<P, CF>, other: &'a Fp6Var<P, CF>| {
+        let c0 = &this.c0 - &other.c0;
+        let c1 = &this.c1 - &other.c1;
+        let c2 = &this.c2 - &other.c2;
+        Fp6Var::new(c0, c1, c2)
+    },
+    |this: &'a Fp6Var<P, CF>, other: Fp6<P>| {
+        this - Fp6Var::constant(other)
+    },
+    (P: Fp6Config, CF: PrimeField),
+);
+
+impl_bounded_ops!(
+    Fp6Var<P, CF>,
+    Fp6<P>,
+    Mul,
+    mul,
+    MulAssign,
+    mul_assign,
+    |this: &'a Fp6Var<P, CF>, other: &'a Fp6Var<P, CF>| {
+        this.mul_without_reduce(other).and_then(|r| r.reduce()).unwrap()
+    },
+    |this: &'a Fp6Var<P, CF>, other: Fp6<P>| {
+        this * Fp6Var::constant(other)
+    },
+    (P: Fp6Config, CF: PrimeField),
+);
+
+impl<P: Fp6Config, CF: PrimeField> EqGadget<CF> for Fp6Var<P, CF>
+{
+    fn is_eq(&self, other: &Self) -> Result<Boolean<CF>, SynthesisError> {
+        let b0 = self.c0.is_eq(&other.c0)?;
+        let b1 = self.c1.is_eq(&other.c1)?;
+        let b2 = self.c2.is_eq(&other.c2)?;
+        b0.and(&b1)?.and(&b2)
+    }
+
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        condition: &Boolean<CF>,
+    ) -> Result<(), SynthesisError> {
+        self.c0.conditional_enforce_equal(&other.c0, condition)?;
+        self.c1.conditional_enforce_equal(&other.c1, condition)?;
+        self.c2.conditional_enforce_equal(&other.c2, condition)
+    }
+
+    fn conditional_enforce_not_equal(
+        &self,
+        other: &Self,
+        condition: &Boolean<CF>,
+    ) -> Result<(), SynthesisError> {
+        let is_equal = self.is_eq(other)?;
+        is_equal
+            .and(condition)?
+            .enforce_equal(&Boolean::Constant(false))
+    }
+}
+
+impl<P: Fp6Config, CF: PrimeField> ToBitsGadget<CF> for Fp6Var<P, CF>
+{
+    fn to_bits_le(&self) -> Result<Vec<Boolean<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_bits_le()?;
+        let mut c1 = self.c1.to_bits_le()?;
+        let mut c2 = self.c2.to_bits_le()?;
+        c0.append(&mut c1);
+        c0.append(&mut c2);
+        Ok(c0)
+    }
+
+    fn to_non_unique_bits_le(&self) -> Result<Vec<Boolean<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_non_unique_bits_le()?;
+        let mut c1 = self.c1.to_non_unique_bits_le()?;
+        let mut c2 = self.c2.to_non_unique_bits_le()?;
+        c0.append(&mut c1);
+        c0.append(&mut c2);
+        Ok(c0)
+    }
+}
+
+impl<P: Fp6Config, CF: PrimeField> ToBytesGadget<CF> for Fp6Var<P, CF>
+{
+    fn to_bytes(&self) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_bytes()?;
+        let mut c1 = self.c1.to_bytes()?;
+        let mut c2 = self.c2.to_bytes()?;
+        c0.append(&mut c1);
+        c0.append(&mut c2);
+        Ok(c0)
+    }
+
+    fn to_non_unique_bytes(&self) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_non_unique_bytes()?;
+        let mut c1 = self.c1.to_non_unique_bytes()?;
+        let mut c2 = self.c2.to_non_unique_bytes()?;
+        c0.append(&mut c1);
+        c0.append(&mut c2);
+        Ok(c0)
     }
 }
 
-impl<TF: PrimeField, BF: PrimeField> CondSelectGadget<BF> for Fp6Var<TF, BF>
+impl<P: Fp6Config, CF: PrimeField> ToConstraintFieldGadget<CF> for Fp6Var<P, CF>
+{
+    fn to_constraint_field(&self) -> Result<Vec<FpVar<CF>>, SynthesisError> {
+        let mut res = self.c0.to_constraint_field()?;
+        res.extend(self.c1.to_constraint_field()?);
+        res.extend(self.c2.to_constraint_field()?);
+        Ok(res)
+    }
+}
+
+impl<P: Fp6Config, CF: PrimeField> CondSelectGadget<CF> for Fp6Var<P, CF>
 {
     fn conditionally_select(
-        cond: &Boolean<BF>,
+        cond: &Boolean<CF>,
         true_value: &Self,
         false_value: &Self,
     ) -> Result<Self, SynthesisError> {
-        let c0 = Fp2Var::<TF, BF>::conditionally_select(
+        let c0 = Fp2Var::<P::Fp2Config, CF>::conditionally_select(
             cond,
             &true_value.c0,
             &false_value.c0,
         )?;
-        let c1 = Fp2Var::<TF, BF>::conditionally_select(
+        let c1 = Fp2Var::<P::Fp2Config, CF>::conditionally_select(
             cond,
             &true_value.c1,
             &false_value.c1,
         )?;
-        let c2 = Fp2Var::<TF, BF>::conditionally_select(
+        let c2 = Fp2Var::<P::Fp2Config, CF>::conditionally_select(
             cond,
             &true_value.c2,
             &false_value.c2,
@@ -344,109 +1074,368 @@ impl<TF: PrimeField, BF: PrimeField> CondSelectGadget<BF> for Fp6Var<TF, BF>
     }
 }
 
+impl<P: Fp6Config, CF: PrimeField> AllocVar<Fp6<P>, CF> for Fp6Var<P, CF>
+{
+    fn new_variable<T: Borrow<Fp6<P>>>(
+        cs: impl Into<Namespace<CF>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let (c0, c1, c2) = match f() {
+            Ok(fe) => (Ok(fe.borrow().c0), Ok(fe.borrow().c1), Ok(fe.borrow().c2)),
+            Err(_) => (
+                Err(SynthesisError::AssignmentMissing),
+                Err(SynthesisError::AssignmentMissing),
+                Err(SynthesisError::AssignmentMissing),
+            ),
+        };
+
+        let c0 = Fp2Var::new_variable(ark_relations::ns!(cs, "c0"), || c0, mode)?;
+        let c1 = Fp2Var::new_variable(ark_relations::ns!(cs, "c1"), || c1, mode)?;
+        let c2 = Fp2Var::new_variable(ark_relations::ns!(cs, "c2"), || c2, mode)?;
+        Ok(Self::new(c0, c1, c2))
+    }
+}
 
-#[derive(Clone, Debug)]
-pub struct Fp12Var<T: PrimeField, B: PrimeField>
+/// This struct is the non-native equivalent of the degree-12 tower extension field type
+/// in `ark-ff`, i.e. `ark_ff::Fp12<P>`, emulated over the constraint field `CF`.
+#[must_use]
+pub struct Fp12Var<P: Fp12Config, CF: PrimeField>
 {
     /// The zero-th coefficient of this field element.
-    pub c0: Fp6Var<T, B>,
+    pub c0: Fp6Var<P::Fp6Config, CF>,
     /// The first coefficient of this field element.
-    pub c1: Fp6Var<T, B>,
+    pub c1: Fp6Var<P::Fp6Config, CF>,
+    _params: PhantomData<P>,
 }
 
+impl<P: Fp12Config, CF: PrimeField> Fp12Var<P, CF>
+{
+    pub fn new(c0: Fp6Var<P::Fp6Config, CF>, c1: Fp6Var<P::Fp6Config, CF>) -> Self {
+        Self {
+            c0,
+            c1,
+            _params: PhantomData,
+        }
+    }
+
+    /// Multiplies a variable of the base field by the quadratic nonresidue `v = (0, 1, 0)`
+    /// that is used to construct the extension field. This is a cheap coefficient shift,
+    /// same as `Fp12Config::mul_fp6_by_nonresidue_in_place`.
+    #[inline]
+    pub fn mul_base_field_by_nonresidue(fe: &Fp6Var<P::Fp6Config, CF>) -> Result<Fp6Var<P::Fp6Config, CF>, SynthesisError> {
+        let c0 = Fp6Var::<P::Fp6Config, CF>::mul_base_field_by_nonresidue(&fe.c2)?;
+        Ok(Fp6Var::new(c0, fe.c0.clone(), fe.c1.clone()))
+    }
 
-impl<T: PrimeField, B: PrimeField> Fp12Var<T, B>
+    /// Computes `self * other` without reducing the result:
+    ///     result.c0 = a0 b0 + v a1 b1
+    ///     result.c1 = a0 b1 + a1 b0
+    #[tracing::instrument(target = "r1cs", skip(self, other))]
+    pub fn mul_without_reduce(&self, other: &Self) -> Result<Fp12MulResultVar<P, CF>, SynthesisError> {
+        let nr_a1 = Self::mul_base_field_by_nonresidue(&self.c1)?;
+
+        let c0 = self.c0.mul_without_reduce(&other.c0)? + &nr_a1.mul_without_reduce(&other.c1)?;
+        let c1 = self.c0.mul_without_reduce(&other.c1)? + &self.c1.mul_without_reduce(&other.c0)?;
+        Ok(Fp12MulResultVar::new(c0, c1))
+    }
+
+    /// Computes `self * self` without reducing the result.
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    pub fn square_without_reduce(&self) -> Result<Fp12MulResultVar<P, CF>, SynthesisError> {
+        let nr_a1 = Self::mul_base_field_by_nonresidue(&self.c1)?;
+
+        let c0 = self.c0.square_without_reduce()? + &nr_a1.mul_without_reduce(&self.c1)?;
+        let c1 = self.c0.double()?.mul_without_reduce(&self.c1)?;
+        Ok(Fp12MulResultVar::new(c0, c1))
+    }
+}
+
+/// Unreduced result of an `Fp12Var` multiplication, see [`Fp2MulResultVar`].
+#[must_use]
+pub struct Fp12MulResultVar<P: Fp12Config, CF: PrimeField>
+{
+    /// The zero-th coefficient of this element.
+    pub c0: Fp6MulResultVar<P::Fp6Config, CF>,
+    /// The first coefficient of this element.
+    pub c1: Fp6MulResultVar<P::Fp6Config, CF>,
+    _params: PhantomData<P>,
+}
+
+impl<P: Fp12Config, CF: PrimeField> Fp12MulResultVar<P, CF>
 {
-    pub fn new(c0: Fp6Var<T, B>, c1: Fp6Var<T, B>) -> Self {
+    pub fn new(c0: Fp6MulResultVar<P::Fp6Config, CF>, c1: Fp6MulResultVar<P::Fp6Config, CF>) -> Self {
         Self {
             c0,
             c1,
+            _params: PhantomData,
+        }
+    }
+
+    /// Reduces the result back to an `Fp12Var`.
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    pub fn reduce(&self) -> Result<Fp12Var<P, CF>, SynthesisError> {
+        Ok(Fp12Var::new(self.c0.reduce()?, self.c1.reduce()?))
+    }
+}
+
+impl<P: Fp12Config, CF: PrimeField> Add<&Self> for Fp12MulResultVar<P, CF>
+{
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self::Output {
+        Self::new(self.c0 + &other.c0, self.c1 + &other.c1)
+    }
+}
+
+impl<P: Fp12Config, CF: PrimeField> Clone for Fp12Var<P, CF>
+{
+    fn clone(&self) -> Self {
+        Self::new(self.c0.clone(), self.c1.clone())
+    }
+}
+
+impl<P: Fp12Config, CF: PrimeField> Debug for Fp12Var<P, CF>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fp12Var")
+            .field("c0", &self.c0)
+            .field("c1", &self.c1)
+            .finish()
+    }
+}
+
+impl<P: Fp12Config, CF: PrimeField> R1CSVar<CF> for Fp12Var<P, CF>
+{
+    type Value = Fp12<P>;
+
+    fn cs(&self) -> ConstraintSystemRef<CF> {
+        [&self.c0, &self.c1].cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        match (self.c0.value(), self.c1.value()) {
+            (Ok(c0), Ok(c1)) => Ok(Fp12::new(c0, c1)),
+            (..) => Err(SynthesisError::AssignmentMissing),
         }
     }
+}
+
+impl<P: Fp12Config, CF: PrimeField> From<Boolean<CF>> for Fp12Var<P, CF>
+{
+    fn from(other: Boolean<CF>) -> Self {
+        let c0 = Fp6Var::from(other);
+        let c1 = Fp6Var::zero();
+        Self::new(c0, c1)
+    }
+}
+
+impl<'a, P: Fp12Config, CF: PrimeField> FieldOpsBounds<'a, Fp12<P>, Fp12Var<P, CF>> for Fp12Var<P, CF> {}
+impl<'a, P: Fp12Config, CF: PrimeField> FieldOpsBounds<'a, Fp12<P>, Fp12Var<P, CF>> for &'a Fp12Var<P, CF> {}
+
+impl<P: Fp12Config, CF: PrimeField> FieldVar<Fp12<P>, CF> for Fp12Var<P, CF>
+{
+    fn zero() -> Self {
+        let c0 = Fp6Var::zero();
+        let c1 = Fp6Var::zero();
+        Self::new(c0, c1)
+    }
 
-    pub fn zero() -> Self {
-        let c0 = Fp6Var::<T, B>::zero();
-        let c1 = Fp6Var::<T, B>::zero();
+    fn one() -> Self {
+        let c0 = Fp6Var::one();
+        let c1 = Fp6Var::zero();
         Self::new(c0, c1)
     }
 
-    pub fn one() -> Self {
-        let c0 = Fp6Var::<T, B>::one();
-        let c1 = Fp6Var::<T, B>::zero();
+    fn constant(other: Fp12<P>) -> Self {
+        let c0 = Fp6Var::constant(other.c0);
+        let c1 = Fp6Var::constant(other.c1);
         Self::new(c0, c1)
     }
 
     #[tracing::instrument(target = "r1cs", skip(self))]
-    pub fn double(&self) -> Result<Self, SynthesisError> {
+    fn double(&self) -> Result<Self, SynthesisError> {
         let c0 = self.c0.double()?;
         let c1 = self.c1.double()?;
         Ok(Self::new(c0, c1))
     }
 
     #[tracing::instrument(target = "r1cs", skip(self))]
-    fn square(&self) -> Result<Self, SynthesisError> {
-        let c0 = self.c0.square()?;
-        let c1 = self.c1.square()?;
+    fn negate(&self) -> Result<Self, SynthesisError> {
+        let c0 = self.c0.negate()?;
+        let c1 = self.c1.negate()?;
         Ok(Self::new(c0, c1))
     }
 
     #[tracing::instrument(target = "r1cs", skip(self))]
-    pub(crate) fn double_in_place(&mut self) -> Result<(), SynthesisError> {
-        *self = self.double()?;
-        Ok(())
+    fn square(&self) -> Result<Self, SynthesisError> {
+        self.square_without_reduce()?.reduce()
     }
 
     #[tracing::instrument(target = "r1cs", skip(self))]
-    pub(crate) fn square_in_place(&mut self) -> Result<(), SynthesisError> {
-        *self = self.square()?;
-        Ok(())
+    fn frobenius_map(&self, power: usize) -> Result<Self, SynthesisError> {
+        let c0 = self.c0.frobenius_map(power)?;
+        let c1 = self.c1.frobenius_map(power)?
+            .mul_by_base_field_constant(P::FROBENIUS_COEFF_FP12_C1[power % 12])?;
+        Ok(Self::new(c0, c1))
     }
 
     #[tracing::instrument(target = "r1cs", skip(self))]
-    pub fn add(&self, other: &Self) -> Self {
-        let mut c0 = self.c0.add(&other.c0);
-        let mut c1 = self.c1.add(&other.c1);
-        Self::new(c0, c1)
+    fn inverse(&self) -> Result<Self, SynthesisError> {
+        let mode = if self.is_constant() {
+            AllocationMode::Constant
+        } else {
+            AllocationMode::Witness
+        };
+        let inverse = Self::new_variable(
+            self.cs(),
+            || self.value().map(|f| f.inverse().unwrap_or_else(Fp12::zero)),
+            mode,
+        )?;
+        self.mul_equals(&inverse, &Self::one())?;
+        Ok(inverse)
     }
+}
 
-    #[tracing::instrument(target = "r1cs", skip(self))]
-    pub fn mul(&self, other: &Self) -> Self {
-        let mut c0 = self.c0.mul(&other.c0);
-        let mut c1 = self.c1.mul(&other.c1);
-        Self::new(c0, c1)
+impl_bounded_ops!(
+    Fp12Var<P, CF>,
+    Fp12<P>,
+    Add,
+    add,
+    AddAssign,
+    add_assign,
+    |this: &'a Fp12Var<P, CF>, other: &'a Fp12Var<P, CF>| {
+        let c0 = &this.c0 + &other.c0;
+        let c1 = &this.c1 + &other.c1;
+        Fp12Var::new(c0, c1)
+    },
+    |this: &'a Fp12Var<P, CF>, other: Fp12<P>| {
+        this + Fp12Var::constant(other)
+    },
+    (P: Fp12Config, CF: PrimeField),
+);
+
+impl_bounded_ops!(
+    Fp12Var<P, CF>,
+    Fp12<P>,
+    Sub,
+    sub,
+    SubAssign,
+    sub_assign,
+    |this: &'a Fp12Var<P, CF>, other: &'a Fp12Var<P, CF>| {
+        let c0 = &this.c0 - &other.c0;
+        let c1 = &this.c1 - &other.c1;
+        Fp12Var::new(c0, c1)
+    },
+    |this: &'a Fp12Var<P, CF>, other: Fp12<P>| {
+        this - Fp12Var::constant(other)
+    },
+    (P: Fp12Config, CF: PrimeField),
+);
+
+impl_bounded_ops!(
+    Fp12Var<P, CF>,
+    Fp12<P>,
+    Mul,
+    mul,
+    MulAssign,
+    mul_assign,
+    |this: &'a Fp12Var<P, CF>, other: &'a Fp12Var<P, CF>| {
+        this.mul_without_reduce(other).and_then(|r| r.reduce()).unwrap()
+    },
+    |this: &'a Fp12Var<P, CF>, other: Fp12<P>| {
+        this * Fp12Var::constant(other)
+    },
+    (P: Fp12Config, CF: PrimeField),
+);
+
+impl<P: Fp12Config, CF: PrimeField> EqGadget<CF> for Fp12Var<P, CF>
+{
+    fn is_eq(&self, other: &Self) -> Result<Boolean<CF>, SynthesisError> {
+        let b0 = self.c0.is_eq(&other.c0)?;
+        let b1 = self.c1.is_eq(&other.c1)?;
+        b0.and(&b1)
     }
 
-    #[tracing::instrument(target = "r1cs", skip(bits))]
-    pub fn scalar_mul_le<'a>(
+    fn conditional_enforce_equal(
         &self,
-        bits: impl Iterator<Item = &'a Boolean<B>>,
-    ) -> Result<Self, SynthesisError> {
-        let mut res = Self::one();
-        let mut mul = (*self).clone();
+        other: &Self,
+        condition: &Boolean<CF>,
+    ) -> Result<(), SynthesisError> {
+        self.c0.conditional_enforce_equal(&other.c0, condition)?;
+        self.c1.conditional_enforce_equal(&other.c1, condition)
+    }
 
-        for bit in bits {
-            let tmp = res.clone().mul(&mul);
-            res = bit.select(&tmp, &res)?;
-            mul.square_in_place()?;
-        }
-        Ok(res)
+    fn conditional_enforce_not_equal(
+        &self,
+        other: &Self,
+        condition: &Boolean<CF>,
+    ) -> Result<(), SynthesisError> {
+        let is_equal = self.is_eq(other)?;
+        is_equal
+            .and(condition)?
+            .enforce_equal(&Boolean::Constant(false))
+    }
+}
+
+impl<P: Fp12Config, CF: PrimeField> ToBitsGadget<CF> for Fp12Var<P, CF>
+{
+    fn to_bits_le(&self) -> Result<Vec<Boolean<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_bits_le()?;
+        let mut c1 = self.c1.to_bits_le()?;
+        c0.append(&mut c1);
+        Ok(c0)
+    }
+
+    fn to_non_unique_bits_le(&self) -> Result<Vec<Boolean<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_non_unique_bits_le()?;
+        let mut c1 = self.c1.to_non_unique_bits_le()?;
+        c0.append(&mut c1);
+        Ok(c0)
+    }
+}
+
+impl<P: Fp12Config, CF: PrimeField> ToBytesGadget<CF> for Fp12Var<P, CF>
+{
+    fn to_bytes(&self) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_bytes()?;
+        let mut c1 = self.c1.to_bytes()?;
+        c0.append(&mut c1);
+        Ok(c0)
+    }
+
+    fn to_non_unique_bytes(&self) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+        let mut c0 = self.c0.to_non_unique_bytes()?;
+        let mut c1 = self.c1.to_non_unique_bytes()?;
+        c0.append(&mut c1);
+        Ok(c0)
     }
 }
 
+impl<P: Fp12Config, CF: PrimeField> ToConstraintFieldGadget<CF> for Fp12Var<P, CF>
+{
+    fn to_constraint_field(&self) -> Result<Vec<FpVar<CF>>, SynthesisError> {
+        let mut res = self.c0.to_constraint_field()?;
+        res.extend(self.c1.to_constraint_field()?);
+        Ok(res)
+    }
+}
 
-impl<TF: PrimeField, BF: PrimeField> CondSelectGadget<BF> for Fp12Var<TF, BF>
+impl<P: Fp12Config, CF: PrimeField> CondSelectGadget<CF> for Fp12Var<P, CF>
 {
     fn conditionally_select(
-        cond: &Boolean<BF>,
+        cond: &Boolean<CF>,
         true_value: &Self,
         false_value: &Self,
     ) -> Result<Self, SynthesisError> {
-        let c0 = Fp6Var::<TF, BF>::conditionally_select(
+        let c0 = Fp6Var::<P::Fp6Config, CF>::conditionally_select(
             cond,
             &true_value.c0,
             &false_value.c0,
         )?;
-        let c1 = Fp6Var::<TF, BF>::conditionally_select(
+        let c1 = Fp6Var::<P::Fp6Config, CF>::conditionally_select(
             cond,
             &true_value.c1,
             &false_value.c1,
@@ -456,90 +1445,475 @@ impl<TF: PrimeField, BF: PrimeField> CondSelectGadget<BF> for Fp12Var<TF, BF>
     }
 }
 
-impl<TF: PrimeField, BF: PrimeField> AbsorbGadget<BF> for Fp12Var<TF, BF>
+impl<P: Fp12Config, CF: PrimeField> AllocVar<Fp12<P>, CF> for Fp12Var<P, CF>
+{
+    fn new_variable<T: Borrow<Fp12<P>>>(
+        cs: impl Into<Namespace<CF>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let (c0, c1) = match f() {
+            Ok(fe) => (Ok(fe.borrow().c0), Ok(fe.borrow().c1)),
+            Err(_) => (
+                Err(SynthesisError::AssignmentMissing),
+                Err(SynthesisError::AssignmentMissing),
+            ),
+        };
+
+        let c0 = Fp6Var::new_variable(ark_relations::ns!(cs, "c0"), || c0, mode)?;
+        let c1 = Fp6Var::new_variable(ark_relations::ns!(cs, "c1"), || c1, mode)?;
+        Ok(Self::new(c0, c1))
+    }
+}
+
+impl<P: Fp12Config, CF: PrimeField> AbsorbGadget<CF> for Fp12Var<P, CF>
 {
-    fn to_sponge_bytes(&self) -> Result<Vec<UInt8<BF>>, SynthesisError> {
-        todo!()
+    /// Outputs the little-endian bytes of each base field coefficient, matching [`fp12_to_sponge_bytes`].
+    fn to_sponge_bytes(&self) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+        let num_bits = <Fp12BaseField<P> as PrimeField>::BigInt::NUM_LIMBS * 64;
+        let mut bytes = Vec::new();
+        for fe in self.coefficients() {
+            let mut bits = fe.to_bits_le()?;
+            bits.resize(num_bits, Boolean::FALSE);
+            bytes.extend(bits.chunks(8).map(UInt8::from_bits_le));
+        }
+        Ok(bytes)
     }
 
-    fn to_sponge_field_elements(&self) -> Result<Vec<FpVar<BF>>, SynthesisError> {
-        Ok(
-            self.c0.c0.c0.to_constraint_field().unwrap().into_iter().chain(
-                self.c0.c0.c1.to_constraint_field().unwrap()).chain(
-                self.c0.c1.c0.to_constraint_field().unwrap()).chain(
-                self.c0.c1.c1.to_constraint_field().unwrap()).chain(
-                self.c0.c2.c0.to_constraint_field().unwrap()).chain(
-                self.c0.c2.c1.to_constraint_field().unwrap()).chain(
-                self.c1.c0.c0.to_constraint_field().unwrap()).chain(
-                self.c1.c0.c1.to_constraint_field().unwrap()).chain(
-                self.c1.c1.c0.to_constraint_field().unwrap()).chain(
-                self.c1.c1.c1.to_constraint_field().unwrap()).chain(
-                self.c1.c2.c0.to_constraint_field().unwrap()).chain(
-                self.c1.c2.c1.to_constraint_field().unwrap())
-                .collect()
-        )
+    /// Outputs the coefficients tightly packed into native elements, matching [`fp12_to_sponge_field_elements`].
+    fn to_sponge_field_elements(&self) -> Result<Vec<FpVar<CF>>, SynthesisError> {
+        pack_nonnative_field_vars(&self.coefficients())
+    }
+}
+
+impl<P: Fp12Config, CF: PrimeField> Fp12Var<P, CF>
+{
+    /// Returns the twelve base field coefficients in the `c0.c0.c0, c0.c0.c1, ..., c1.c2.c1` order.
+    pub fn coefficients(&self) -> Vec<&NonNativeFieldVar<Fp12BaseField<P>, CF>> {
+        [&self.c0, &self.c1].into_iter()
+            .flat_map(|c| [&c.c0, &c.c1, &c.c2])
+            .flat_map(|c| [&c.c0, &c.c1])
+            .collect()
+    }
+}
+
+/// Poseidon sponge over the non-native field `TF`, which outputs the same elements and bytes as
+/// `PoseidonSponge<TF>` for the same parameters and inputs.
+///
+/// This lets a circuit over `CF` check the hashes of the native circuit over `TF` instead of
+/// hashing with parameters of its own constraint field, so that ciphertexts don't depend on the
+/// curve of the proof system. Products of the MDS matrix are summed before a single reduction.
+pub struct NonNativePoseidonSpongeVar<TF: PrimeField, CF: PrimeField> {
+    params: PoseidonConfig<TF>,
+    state: Vec<NonNativeFieldVar<TF, CF>>,
+    mode: DuplexSpongeMode,
+}
+
+impl<TF: PrimeField, CF: PrimeField> NonNativePoseidonSpongeVar<TF, CF> {
+    pub fn new(params: &PoseidonConfig<TF>) -> Self {
+        Self {
+            params: params.clone(),
+            state: vec![NonNativeFieldVar::zero(); params.rate + params.capacity],
+            mode: DuplexSpongeMode::Absorbing { next_absorb_index: 0 },
+        }
+    }
+
+    /// Raises `fe` to `alpha`, starting from `fe` rather than from one to skip a multiplication.
+    fn apply_s_box(&self, fe: &NonNativeFieldVar<TF, CF>) -> Result<NonNativeFieldVar<TF, CF>, SynthesisError> {
+        let mut res = fe.clone();
+        for bit in BitIteratorBE::without_leading_zeros([self.params.alpha]).skip(1) {
+            res = res.square()?;
+            if bit {
+                res = &res * fe;
+            }
+        }
+        Ok(res)
+    }
+
+    fn apply_mds(&self, state: &[NonNativeFieldVar<TF, CF>]) -> Result<Vec<NonNativeFieldVar<TF, CF>>, SynthesisError> {
+        self.params.mds.iter()
+            .map(|row| {
+                let mut products = row.iter().zip(state)
+                    .map(|(m, fe)| fe.mul_without_reduce(&NonNativeFieldVar::Constant(*m)));
+                let first = products.next().expect("MDS matrix rows are not empty")?;
+                products.try_fold(first, |acc, product| Ok(acc + &product?))?.reduce()
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn permute(&mut self) -> Result<(), SynthesisError> {
+        let full_rounds_over_2 = self.params.full_rounds / 2;
+        let partial_rounds_end = full_rounds_over_2 + self.params.partial_rounds;
+        let mut state = self.state.clone();
+        for i in 0..(self.params.full_rounds + self.params.partial_rounds) {
+            for (fe, ark) in state.iter_mut().zip(&self.params.ark[i]) {
+                *fe += *ark;
+            }
+            if i < full_rounds_over_2 || i >= partial_rounds_end {
+                for fe in state.iter_mut() {
+                    *fe = self.apply_s_box(fe)?;
+                }
+            } else {
+                state[0] = self.apply_s_box(&state[0])?;
+            }
+            state = self.apply_mds(&state)?;
+        }
+        self.state = state;
+
+        Ok(())
+    }
+
+    fn absorb_internal(&mut self, mut rate_start_index: usize, elements: &[NonNativeFieldVar<TF, CF>]) -> Result<(), SynthesisError> {
+        let mut remaining_elements = elements;
+        loop {
+            if rate_start_index + remaining_elements.len() <= self.params.rate {
+                for (i, element) in remaining_elements.iter().enumerate() {
+                    self.state[self.params.capacity + i + rate_start_index] += element;
+                }
+                self.mode = DuplexSpongeMode::Absorbing {
+                    next_absorb_index: rate_start_index + remaining_elements.len(),
+                };
+                return Ok(());
+            }
+
+            let num_elements_absorbed = self.params.rate - rate_start_index;
+            for (i, element) in remaining_elements.iter().enumerate().take(num_elements_absorbed) {
+                self.state[self.params.capacity + i + rate_start_index] += element;
+            }
+            self.permute()?;
+            remaining_elements = &remaining_elements[num_elements_absorbed..];
+            rate_start_index = 0;
+        }
+    }
+
+    fn squeeze_internal(&mut self, mut rate_start_index: usize, output: &mut [NonNativeFieldVar<TF, CF>]) -> Result<(), SynthesisError> {
+        let mut output_remaining = output;
+        loop {
+            if rate_start_index + output_remaining.len() <= self.params.rate {
+                output_remaining.clone_from_slice(
+                    &self.state[self.params.capacity + rate_start_index..(self.params.capacity + output_remaining.len() + rate_start_index)],
+                );
+                self.mode = DuplexSpongeMode::Squeezing {
+                    next_squeeze_index: rate_start_index + output_remaining.len(),
+                };
+                return Ok(());
+            }
+
+            let num_elements_squeezed = self.params.rate - rate_start_index;
+            output_remaining[..num_elements_squeezed].clone_from_slice(
+                &self.state[self.params.capacity + rate_start_index..(self.params.capacity + num_elements_squeezed + rate_start_index)],
+            );
+            if output_remaining.len() != self.params.rate {
+                self.permute()?;
+            }
+            output_remaining = &mut output_remaining[num_elements_squeezed..];
+            rate_start_index = 0;
+        }
+    }
+
+    /// Absorbs `input`, as `PoseidonSponge::absorb` does for a slice of `TF` elements.
+    pub fn absorb(&mut self, input: &[NonNativeFieldVar<TF, CF>]) -> Result<(), SynthesisError> {
+        if input.is_empty() {
+            return Ok(());
+        }
+
+        match self.mode {
+            DuplexSpongeMode::Absorbing { next_absorb_index } => {
+                let mut absorb_index = next_absorb_index;
+                if absorb_index == self.params.rate {
+                    self.permute()?;
+                    absorb_index = 0;
+                }
+                self.absorb_internal(absorb_index, input)
+            }
+            DuplexSpongeMode::Squeezing { .. } => {
+                self.permute()?;
+                self.absorb_internal(0, input)
+            }
+        }
+    }
+
+    /// Squeezes `num_elements` elements of `TF`, see `PoseidonSponge::squeeze_native_field_elements`.
+    pub fn squeeze_native_field_elements(&mut self, num_elements: usize) -> Result<Vec<NonNativeFieldVar<TF, CF>>, SynthesisError> {
+        let mut squeezed_elems = vec![NonNativeFieldVar::zero(); num_elements];
+        match self.mode {
+            DuplexSpongeMode::Absorbing { .. } => {
+                self.permute()?;
+                self.squeeze_internal(0, &mut squeezed_elems)?;
+            }
+            DuplexSpongeMode::Squeezing { next_squeeze_index } => {
+                let mut squeeze_index = next_squeeze_index;
+                if squeeze_index == self.params.rate {
+                    self.permute()?;
+                    squeeze_index = 0;
+                }
+                self.squeeze_internal(squeeze_index, &mut squeezed_elems)?;
+            }
+        }
+
+        Ok(squeezed_elems)
+    }
+
+    /// Squeezes `num_bytes` bytes from the canonical little-endian encoding of squeezed elements,
+    /// keeping the `(TF::MODULUS_BIT_SIZE - 1) / 8` low bytes of each, see `PoseidonSponge::squeeze_bytes`.
+    pub fn squeeze_bytes(&mut self, num_bytes: usize) -> Result<Vec<UInt8<CF>>, SynthesisError> {
+        let usable_bytes = ((TF::MODULUS_BIT_SIZE - 1) / 8) as usize;
+        let num_elements = num_bytes.div_ceil(usable_bytes);
+
+        let mut bytes = Vec::with_capacity(usable_bytes * num_elements);
+        for fe in self.squeeze_native_field_elements(num_elements)? {
+            bytes.extend_from_slice(&fe.to_bytes()?[..usable_bytes]);
+        }
+        bytes.truncate(num_bytes);
+
+        Ok(bytes)
     }
 }
 
+/// Packs the canonical little-endian bits of non-native field elements into as few
+/// constraint field elements as possible, each holding `CF::MODULUS_BIT_SIZE - 1` bits.
+///
+/// Compared to absorbing raw limbs this costs the same bit decomposition, but yields
+/// ~3x fewer elements to permute. The native counterpart is [`pack_field_elements`].
+pub fn pack_nonnative_field_vars<TF: PrimeField, CF: PrimeField>(
+    elems: &[&NonNativeFieldVar<TF, CF>],
+) -> Result<Vec<FpVar<CF>>, SynthesisError> {
+    let mut bits = Vec::with_capacity(elems.len() * TF::MODULUS_BIT_SIZE as usize);
+    for fe in elems {
+        let mut fe_bits = fe.to_bits_le()?;
+        // Bits above the modulus size are constrained to zero by `to_bits_le`.
+        fe_bits.resize(TF::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
+        bits.extend(fe_bits);
+    }
+
+    bits.chunks(CF::MODULUS_BIT_SIZE as usize - 1)
+        .map(Boolean::le_bits_to_fp_var)
+        .collect()
+}
+
+/// Native counterpart of [`pack_nonnative_field_vars`]: packs the canonical little-endian bits
+/// of `elems` into `CF` elements of `CF::MODULUS_BIT_SIZE - 1` bits each.
+pub fn pack_field_elements<TF: PrimeField, CF: PrimeField>(elems: &[TF]) -> Vec<CF> {
+    let bits = elems.iter()
+        .flat_map(|fe| BitIteratorLE::new(fe.into_bigint()).take(TF::MODULUS_BIT_SIZE as usize))
+        .collect::<Vec<_>>();
+
+    bits.chunks(CF::MODULUS_BIT_SIZE as usize - 1)
+        .map(|chunk| CF::from_bigint(CF::BigInt::from_bits_le(chunk)).unwrap())
+        .collect()
+}
+
+/// Returns the twelve base field coefficients of `fe` in the same order as [`Fp12Var::coefficients`].
+pub fn fp12_coefficients<P: Fp12Config>(fe: &Fp12<P>) -> Vec<Fp12BaseField<P>> {
+    [fe.c0, fe.c1].into_iter()
+        .flat_map(|c| [c.c0, c.c1, c.c2])
+        .flat_map(|c| [c.c0, c.c1])
+        .collect()
+}
+
+/// Native counterpart of `Fp12Var::to_sponge_field_elements`, i.e. what a sponge over `CF`
+/// must absorb off-circuit to hash `fe` exactly as the non-native gadget does.
+pub fn fp12_to_sponge_field_elements<P: Fp12Config, CF: PrimeField>(fe: &Fp12<P>) -> Vec<CF> {
+    pack_field_elements(&fp12_coefficients(fe))
+}
+
+/// Native counterpart of `Fp12Var::to_sponge_bytes`.
+pub fn fp12_to_sponge_bytes<P: Fp12Config>(fe: &Fp12<P>) -> Vec<u8> {
+    fp12_coefficients(fe).into_iter()
+        .flat_map(|c| c.into_bigint().to_bytes_le())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_relations::ns;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_sponge::poseidon::PoseidonSponge;
+    use ark_sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
+    use ark_std::{test_rng, UniformRand};
+    use crate::Parameters;
+    use super::*;
+
+    #[test]
+    fn test_fq12_var_arithmetic() {
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<ark_bls12_377::Fq>::new_ref();
+
+        let a = ark_bls12_381::Fq12::rand(&mut rng);
+        let b = ark_bls12_381::Fq12::rand(&mut rng);
+        let a_var = Fq12Var::new_witness(ns!(cs, "a"), || Ok(a)).unwrap();
+        let b_var = Fq12Var::new_witness(ns!(cs, "b"), || Ok(b)).unwrap();
+
+        let ab_var = &a_var * &b_var;
+        assert_eq!(ab_var.value().unwrap(), a * b);
+
+        let ab_expected = Fq12Var::new_input(ns!(cs, "ab"), || Ok(a * b)).unwrap();
+        ab_var.enforce_equal(&ab_expected).unwrap();
+
+        assert_eq!(a_var.square().unwrap().value().unwrap(), a.square());
+        assert_eq!(a_var.inverse().unwrap().value().unwrap(), a.inverse().unwrap());
+
+        let mut a_frob = a;
+        a_frob.frobenius_map_in_place(1);
+        assert_eq!(a_var.frobenius_map(1).unwrap().value().unwrap(), a_frob);
+
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    /// Multiplies as `Fp2Var::mul`, but reducing each product of base field coefficients.
+    fn fq2_mul_reduced<CF: PrimeField>(a: &Fq2Var<CF>, b: &Fq2Var<CF>) -> Fq2Var<CF> {
+        let nr_a1 = Fq2Var::<CF>::mul_base_field_by_nonresidue(&a.c1).unwrap();
+        Fq2Var::new(&a.c0 * &b.c0 + &nr_a1 * &b.c1, &a.c0 * &b.c1 + &a.c1 * &b.c0)
+    }
+
+    fn fq6_mul_reduced<CF: PrimeField>(a: &Fq6Var<CF>, b: &Fq6Var<CF>) -> Fq6Var<CF> {
+        let nr_a1 = Fq6Var::<CF>::mul_base_field_by_nonresidue(&a.c1).unwrap();
+        let nr_a2 = Fq6Var::<CF>::mul_base_field_by_nonresidue(&a.c2).unwrap();
+        Fq6Var::new(
+            fq2_mul_reduced(&a.c0, &b.c0) + fq2_mul_reduced(&nr_a1, &b.c2) + fq2_mul_reduced(&nr_a2, &b.c1),
+            fq2_mul_reduced(&a.c0, &b.c1) + fq2_mul_reduced(&a.c1, &b.c0) + fq2_mul_reduced(&nr_a2, &b.c2),
+            fq2_mul_reduced(&a.c0, &b.c2) + fq2_mul_reduced(&a.c1, &b.c1) + fq2_mul_reduced(&a.c2, &b.c0),
+        )
+    }
 
-pub fn new_fp12_variable<V: Borrow<ark_bls12_381::Fq12>, CF: PrimeField>(
-    cs: impl Into<Namespace<CF>>,
-    f: impl FnOnce() -> Result<V, SynthesisError>,
-    mode: AllocationMode,
-) -> Result<Fq12Var<CF>, SynthesisError> {
-    let ns = cs.into();
-    let cs = ns.cs();
-    let (c0, c1) = match f() {
-        Ok(fe) => (Ok(fe.borrow().c0), Ok(fe.borrow().c1)),
-        Err(_) => (
-            Err(SynthesisError::AssignmentMissing),
-            Err(SynthesisError::AssignmentMissing),
-        ),
-    };
-
-    let c0 = new_fp6_variable(ark_relations::ns!(cs, "c0"), || c0, mode)?;
-    let c1 = new_fp6_variable(ark_relations::ns!(cs, "c1"), || c1, mode)?;
-    Ok(Fq12Var::new(c0, c1))
-}
-
-pub fn new_fp6_variable<V: Borrow<ark_bls12_381::Fq6>, CF: PrimeField>(
-    cs: impl Into<Namespace<CF>>,
-    f: impl FnOnce() -> Result<V, SynthesisError>,
-    mode: AllocationMode,
-) -> Result<Fq6Var<CF>, SynthesisError> {
-    let ns = cs.into();
-    let cs = ns.cs();
-    let (c0, c1, c2) = match f() {
-        Ok(fe) => (Ok(fe.borrow().c0), Ok(fe.borrow().c1), Ok(fe.borrow().c2)),
-        Err(_) => (
-            Err(SynthesisError::AssignmentMissing),
-            Err(SynthesisError::AssignmentMissing),
-            Err(SynthesisError::AssignmentMissing),
-        ),
-    };
-
-    let c0 = new_fp2_variable(ark_relations::ns!(cs, "c0"), || c0, mode)?;
-    let c1 = new_fp2_variable(ark_relations::ns!(cs, "c1"), || c1, mode)?;
-    let c2 = new_fp2_variable(ark_relations::ns!(cs, "c2"), || c2, mode)?;
-    Ok(Fq6Var::new(c0, c1, c2))
-}
-
-pub fn new_fp2_variable<V: Borrow<ark_bls12_381::Fq2>, CF: PrimeField>(
-    cs: impl Into<Namespace<CF>>,
-    f: impl FnOnce() -> Result<V, SynthesisError>,
-    mode: AllocationMode,
-) -> Result<Fq2Var<CF>, SynthesisError> {
-    let ns = cs.into();
-    let cs = ns.cs();
-    let (c0, c1) = match f() {
-        Ok(fe) => (Ok(fe.borrow().c0), Ok(fe.borrow().c1)),
-        Err(_) => (
-            Err(SynthesisError::AssignmentMissing),
-            Err(SynthesisError::AssignmentMissing),
-        ),
-    };
-
-    let c0 = NonNativeFieldVar::new_variable(ark_relations::ns!(cs, "c0"), || c0, mode)?;
-    let c1 = NonNativeFieldVar::new_variable(ark_relations::ns!(cs, "c1"), || c1, mode)?;
-    Ok(Fq2Var::new(c0, c1))
+    fn fq12_mul_reduced<CF: PrimeField>(a: &Fq12Var<CF>, b: &Fq12Var<CF>) -> Fq12Var<CF> {
+        let nr_a1 = Fq12Var::<CF>::mul_base_field_by_nonresidue(&a.c1).unwrap();
+        Fq12Var::new(
+            fq6_mul_reduced(&a.c0, &b.c0) + fq6_mul_reduced(&nr_a1, &b.c1),
+            fq6_mul_reduced(&a.c0, &b.c1) + fq6_mul_reduced(&a.c1, &b.c0),
+        )
+    }
+
+    /// Returns the constraints of an `Fq12Var` multiplication over `CF` when every product is
+    /// reduced, and with the lazy reduction of `Fp12Var::mul`.
+    fn fq12_mul_num_constraints<CF: NonNativeConfig>() -> (usize, usize) {
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<CF>::new_ref();
+        cs.set_optimization_goal(CF::OPTIMIZATION_GOAL);
+
+        let (a, b) = (ark_bls12_381::Fq12::rand(&mut rng), ark_bls12_381::Fq12::rand(&mut rng));
+        let a_var = Fq12Var::new_witness(ns!(cs, "a"), || Ok(a)).unwrap();
+        let b_var = Fq12Var::new_witness(ns!(cs, "b"), || Ok(b)).unwrap();
+
+        let start = cs.num_constraints();
+        let reduced = fq12_mul_reduced(&a_var, &b_var);
+        let middle = cs.num_constraints();
+        let lazy = &a_var * &b_var;
+        let end = cs.num_constraints();
+
+        assert_eq!(reduced.value().unwrap(), a * b);
+        assert_eq!(lazy.value().unwrap(), a * b);
+        assert!(cs.is_satisfied().unwrap());
+
+        (middle - start, end - middle)
+    }
+
+    #[test]
+    fn test_fq12_mul_lazy_reduction() {
+        let (reduced, lazy) = fq12_mul_num_constraints::<ark_bls12_377::Fq>();
+        assert!(lazy < reduced);
+
+        let (reduced, lazy) = fq12_mul_num_constraints::<ark_bn254::Fr>();
+        assert!(lazy < reduced);
+    }
+
+    #[test]
+    fn test_g1_var_scalar_mul() {
+        use ark_ec::{AffineRepr, CurveGroup};
+
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<ark_bls12_377::Fq>::new_ref();
+
+        let p = ark_bls12_381::G1Projective::rand(&mut rng).into_affine();
+        let k = u8::rand(&mut rng) | 1;
+        let expected = p.mul_bigint([k as u64]).into_affine();
+
+        let p_var = G1Var::new(
+            FqVar::new_witness(ns!(cs, "p_x"), || Ok(p.x)).unwrap(),
+            FqVar::new_witness(ns!(cs, "p_y"), || Ok(p.y)).unwrap(),
+        );
+        let expected_var = G1Var::new(
+            FqVar::new_input(ns!(cs, "expected_x"), || Ok(expected.x)).unwrap(),
+            FqVar::new_input(ns!(cs, "expected_y"), || Ok(expected.y)).unwrap(),
+        );
+        let k_bits = Vec::<Boolean<_>>::new_witness(ns!(cs, "k"), || {
+            Ok((0..8).map(|i| (k >> i) & 1 == 1).collect::<Vec<_>>())
+        }).unwrap();
+
+        p_var.scalar_mul_le(k_bits.iter()).unwrap().enforce_equal(&expected_var).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_g1_var_fixed_base_scalar_mul() {
+        use ark_ec::{AffineRepr, CurveGroup, Group};
+
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<ark_bls12_377::Fq>::new_ref();
+
+        let g = ark_bls12_381::G1Projective::generator();
+        // An odd number of bits exercises the last, incomplete window.
+        let k = u64::rand(&mut rng) >> 1;
+        let expected = g.into_affine().mul_bigint([k]).into_affine();
+
+        let expected_var = G1Var::new(
+            FqVar::new_input(ns!(cs, "expected_x"), || Ok(expected.x)).unwrap(),
+            FqVar::new_input(ns!(cs, "expected_y"), || Ok(expected.y)).unwrap(),
+        );
+        let k_bits = Vec::<Boolean<_>>::new_witness(ns!(cs, "k"), || {
+            Ok((0..63).map(|i| (k >> i) & 1 == 1).collect::<Vec<_>>())
+        }).unwrap();
+
+        G1Var::fixed_base_scalar_mul_le(g, &k_bits).unwrap().enforce_equal(&expected_var).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_fq12_var_sponge_packing() {
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<ark_bls12_377::Fq>::new_ref();
+
+        let a = ark_bls12_381::Fq12::rand(&mut rng);
+        let a_var = Fq12Var::new_witness(ns!(cs, "a"), || Ok(a)).unwrap();
+
+        let packed = a_var.to_sponge_field_elements().unwrap();
+        let expected = fp12_to_sponge_field_elements::<_, ark_bls12_377::Fq>(&a);
+        assert_eq!(packed.value().unwrap(), expected);
+        assert!(packed.len() < a_var.to_constraint_field().unwrap().len());
+
+        let bytes = a_var.to_sponge_bytes().unwrap();
+        assert_eq!(bytes.value().unwrap(), fp12_to_sponge_bytes(&a));
+
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_nonnative_poseidon_sponge() {
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<ark_bn254::Fr>::new_ref();
+        let params = Parameters::<ark_bls12_381::G1Projective>::default().poseidon;
+
+        // Three elements and outputs overflow the rate, so that both absorbing and squeezing permute.
+        let elems = (0..3).map(|_| ark_bls12_381::Fq::rand(&mut rng)).collect::<Vec<_>>();
+        let elem_vars = elems.iter()
+            .map(|fe| FqVar::new_witness(ns!(cs, "elem"), || Ok(*fe)).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut sponge = PoseidonSponge::new(&params);
+        sponge.absorb(&elems);
+        let outputs = sponge.squeeze_native_field_elements(3);
+        sponge.absorb(&elems[0]);
+        let bytes = sponge.squeeze_bytes(32);
+
+        let mut sponge_var = NonNativePoseidonSpongeVar::new(&params);
+        sponge_var.absorb(&elem_vars).unwrap();
+        assert_eq!(sponge_var.squeeze_native_field_elements(3).unwrap().value().unwrap(), outputs);
+        sponge_var.absorb(&elem_vars[..1]).unwrap();
+        assert_eq!(sponge_var.squeeze_bytes(32).unwrap().value().unwrap(), bytes);
+
+        assert!(cs.is_satisfied().unwrap());
+    }
 }
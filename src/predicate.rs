@@ -0,0 +1,488 @@
+//! Statements about the plaintext of a time-lock ciphertext, proven along with its encryption.
+//!
+//! A [`PlaintextPredicate`] is attached to [`crate::Circuit`] with [`crate::Circuit::with_predicate`]
+//! (or to [`crate::NonnativeCircuit`]), e.g. to prove that an encrypted bid doesn't exceed a public
+//! balance, or that an encrypted vote is one of the candidates, without forking the circuit.
+
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, BitIteratorLE, Field, PrimeField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::curves::short_weierstrass::ProjectiveVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use ark_sponge::constraints::CryptographicSpongeVar;
+use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_sponge::poseidon::{PoseidonConfig, PoseidonSponge};
+use ark_sponge::{Absorb, CryptographicSponge, FieldBasedCryptographicSponge};
+use sha2::{Digest, Sha256};
+use crate::utils::fixed_base_mul_le;
+
+/// Constraints on the plaintext `V` of a circuit over `F`, i.e. an `FpVar<F>` for [`crate::Circuit`]
+/// and an `FqVar<F>` for [`crate::NonnativeCircuit`].
+///
+/// [`Self::enforce`] is called by `generate_constraints` once the plaintext is allocated, and after
+/// the public inputs of the circuit, which are hence followed by those allocated by the predicate.
+pub trait PlaintextPredicate<F: PrimeField, V = FpVar<F>> {
+    /// Enforces the predicate on the plaintext `msg`.
+    fn enforce(&self, cs: ConstraintSystemRef<F>, msg: &V) -> Result<(), SynthesisError>;
+
+    /// Returns the values of the public inputs allocated by [`Self::enforce`], in order.
+    fn public_inputs(&self) -> Vec<F> {
+        vec![]
+    }
+}
+
+/// Bound of a [`RangePredicate`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound<TF> {
+    /// Bound fixed in the circuit, hence in its keys.
+    Constant(TF),
+    /// Bound given by the verifier as a public input. Its value is only used for the assignment,
+    /// so keys can be generated with any value.
+    Public(TF),
+}
+
+/// Proves that the plaintext, read as an integer, lies in `[min, max]`, e.g. for sealed bids.
+///
+/// Both `msg - min` and `max - msg` are decomposed into `num_bits` bits, which costs `2 * num_bits`
+/// constraints (and `num_bits` for each public bound). Bounds must be below `2^num_bits`, which
+/// the circuit enforces for public ones.
+#[derive(Clone, Debug)]
+pub struct RangePredicate<TF: PrimeField> {
+    pub min: Bound<TF>,
+    pub max: Bound<TF>,
+    pub num_bits: usize,
+}
+
+impl<TF: PrimeField> RangePredicate<TF> {
+    pub fn new(min: Bound<TF>, max: Bound<TF>, num_bits: usize) -> Self {
+        assert!(num_bits < TF::MODULUS_BIT_SIZE as usize - 1, "range must be smaller than the field");
+        for bound in [&min, &max] {
+            if let Bound::Constant(c) = bound {
+                assert!(c.into_bigint().num_bits() as usize <= num_bits, "bound exceeds {num_bits} bits");
+            }
+        }
+
+        Self { min, max, num_bits }
+    }
+
+    /// Allocates `bound` in the circuit, as a single native input if it's public.
+    fn bound_var<F: PrimeField, V: FieldVar<TF, F>>(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        bound: &Bound<TF>,
+    ) -> Result<V, SynthesisError> {
+        match bound {
+            Bound::Constant(c) => Ok(V::constant(*c)),
+            Bound::Public(value) => {
+                let input = FpVar::new_input(ns!(cs, "range_bound"), || Ok(to_native::<TF, F>(value)))?;
+                // Being range checked, the input is a valid element of `TF` too.
+                let bits = witness_bits(cs, input.value(), self.num_bits)?;
+                Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(&input)?;
+                Ok(from_bits_le(&bits))
+            }
+        }
+    }
+}
+
+impl<TF: PrimeField, F: PrimeField, V: FieldVar<TF, F>> PlaintextPredicate<F, V> for RangePredicate<TF> {
+    fn enforce(&self, cs: ConstraintSystemRef<F>, msg: &V) -> Result<(), SynthesisError> {
+        assert!(self.num_bits < F::MODULUS_BIT_SIZE as usize - 1, "range must be smaller than the field");
+        let min = self.bound_var::<F, V>(cs.clone(), &self.min)?;
+        let max = self.bound_var::<F, V>(cs.clone(), &self.max)?;
+
+        // Both differences are below `2^num_bits`, hence their sum `max - min` doesn't wrap around
+        // the modulus, and so `min <= msg <= max` as integers.
+        for diff in [msg.clone() - &min, max - msg] {
+            let bits = witness_bits(cs.clone(), diff.value(), self.num_bits)?;
+            from_bits_le::<TF, F, V>(&bits).enforce_equal(&diff)?;
+        }
+
+        Ok(())
+    }
+
+    fn public_inputs(&self) -> Vec<F> {
+        [&self.min, &self.max].into_iter()
+            .filter_map(|bound| match bound {
+                Bound::Public(value) => Some(to_native(value)),
+                Bound::Constant(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Proves that the plaintext is the value committed to by the public `Poseidon(msg, blinding)`.
+///
+/// The prover's predicate carries the opening, see [`Self::new`], while the verifier and key
+/// generation only need the commitment, see [`Self::public`].
+#[derive(Clone, Debug)]
+pub struct PoseidonCommitment<F: PrimeField> {
+    params: PoseidonConfig<F>,
+    pub commitment: F,
+    blinding: Option<F>,
+}
+
+impl<F: PrimeField + Absorb> PoseidonCommitment<F> {
+    pub fn new(params: PoseidonConfig<F>, commitment: F, blinding: F) -> Self {
+        Self { params, commitment, blinding: Some(blinding) }
+    }
+
+    pub fn public(params: PoseidonConfig<F>, commitment: F) -> Self {
+        Self { params, commitment, blinding: None }
+    }
+
+    pub fn commit(params: &PoseidonConfig<F>, msg: &F, blinding: &F) -> F {
+        let mut sponge = PoseidonSponge::new(params);
+        sponge.absorb(msg);
+        sponge.absorb(blinding);
+        sponge.squeeze_native_field_elements(1).remove(0)
+    }
+
+    pub fn open(params: &PoseidonConfig<F>, commitment: &F, msg: &F, blinding: &F) -> bool {
+        Self::commit(params, msg, blinding) == *commitment
+    }
+}
+
+impl<F: PrimeField + Absorb> PlaintextPredicate<F> for PoseidonCommitment<F> {
+    fn enforce(&self, cs: ConstraintSystemRef<F>, msg: &FpVar<F>) -> Result<(), SynthesisError> {
+        let commitment = FpVar::new_input(ns!(cs, "commitment"), || Ok(self.commitment))?;
+        let blinding = FpVar::new_witness(ns!(cs, "blinding"), || self.blinding.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let mut sponge = PoseidonSpongeVar::new(cs.clone(), &self.params);
+        sponge.absorb(msg)?;
+        sponge.absorb(&blinding)?;
+        sponge.squeeze_field_elements(1)?[0].enforce_equal(&commitment)
+    }
+
+    fn public_inputs(&self) -> Vec<F> {
+        vec![self.commitment]
+    }
+}
+
+/// Proves that the plaintext is the value committed to by the public Pedersen commitment
+/// `msg * G + blinding * H` on a curve `C` over the constraint field, e.g. G1 of the pairing in
+/// [`crate::Circuit`].
+///
+/// `msg` is used as an integer scalar, hence bound modulo the order of `C`, which plaintexts
+/// such as amounts don't exceed. `H` is derived with try-and-increment, so its discrete
+/// logarithm to `G` is unknown, see [`Self::generators`].
+#[derive(Clone, Debug)]
+pub struct PedersenCommitment<C: SWCurveConfig> {
+    pub commitment: Affine<C>,
+    blinding: Option<C::ScalarField>,
+}
+
+impl<C: SWCurveConfig> PedersenCommitment<C>
+    where C::BaseField: PrimeField
+{
+    pub fn new(commitment: Affine<C>, blinding: C::ScalarField) -> Self {
+        Self { commitment, blinding: Some(blinding) }
+    }
+
+    pub fn public(commitment: Affine<C>) -> Self {
+        Self { commitment, blinding: None }
+    }
+
+    /// Returns the generators `G` and `H` of the commitment.
+    pub fn generators() -> (Affine<C>, Affine<C>) {
+        let h = (0u32..).find_map(|counter| {
+            let digest = Sha256::digest(&[b"zk-tlock-pedersen-h".as_ref(), &counter.to_be_bytes()].concat());
+            let x = C::BaseField::from_be_bytes_mod_order(&digest);
+            let y = (x.square() * x + C::COEFF_A * x + C::COEFF_B).sqrt()?;
+            let h = Affine::<C>::new_unchecked(x, y).clear_cofactor();
+            (!h.is_zero()).then_some(h)
+        }).unwrap();
+
+        (C::GENERATOR, h)
+    }
+
+    pub fn commit(msg: &C::BaseField, blinding: &C::ScalarField) -> Affine<C> {
+        let (g, h) = Self::generators();
+        let msg = C::ScalarField::from_le_bytes_mod_order(&msg.into_bigint().to_bytes_le());
+        (g * msg + h * blinding).into_affine()
+    }
+
+    pub fn open(commitment: &Affine<C>, msg: &C::BaseField, blinding: &C::ScalarField) -> bool {
+        Self::commit(msg, blinding) == *commitment
+    }
+}
+
+impl<C: SWCurveConfig> PlaintextPredicate<C::BaseField> for PedersenCommitment<C>
+    where C::BaseField: PrimeField
+{
+    fn enforce(&self, cs: ConstraintSystemRef<C::BaseField>, msg: &FpVar<C::BaseField>) -> Result<(), SynthesisError> {
+        let [x, y] = [self.commitment.x, self.commitment.y]
+            .map(|coord| FpVar::new_input(ns!(cs, "commitment"), || Ok(coord)));
+        let commitment = ProjectiveVar::<C, FpVar<C::BaseField>>::new(x?, y?, FpVar::one());
+        let blinding = witness_bits(
+            cs.clone(),
+            self.blinding.ok_or(SynthesisError::AssignmentMissing),
+            C::ScalarField::MODULUS_BIT_SIZE as usize,
+        )?;
+
+        let (g, h) = Self::generators();
+        let res = fixed_base_mul_le::<C>(Projective::from(g), &msg.to_bits_le()?)?
+            + fixed_base_mul_le::<C>(Projective::from(h), &blinding)?;
+        res.enforce_equal(&commitment)
+    }
+
+    fn public_inputs(&self) -> Vec<C::BaseField> {
+        vec![self.commitment.x, self.commitment.y]
+    }
+}
+
+/// Proves that the plaintext is the preimage of a public SHA-256 digest, e.g. of a commit-reveal
+/// contract that stores `sha256(secret)`.
+///
+/// The plaintext is hashed in its canonical encoding, i.e. the little-endian bytes of its field
+/// element (48 for BLS12-381), see [`Self::digest`]. The digest is exposed as two public inputs,
+/// its first and last 16 bytes read as big-endian integers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sha256Preimage {
+    pub digest: [u8; 32],
+}
+
+impl Sha256Preimage {
+    pub fn new(digest: [u8; 32]) -> Self {
+        Self { digest }
+    }
+
+    /// Returns the digest of `msg` proven by the predicate.
+    pub fn digest<TF: PrimeField>(msg: &TF) -> [u8; 32] {
+        let mut bytes = msg.into_bigint().to_bytes_le();
+        bytes.truncate((TF::MODULUS_BIT_SIZE as usize + 7) / 8);
+        Sha256::digest(&bytes).into()
+    }
+}
+
+impl<F: PrimeField, V: ToBytesGadget<F>> PlaintextPredicate<F, V> for Sha256Preimage {
+    fn enforce(&self, cs: ConstraintSystemRef<F>, msg: &V) -> Result<(), SynthesisError> {
+        let inputs = PlaintextPredicate::<F, V>::public_inputs(self)
+            .into_iter()
+            .map(|half| FpVar::new_input(ns!(cs, "digest"), || Ok(half)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // `to_bytes` of field variables is their canonical little-endian encoding.
+        let digest = Sha256Gadget::digest(&msg.to_bytes()?)?.0;
+        for (half, input) in digest.chunks(16).zip(inputs) {
+            let bits = half.iter().rev().flat_map(|byte| byte.to_bits_le().unwrap()).collect::<Vec<_>>();
+            Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(&input)?;
+        }
+
+        Ok(())
+    }
+
+    fn public_inputs(&self) -> Vec<F> {
+        self.digest.chunks(16).map(F::from_be_bytes_mod_order).collect()
+    }
+}
+
+/// Returns `value`, smaller than `F`'s modulus, as an element of `F`.
+fn to_native<TF: PrimeField, F: PrimeField>(value: &TF) -> F {
+    F::from_le_bytes_mod_order(&value.into_bigint().to_bytes_le())
+}
+
+/// Allocates the `num_bits` least significant bits of `value` as witnesses.
+fn witness_bits<T: PrimeField, F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    value: Result<T, SynthesisError>,
+    num_bits: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let bits = value.map(|v| BitIteratorLE::new(v.into_bigint()).take(num_bits).collect::<Vec<_>>());
+    (0..num_bits).map(|i| {
+        Boolean::new_witness(ns!(cs, "range_bit"), || {
+            bits.as_ref().map(|bits| bits[i]).map_err(|_| SynthesisError::AssignmentMissing)
+        })
+    }).collect()
+}
+
+fn from_bits_le<TF: PrimeField, F: PrimeField, V: FieldVar<TF, F>>(bits: &[Boolean<F>]) -> V {
+    let mut coeff = TF::one();
+    bits.iter().fold(V::zero(), |acc, bit| {
+        let acc = acc + V::from(bit.clone()) * coeff;
+        coeff.double_in_place();
+        acc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonnative::FqVar;
+    use crate::prover::{Groth16Native, TimelockProver};
+    use crate::Circuit;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+
+    type Fq = ark_bls12_381::Fq;
+    type NativeFq = ark_bls12_377::Fq;
+    type NativeCircuit = Circuit<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters>;
+    type Prover = Groth16Native<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters, ark_bw6_761::BW6_761>;
+
+    /// Generates Groth16 keys for the native circuit over BLS12-377 with `setup` attached, then
+    /// proves the encryption of `msg` with `predicate` attached. Returns the verifier of the proof,
+    /// given the public inputs of the predicate.
+    fn prove_native<P: PlaintextPredicate<NativeFq> + 'static>(setup: P, predicate: P, msg: NativeFq) -> impl Fn(&[NativeFq]) -> bool {
+        let mut rng = ark_std::test_rng();
+        let (pk, vk) = Prover::setup(NativeCircuit::blank().with_predicate(setup), &mut rng).unwrap();
+        let circuit = NativeCircuit::new(ark_bls12_377::G1Affine::generator(), b"round-1000", msg, &mut rng).unwrap()
+            .with_predicate(predicate);
+        let gid = circuit.gid.clone().unwrap();
+        let ct = circuit.ciphertext.clone().unwrap();
+        let proof = Prover::prove(&pk, circuit, &mut rng).unwrap();
+
+        move |predicate_inputs| Prover::verify(&vk, &gid, &ct, predicate_inputs, &proof).unwrap()
+    }
+
+    #[test]
+    fn test_range_predicate() {
+        let predicates = [
+            RangePredicate::new(Bound::Constant(Fq::from(100u64)), Bound::Constant(Fq::from(200u64)), 16),
+            RangePredicate::new(Bound::Public(Fq::from(100u64)), Bound::Public(Fq::from(200u64)), 16),
+        ];
+        for predicate in predicates {
+            for (msg, in_range) in [(100u64, true), (150, true), (200, true), (99, false), (201, false), (1 << 20, false)] {
+                let cs = ConstraintSystem::<Fq>::new_ref();
+                let msg = FpVar::new_witness(cs.clone(), || Ok(Fq::from(msg))).unwrap();
+                predicate.enforce(cs.clone(), &msg).unwrap();
+                assert_eq!(cs.is_satisfied().unwrap(), in_range);
+                assert_eq!(cs.borrow().unwrap().instance_assignment[1..], PlaintextPredicate::<Fq>::public_inputs(&predicate));
+            }
+        }
+
+        // `min > max` is an empty range, whatever the plaintext.
+        let predicate = RangePredicate::new(Bound::Public(Fq::from(200u64)), Bound::Public(Fq::from(100u64)), 16);
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let msg = FpVar::new_witness(cs.clone(), || Ok(Fq::from(150u64))).unwrap();
+        predicate.enforce(cs.clone(), &msg).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_range_predicate_groth16() {
+        // Keys only fix the number of bits of public bounds, not their values.
+        let setup = RangePredicate::new(Bound::Public(NativeFq::from(0u64)), Bound::Public(NativeFq::from(0u64)), 16);
+        let predicate = RangePredicate::new(Bound::Public(NativeFq::from(100u64)), Bound::Public(NativeFq::from(200u64)), 16);
+        let inputs = PlaintextPredicate::<NativeFq>::public_inputs(&predicate);
+        let verify = prove_native(setup, predicate, NativeFq::from(150u64));
+        assert!(verify(&inputs));
+        assert!(!verify(&[NativeFq::from(100u64), NativeFq::from(149u64)]));
+    }
+
+    #[test]
+    fn test_nonnative_range_predicate() {
+        type CF = ark_bn254::Fr;
+        let predicate = RangePredicate::new(Bound::Constant(Fq::from(100u64)), Bound::Public(Fq::from(200u64)), 16);
+        for (msg, in_range) in [(150u64, true), (99, false), (201, false)] {
+            let cs = ConstraintSystem::<CF>::new_ref();
+            let msg = FqVar::<CF>::new_witness(cs.clone(), || Ok(Fq::from(msg))).unwrap();
+            PlaintextPredicate::<CF, FqVar<CF>>::enforce(&predicate, cs.clone(), &msg).unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), in_range);
+            assert_eq!(cs.borrow().unwrap().instance_assignment[1..], PlaintextPredicate::<CF, FqVar<CF>>::public_inputs(&predicate));
+        }
+    }
+
+    #[test]
+    fn test_poseidon_commitment() {
+        let mut rng = ark_std::test_rng();
+        let params = crate::poseidon::get_poseidon_params::<ark_bls12_381::G1Projective>(2);
+        let (msg, blinding) = (Fq::from(42u64), Fq::rand(&mut rng));
+        let commitment = PoseidonCommitment::commit(&params, &msg, &blinding);
+        assert!(PoseidonCommitment::open(&params, &commitment, &msg, &blinding));
+        assert!(!PoseidonCommitment::open(&params, &commitment, &Fq::from(43u64), &blinding));
+
+        for (msg, is_committed) in [(msg, true), (Fq::from(43u64), false)] {
+            let predicate = PoseidonCommitment::new(params.clone(), commitment, blinding);
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let msg = FpVar::new_witness(cs.clone(), || Ok(msg)).unwrap();
+            predicate.enforce(cs.clone(), &msg).unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), is_committed);
+            assert_eq!(cs.borrow().unwrap().instance_assignment[1..], predicate.public_inputs());
+        }
+    }
+
+    #[test]
+    fn test_pedersen_commitment() {
+        type TestCommitment = PedersenCommitment<ark_bls12_381::g1::Parameters>;
+        let mut rng = ark_std::test_rng();
+        let (msg, blinding) = (Fq::from(42u64), ark_bls12_381::Fr::rand(&mut rng));
+        let commitment = TestCommitment::commit(&msg, &blinding);
+        assert!(TestCommitment::open(&commitment, &msg, &blinding));
+        assert!(!TestCommitment::open(&commitment, &Fq::from(43u64), &blinding));
+
+        let (g, h) = TestCommitment::generators();
+        assert!(h.is_on_curve() && h.is_in_correct_subgroup_assuming_on_curve() && h != g);
+
+        for (msg, is_committed) in [(msg, true), (Fq::from(43u64), false)] {
+            let predicate = TestCommitment::new(commitment, blinding);
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let msg = FpVar::new_witness(cs.clone(), || Ok(msg)).unwrap();
+            predicate.enforce(cs.clone(), &msg).unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), is_committed);
+            assert_eq!(cs.borrow().unwrap().instance_assignment[1..], predicate.public_inputs());
+        }
+    }
+
+    #[test]
+    fn test_poseidon_commitment_groth16() {
+        let mut rng = ark_std::test_rng();
+        let params = crate::poseidon::get_poseidon_params::<ark_bls12_377::G1Projective>(2);
+        let (msg, blinding) = (NativeFq::from(42u64), NativeFq::rand(&mut rng));
+        let commitment = PoseidonCommitment::commit(&params, &msg, &blinding);
+
+        let setup = PoseidonCommitment::public(params.clone(), NativeFq::from(0u64));
+        let verify = prove_native(setup, PoseidonCommitment::new(params.clone(), commitment, blinding), msg);
+        assert!(verify(&[commitment]));
+        assert!(!verify(&[PoseidonCommitment::commit(&params, &NativeFq::from(43u64), &blinding)]));
+    }
+
+    #[test]
+    fn test_pedersen_commitment_groth16() {
+        type TestCommitment = PedersenCommitment<ark_bls12_377::g1::Parameters>;
+        let mut rng = ark_std::test_rng();
+        let (msg, blinding) = (NativeFq::from(42u64), ark_bls12_377::Fr::rand(&mut rng));
+        let commitment = TestCommitment::commit(&msg, &blinding);
+        let other = TestCommitment::commit(&NativeFq::from(43u64), &blinding);
+
+        let setup = TestCommitment::public(TestCommitment::generators().0);
+        let verify = prove_native(setup, TestCommitment::new(commitment, blinding), msg);
+        assert!(verify(&[commitment.x, commitment.y]));
+        assert!(!verify(&[other.x, other.y]));
+    }
+
+    #[test]
+    fn test_sha256_preimage() {
+        let msg = Fq::from_be_bytes_mod_order(b"timelocked reveal");
+        let predicate = Sha256Preimage::new(Sha256Preimage::digest(&msg));
+
+        for (msg, is_preimage) in [(msg, true), (msg + Fq::from(1u64), false)] {
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let msg = FpVar::new_witness(cs.clone(), || Ok(msg)).unwrap();
+            predicate.enforce(cs.clone(), &msg).unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), is_preimage);
+            assert_eq!(cs.borrow().unwrap().instance_assignment[1..], PlaintextPredicate::<Fq>::public_inputs(&predicate));
+        }
+
+        type CF = ark_bn254::Fr;
+        let cs = ConstraintSystem::<CF>::new_ref();
+        let msg = FqVar::<CF>::new_witness(cs.clone(), || Ok(msg)).unwrap();
+        PlaintextPredicate::<CF, FqVar<CF>>::enforce(&predicate, cs.clone(), &msg).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_sha256_preimage_groth16() {
+        let msg = NativeFq::from_be_bytes_mod_order(b"timelocked reveal");
+        let predicate = Sha256Preimage::new(Sha256Preimage::digest(&msg));
+        let inputs = PlaintextPredicate::<NativeFq>::public_inputs(&predicate);
+        let other = Sha256Preimage::new(Sha256Preimage::digest(&(msg + NativeFq::from(1u64))));
+
+        // Keys don't depend on the digest, which is a public input.
+        let verify = prove_native(Sha256Preimage::new([0; 32]), predicate, msg);
+        assert!(verify(&inputs));
+        assert!(!verify(&PlaintextPredicate::<NativeFq>::public_inputs(&other)));
+    }
+}
@@ -0,0 +1,866 @@
+//! Proof systems for the timelock circuits behind a common [`TimelockProver`] interface, so that
+//! applications can switch backends by configuration instead of wiring each one by hand.
+
+use std::borrow::Borrow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use anyhow::anyhow;
+use ark_ec::bls12::Bls12Parameters;
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_ff::{Fp12ConfigWrapper, One, PrimeField, QuadExtField, ToConstraintField};
+use ark_groth16::Groth16;
+use ark_marlin::{IndexProverKey, IndexVerifierKey, SimpleHashFiatShamirRng, UniversalSRS};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::bls12;
+use ark_r1cs_std::groups::curves::short_weierstrass::ProjectiveVar;
+use ark_r1cs_std::prelude::{AllocVar, CurveVar};
+use ark_relations::r1cs::{ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode};
+use ark_gemini::circuit::R1cs;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_sponge::Absorb;
+use ark_std::rand::{CryptoRng, Rng};
+use blake2::Blake2s;
+use halo2_proofs::pasta::EqAffine;
+use halo2_proofs::plonk::{self, SingleVerifier};
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+use rand_chacha::ChaChaRng;
+use crate::{halo2, keys};
+use crate::nonnative::NonNativeConfig;
+use crate::utils::{GtAbsorbable, Hash2Curve};
+use crate::yt6_776::YT6_776;
+use crate::{Bls12_381, Ciphertext, Circuit, DecryptionCircuit, DigestCircuit, GeminiNativeCircuit, NonnativeCircuit, Plaintext, PublicKey, RoundCircuit, RoundNumberCircuit, SecretKey};
+
+/// A proof system proving that a ciphertext is a valid encryption to some `Gid`.
+pub trait TimelockProver {
+    /// Pairing-friendly curve that messages are encrypted over.
+    type Curve: Pairing;
+    /// Circuit proven by this backend.
+    type Circuit;
+    /// Field of the public inputs of [`Self::Circuit`], hence of those allocated by its plaintext
+    /// predicates.
+    type Field;
+    type ProvingKey;
+    type VerifyingKey;
+    type Proof;
+
+    /// Optimization goal the backend synthesizes [`Self::Circuit`] with, which determines its
+    /// constraint matrices, e.g. to compute a [`keys::KeyHeader`].
+    const OPTIMIZATION_GOAL: OptimizationGoal = OptimizationGoal::Constraints;
+
+    /// Generates the keys for `circuit`, whose assignments are ignored, e.g. a blank circuit with
+    /// the plaintext predicates that proofs will be generated for.
+    fn setup<R: Rng + CryptoRng>(circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<(Self::ProvingKey, Self::VerifyingKey)>;
+
+    /// Proves that the ciphertext assigned to `circuit` is a valid encryption.
+    fn prove<R: Rng + CryptoRng>(
+        pk: &Self::ProvingKey,
+        circuit: Self::Circuit,
+        rng: &mut R,
+    ) -> anyhow::Result<Self::Proof>;
+
+    /// Verifies that `ct` is a valid encryption to `gid` of a plaintext satisfying the predicates
+    /// of the circuit, whose public inputs are `predicate_inputs`, see `Circuit::predicate_inputs`.
+    fn verify(
+        vk: &Self::VerifyingKey,
+        gid: &<Self::Curve as Pairing>::TargetField,
+        ct: &Ciphertext<<Self::Curve as Pairing>::G1>,
+        predicate_inputs: &[Self::Field],
+        proof: &Self::Proof,
+    ) -> anyhow::Result<bool>;
+}
+
+/// Groth16 over `PE` for the native [`Circuit`] over `E`, e.g. BW6-761 for BLS12-377.
+pub struct Groth16Native<E, P, PE>(PhantomData<(E, P, PE)>);
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>, PE: Pairing<ScalarField = <E::G1 as CurveGroup>::BaseField>> TimelockProver for Groth16Native<E, P, PE>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+          E::TargetField: ToConstraintField<<E::G1 as CurveGroup>::BaseField> + Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    type Curve = E;
+    type Circuit = Circuit<E, P>;
+    type Field = <E::G1 as CurveGroup>::BaseField;
+    type ProvingKey = ark_groth16::ProvingKey<PE>;
+    type VerifyingKey = ark_groth16::VerifyingKey<PE>;
+    type Proof = ark_groth16::Proof<PE>;
+
+    fn setup<R: Rng + CryptoRng>(circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<(Self::ProvingKey, Self::VerifyingKey)> {
+        Groth16::<PE>::setup(circuit, rng)
+            .map_err(|e| anyhow!("error running setup: {e}"))
+    }
+
+    fn prove<R: Rng + CryptoRng>(pk: &Self::ProvingKey, circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<Self::Proof> {
+        Groth16::<PE>::prove(pk, circuit, rng)
+            .map_err(|e| anyhow!("error proving encryption: {e}"))
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        gid: &E::TargetField,
+        ct: &Ciphertext<E::G1>,
+        predicate_inputs: &[Self::Field],
+        proof: &Self::Proof,
+    ) -> anyhow::Result<bool> {
+        let mut public_inputs = Circuit::<E, P>::get_public_inputs(gid, ct);
+        public_inputs.extend_from_slice(predicate_inputs);
+        Groth16::<PE>::verify(vk, &public_inputs, proof)
+            .map_err(|e| anyhow!("error verifying proof: {e}"))
+    }
+}
+
+/// Groth16 over `PE` for the [`DigestCircuit`] over `E`, whose verifier processes a single
+/// public input, the digest of `Gid` and the ciphertext.
+pub struct Groth16Digest<E, P, PE>(PhantomData<(E, P, PE)>);
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>, PE: Pairing<ScalarField = <E::G1 as CurveGroup>::BaseField>> TimelockProver for Groth16Digest<E, P, PE>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+          E::TargetField: ToConstraintField<<E::G1 as CurveGroup>::BaseField> + Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    type Curve = E;
+    type Circuit = DigestCircuit<E, P>;
+    type Field = <E::G1 as CurveGroup>::BaseField;
+    type ProvingKey = ark_groth16::ProvingKey<PE>;
+    type VerifyingKey = ark_groth16::VerifyingKey<PE>;
+    type Proof = ark_groth16::Proof<PE>;
+
+    fn setup<R: Rng + CryptoRng>(circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<(Self::ProvingKey, Self::VerifyingKey)> {
+        Groth16::<PE>::setup(circuit, rng)
+            .map_err(|e| anyhow!("error running setup: {e}"))
+    }
+
+    fn prove<R: Rng + CryptoRng>(pk: &Self::ProvingKey, circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<Self::Proof> {
+        Groth16::<PE>::prove(pk, circuit, rng)
+            .map_err(|e| anyhow!("error proving encryption: {e}"))
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        gid: &E::TargetField,
+        ct: &Ciphertext<E::G1>,
+        predicate_inputs: &[Self::Field],
+        proof: &Self::Proof,
+    ) -> anyhow::Result<bool> {
+        let mut public_inputs = DigestCircuit::<E, P>::get_public_inputs(gid, ct);
+        public_inputs.extend_from_slice(predicate_inputs);
+        Groth16::<PE>::verify(vk, &public_inputs, proof)
+            .map_err(|e| anyhow!("error verifying proof: {e}"))
+    }
+}
+
+/// Groth16 over `PE` for the [`DecryptionCircuit`] over `E`, proving that a ciphertext decrypts to
+/// a plaintext once the signature of its round is published.
+///
+/// Its statement includes the plaintext, so it doesn't fit [`TimelockProver`].
+pub struct Groth16Decryption<E, P, PE>(PhantomData<(E, P, PE)>);
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>, PE: Pairing<ScalarField = <E::G1 as CurveGroup>::BaseField>> Groth16Decryption<E, P, PE>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+          E::TargetField: ToConstraintField<<E::G1 as CurveGroup>::BaseField> + Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+          bls12::G2Var<P>: AllocVar<SecretKey<E>, <E::G1 as CurveGroup>::BaseField>,
+{
+    pub fn setup<R: Rng + CryptoRng>(rng: &mut R) -> anyhow::Result<(ark_groth16::ProvingKey<PE>, ark_groth16::VerifyingKey<PE>)> {
+        Groth16::<PE>::setup(DecryptionCircuit::<E, P>::blank(), rng)
+            .map_err(|e| anyhow!("error running setup: {e}"))
+    }
+
+    /// Decrypts `ct` with the round signature `signature`, returning the plaintext along with a
+    /// proof that it is the decryption of `ct`.
+    pub fn prove<R: Rng + CryptoRng>(
+        pk: &ark_groth16::ProvingKey<PE>,
+        signature: SecretKey<E>,
+        ct: Ciphertext<E::G1>,
+        rng: &mut R,
+    ) -> anyhow::Result<(Plaintext<E::G1>, ark_groth16::Proof<PE>)> {
+        let circuit = DecryptionCircuit::<E, P>::new(signature, ct)?;
+        let msg = circuit.plaintext()
+            .ok_or_else(|| anyhow!("circuit is missing plaintext"))?;
+        let proof = Groth16::<PE>::prove(pk, circuit, rng)
+            .map_err(|e| anyhow!("error proving decryption: {e}"))?;
+
+        Ok((msg, proof))
+    }
+
+    pub fn verify(
+        vk: &ark_groth16::VerifyingKey<PE>,
+        gid: &E::TargetField,
+        ct: &Ciphertext<E::G1>,
+        msg: &Plaintext<E::G1>,
+        proof: &ark_groth16::Proof<PE>,
+    ) -> anyhow::Result<bool> {
+        let public_inputs = DecryptionCircuit::<E, P>::get_public_inputs(gid, ct, msg);
+        Groth16::<PE>::verify(vk, &public_inputs, proof)
+            .map_err(|e| anyhow!("error verifying proof: {e}"))
+    }
+}
+
+/// Groth16 over `PE` for the [`RoundNumberCircuit`], whose constraint field is the base field of
+/// BLS12-381, e.g. YT6-776.
+///
+/// Its keys are bound to the master key of the network and its statement is the round number in
+/// place of `Gid`, so it doesn't fit [`TimelockProver`].
+pub struct Groth16RoundNumber<PE>(PhantomData<PE>);
+
+impl<PE: Pairing<ScalarField = ark_bls12_381::Fq>> Groth16RoundNumber<PE> {
+    /// Generates the keys for the rounds of the network with key `master`.
+    pub fn setup<R: Rng + CryptoRng>(
+        master: PublicKey<Bls12_381>,
+        rng: &mut R,
+    ) -> anyhow::Result<(ark_groth16::ProvingKey<PE>, ark_groth16::VerifyingKey<PE>)> {
+        Groth16::<PE>::setup(RoundNumberCircuit::blank(master), rng)
+            .map_err(|e| anyhow!("error running setup: {e}"))
+    }
+
+    pub fn prove<R: Rng + CryptoRng>(
+        pk: &ark_groth16::ProvingKey<PE>,
+        circuit: RoundNumberCircuit,
+        rng: &mut R,
+    ) -> anyhow::Result<ark_groth16::Proof<PE>> {
+        Groth16::<PE>::prove(pk, circuit, rng)
+            .map_err(|e| anyhow!("error proving encryption: {e}"))
+    }
+
+    /// Verifies that `ct` is a valid encryption to the round `round`.
+    pub fn verify(
+        vk: &ark_groth16::VerifyingKey<PE>,
+        round: u64,
+        ct: &Ciphertext<ark_bls12_381::G1Projective>,
+        proof: &ark_groth16::Proof<PE>,
+    ) -> anyhow::Result<bool> {
+        let public_inputs = RoundNumberCircuit::get_public_inputs(round, ct);
+        Groth16::<PE>::verify(vk, &public_inputs, proof)
+            .map_err(|e| anyhow!("error verifying proof: {e}"))
+    }
+}
+
+/// Groth16 over `PE` for the [`NonnativeCircuit`] over `PC::BaseField`, e.g. BN254 with Baby Jubjub.
+pub struct Groth16Nonnative<PE, PC>(PhantomData<(PE, PC)>);
+
+impl<PE: Pairing, PC: CurveGroup<BaseField = PE::ScalarField>> TimelockProver for Groth16Nonnative<PE, PC>
+    where PC::BaseField: NonNativeConfig + Absorb
+{
+    type Curve = Bls12_381;
+    type Circuit = NonnativeCircuit<PC>;
+    type Field = PE::ScalarField;
+    type ProvingKey = ark_groth16::ProvingKey<PE>;
+    type VerifyingKey = ark_groth16::VerifyingKey<PE>;
+    type Proof = ark_groth16::Proof<PE>;
+
+    fn setup<R: Rng + CryptoRng>(circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<(Self::ProvingKey, Self::VerifyingKey)> {
+        Groth16::<PE>::setup(circuit, rng)
+            .map_err(|e| anyhow!("error running setup: {e}"))
+    }
+
+    fn prove<R: Rng + CryptoRng>(pk: &Self::ProvingKey, circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<Self::Proof> {
+        Groth16::<PE>::prove(pk, circuit, rng)
+            .map_err(|e| anyhow!("error proving encryption: {e}"))
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        gid: &ark_bls12_381::Fq12,
+        ct: &Ciphertext<ark_bls12_381::G1Projective>,
+        predicate_inputs: &[Self::Field],
+        proof: &Self::Proof,
+    ) -> anyhow::Result<bool> {
+        let mut public_inputs = NonnativeCircuit::<PC>::get_public_inputs(gid, ct);
+        public_inputs.extend_from_slice(predicate_inputs);
+        Groth16::<PE>::verify(vk, &public_inputs, proof)
+            .map_err(|e| anyhow!("error verifying proof: {e}"))
+    }
+}
+
+/// Marlin over `PE` with the KZG-based polynomial commitment, for the native [`Circuit`] over `E`.
+///
+/// Unlike Groth16, keys are derived from a universal SRS by indexing the circuit, so that
+/// circuit variants can share a single setup.
+pub struct MarlinNative<E, P, PE>(PhantomData<(E, P, PE)>);
+
+type MarlinPC<PE> = MarlinKZG10<PE, DensePolynomial<<PE as Pairing>::ScalarField>>;
+
+type MarlinInst<PE> = ark_marlin::Marlin<
+    <PE as Pairing>::ScalarField,
+    MarlinPC<PE>,
+    SimpleHashFiatShamirRng<Blake2s, ChaChaRng>,
+>;
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>, PE: Pairing<ScalarField = <E::G1 as CurveGroup>::BaseField>> MarlinNative<E, P, PE>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+          E::TargetField: ToConstraintField<<E::G1 as CurveGroup>::BaseField> + Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    /// Generates a universal SRS supporting circuits up to the given dimensions.
+    pub fn universal_setup<R: Rng + CryptoRng>(
+        num_constraints: usize,
+        num_variables: usize,
+        num_non_zero: usize,
+        rng: &mut R,
+    ) -> anyhow::Result<UniversalSRS<PE::ScalarField, MarlinPC<PE>>> {
+        MarlinInst::<PE>::universal_setup(num_constraints, num_variables, num_non_zero, rng)
+            .map_err(|e| anyhow!("error generating universal SRS: {e:?}"))
+    }
+
+    /// Generates a universal SRS large enough for [`Circuit`].
+    pub fn universal_setup_for_circuit<R: Rng + CryptoRng>(
+        rng: &mut R,
+    ) -> anyhow::Result<UniversalSRS<PE::ScalarField, MarlinPC<PE>>> {
+        Self::universal_setup_for(Circuit::<E, P>::blank(), rng)
+    }
+
+    /// Generates a universal SRS large enough for `circuit`, e.g. with plaintext predicates.
+    pub fn universal_setup_for<R: Rng + CryptoRng>(
+        circuit: Circuit<E, P>,
+        rng: &mut R,
+    ) -> anyhow::Result<UniversalSRS<PE::ScalarField, MarlinPC<PE>>> {
+        let matrices = blank_matrices(circuit, <Self as TimelockProver>::OPTIMIZATION_GOAL)?;
+        let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+        // Marlin pads the matrices to be square.
+        let size = matrices.num_constraints.max(num_variables);
+        let num_non_zero = matrices.a_num_non_zero
+            .max(matrices.b_num_non_zero)
+            .max(matrices.c_num_non_zero);
+
+        Self::universal_setup(size, size, num_non_zero, rng)
+    }
+
+    /// Derives the keys of [`Circuit`] from `srs`.
+    pub fn index(
+        srs: &UniversalSRS<PE::ScalarField, MarlinPC<PE>>,
+    ) -> anyhow::Result<(IndexProverKey<PE::ScalarField, MarlinPC<PE>>, IndexVerifierKey<PE::ScalarField, MarlinPC<PE>>)> {
+        MarlinInst::<PE>::index(srs, Circuit::<E, P>::blank())
+            .map_err(|e| anyhow!("error indexing circuit: {e:?}"))
+    }
+
+    /// Derives the keys of the [`RoundCircuit`] of `gid` from `srs`. The round circuit is smaller
+    /// than [`Circuit`], so an SRS from [`MarlinNative::universal_setup_for_circuit`] serves every round.
+    pub fn index_round(
+        srs: &UniversalSRS<PE::ScalarField, MarlinPC<PE>>,
+        gid: &E::TargetField,
+    ) -> anyhow::Result<(IndexProverKey<PE::ScalarField, MarlinPC<PE>>, IndexVerifierKey<PE::ScalarField, MarlinPC<PE>>)> {
+        MarlinInst::<PE>::index(srs, RoundCircuit::<E, P>::blank(gid.clone()))
+            .map_err(|e| anyhow!("error indexing round circuit: {e:?}"))
+    }
+
+    /// Proves that the ciphertext assigned to `circuit` is a valid encryption to its round.
+    pub fn prove_round<R: Rng + CryptoRng>(
+        pk: &IndexProverKey<PE::ScalarField, MarlinPC<PE>>,
+        circuit: RoundCircuit<E, P>,
+        rng: &mut R,
+    ) -> anyhow::Result<ark_marlin::Proof<PE::ScalarField, MarlinPC<PE>>> {
+        MarlinInst::<PE>::prove(pk, circuit, rng)
+            .map_err(|e| anyhow!("error proving encryption: {e:?}"))
+    }
+
+    /// Verifies that `ct` is a valid encryption to the round whose keys `vk` are.
+    pub fn verify_round(
+        vk: &IndexVerifierKey<PE::ScalarField, MarlinPC<PE>>,
+        ct: &Ciphertext<E::G1>,
+        proof: &ark_marlin::Proof<PE::ScalarField, MarlinPC<PE>>,
+    ) -> anyhow::Result<bool> {
+        let public_inputs = RoundCircuit::<E, P>::get_public_inputs(ct);
+        MarlinInst::<PE>::verify(vk, &public_inputs, proof, &mut rand::thread_rng())
+            .map_err(|e| anyhow!("error verifying proof: {e:?}"))
+    }
+}
+
+impl<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>::BaseField>, PE: Pairing<ScalarField = <E::G1 as CurveGroup>::BaseField>> TimelockProver for MarlinNative<E, P, PE>
+    where <E::G1 as CurveGroup>::BaseField: PrimeField + Absorb,
+          E: Hash2Curve + GtAbsorbable,
+          E::G1: ToConstraintField<<E::G1 as CurveGroup>::BaseField>,
+          E::TargetField: ToConstraintField<<E::G1 as CurveGroup>::BaseField> + Borrow<QuadExtField<Fp12ConfigWrapper<<P as Bls12Parameters>::Fp12Config>>>,
+          ProjectiveVar<P::G1Parameters, FpVar<P::Fp>>: AllocVar<E::G1, <E::G1 as CurveGroup>::BaseField> + CurveVar<E::G1, <E::G1 as CurveGroup>::BaseField> + AllocVar<E::G1, P::Fp>,
+{
+    type Curve = E;
+    type Circuit = Circuit<E, P>;
+    type Field = <E::G1 as CurveGroup>::BaseField;
+    type ProvingKey = IndexProverKey<PE::ScalarField, MarlinPC<PE>>;
+    type VerifyingKey = IndexVerifierKey<PE::ScalarField, MarlinPC<PE>>;
+    type Proof = ark_marlin::Proof<PE::ScalarField, MarlinPC<PE>>;
+
+    // The Marlin indexer inlines linear combinations by weight.
+    const OPTIMIZATION_GOAL: OptimizationGoal = OptimizationGoal::Weight;
+
+    /// Generates a universal SRS sized for `circuit` and indexes it. To share an SRS between
+    /// circuits, use [`MarlinNative::universal_setup`] and [`MarlinNative::index`] instead.
+    fn setup<R: Rng + CryptoRng>(circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<(Self::ProvingKey, Self::VerifyingKey)> {
+        let srs = Self::universal_setup_for(circuit.clone(), rng)?;
+        MarlinInst::<PE>::index(&srs, circuit)
+            .map_err(|e| anyhow!("error indexing circuit: {e:?}"))
+    }
+
+    fn prove<R: Rng + CryptoRng>(pk: &Self::ProvingKey, circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<Self::Proof> {
+        MarlinInst::<PE>::prove(pk, circuit, rng)
+            .map_err(|e| anyhow!("error proving encryption: {e:?}"))
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        gid: &E::TargetField,
+        ct: &Ciphertext<E::G1>,
+        predicate_inputs: &[Self::Field],
+        proof: &Self::Proof,
+    ) -> anyhow::Result<bool> {
+        let mut public_inputs = Circuit::<E, P>::get_public_inputs(gid, ct);
+        public_inputs.extend_from_slice(predicate_inputs);
+        MarlinInst::<PE>::verify(vk, &public_inputs, proof, &mut rand::thread_rng())
+            .map_err(|e| anyhow!("error verifying proof: {e:?}"))
+    }
+}
+
+/// Gemini over YT6-776 for the [`GeminiNativeCircuit`].
+///
+/// Uses the non-preprocessing Gemini SNARK (`ark_gemini::snark`) rather than the preprocessing
+/// one (`ark_gemini::psnark`), which has a prover but no verifier at the pinned revision. Its
+/// verifier reads the R1CS matrices in time linear in their size; they are derived from the blank
+/// circuit once, by the setup, and kept in the [`GeminiVerifyingKey`].
+///
+/// Proofs are generated with the time-efficient prover only. The elastic prover would need
+/// streams that synthesize the circuit again for each pass, while `ConstraintSystem` always
+/// holds the whole relation in memory, so it wouldn't use less memory here.
+pub struct Gemini;
+
+/// Verifying key of [`Gemini`]: the KZG verifier key and the constraint matrices of
+/// [`GeminiNativeCircuit`], which the verifier of the non-preprocessing SNARK reads.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GeminiVerifyingKey {
+    pub kzg: ark_gemini::kzg::VerifierKey<YT6_776>,
+    pub a: Vec<Vec<(ark_bls12_381::Fq, usize)>>,
+    pub b: Vec<Vec<(ark_bls12_381::Fq, usize)>>,
+    pub c: Vec<Vec<(ark_bls12_381::Fq, usize)>>,
+    /// Number of instance variables, including the constant `1`.
+    pub num_instance_variables: usize,
+}
+
+impl Gemini {
+    const MAX_EVAL_POINTS: usize = 5;
+
+    /// Degree of the committer key needed to prove an R1CS returned by `generate_relation`.
+    pub fn committer_key_degree(r1cs: &R1cs<ark_bls12_381::Fq>) -> usize {
+        let num_non_zero = [&r1cs.a, &r1cs.b, &r1cs.c].into_iter()
+            .map(|m| m.iter().map(Vec::len).sum::<usize>())
+            .max()
+            .unwrap_or_default();
+        Self::degree(r1cs.a.len(), r1cs.z.len(), num_non_zero)
+    }
+
+    fn degree(num_constraints: usize, num_variables: usize, num_non_zero: usize) -> usize {
+        num_non_zero + num_variables + num_constraints
+    }
+
+    /// Fails if `ck` is too small to prove `r1cs`.
+    pub fn check_committer_key(
+        ck: &ark_gemini::kzg::CommitterKey<YT6_776>,
+        r1cs: &R1cs<ark_bls12_381::Fq>,
+    ) -> anyhow::Result<()> {
+        let degree = Self::committer_key_degree(r1cs);
+        if degree > ck.max_degree() {
+            return Err(anyhow!(
+                "circuit needs a committer key of degree {degree}, but the key only supports {}",
+                ck.max_degree()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Loads the committer key cached at `path`, or generates one sized for [`GeminiNativeCircuit`]
+    /// and caches it there. The constraint matrices of the verifying key are synthesized either way.
+    ///
+    /// Keys are cached with [`keys::write_key`], hence a key generated for another version of the
+    /// circuit is refused rather than used.
+    pub fn load_or_setup<P: AsRef<Path>, R: Rng + CryptoRng>(
+        path: P,
+        rng: &mut R,
+    ) -> anyhow::Result<(<Self as TimelockProver>::ProvingKey, <Self as TimelockProver>::VerifyingKey)> {
+        let path = path.as_ref();
+        let header = keys::KeyHeader::new::<YT6_776, _>(GeminiNativeCircuit::blank(), Self::OPTIMIZATION_GOAL)?;
+        if path.exists() {
+            let file = File::open(path)
+                .map_err(|e| anyhow!("error opening {}: {e}", path.display()))?;
+            let ck: ark_gemini::kzg::CommitterKey<YT6_776> = keys::read_key(&header, file)
+                .map_err(|e| anyhow!("error loading committer key from {}: {e}", path.display()))?;
+            let matrices = blank_matrices(GeminiNativeCircuit::blank(), Self::OPTIMIZATION_GOAL)?;
+            let vk = Self::verifying_key(&ck, matrices);
+            return Ok((ck, vk));
+        }
+
+        let (ck, vk) = Self::setup(GeminiNativeCircuit::blank(), rng)?;
+        let file = File::create(path)
+            .map_err(|e| anyhow!("error creating {}: {e}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        keys::write_key(&header, &ck, &mut writer)?;
+        writer.flush()?;
+
+        Ok((ck, vk))
+    }
+
+    fn verifying_key(ck: &ark_gemini::kzg::CommitterKey<YT6_776>, matrices: ConstraintMatrices<ark_bls12_381::Fq>) -> GeminiVerifyingKey {
+        GeminiVerifyingKey {
+            kzg: ark_gemini::kzg::VerifierKey::from(ck),
+            a: matrices.a,
+            b: matrices.b,
+            c: matrices.c,
+            num_instance_variables: matrices.num_instance_variables,
+        }
+    }
+
+    /// R1CS of [`GeminiNativeCircuit`] for the statement `(gid, ct)`, as read by the verifier.
+    ///
+    /// The matrices are copied from `vk` rather than synthesized.
+    pub fn relation(
+        vk: &GeminiVerifyingKey,
+        gid: &ark_bls12_381::Fq12,
+        ct: &Ciphertext<ark_bls12_381::G1Projective>,
+    ) -> anyhow::Result<R1cs<ark_bls12_381::Fq>> {
+        let x = std::iter::once(ark_bls12_381::Fq::one())
+            .chain(GeminiNativeCircuit::get_public_inputs(gid, ct))
+            .collect::<Vec<_>>();
+        if x.len() != vk.num_instance_variables {
+            return Err(anyhow!("expected {} instance variables, got {}", vk.num_instance_variables, x.len()));
+        }
+
+        Ok(R1cs {
+            a: vk.a.clone(),
+            b: vk.b.clone(),
+            c: vk.c.clone(),
+            z: vec![],
+            w: vec![],
+            x,
+        })
+    }
+}
+
+impl TimelockProver for Gemini {
+    type Curve = Bls12_381;
+    type Circuit = GeminiNativeCircuit;
+    type Field = ark_bls12_381::Fq;
+    type ProvingKey = ark_gemini::kzg::CommitterKey<YT6_776>;
+    type VerifyingKey = GeminiVerifyingKey;
+    type Proof = ark_gemini::snark::Proof<YT6_776>;
+
+    fn setup<R: Rng + CryptoRng>(circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<(Self::ProvingKey, Self::VerifyingKey)> {
+        circuit.check_predicates()?;
+        let matrices = blank_matrices(circuit, Self::OPTIMIZATION_GOAL)?;
+        let num_non_zero = matrices.a_num_non_zero
+            .max(matrices.b_num_non_zero)
+            .max(matrices.c_num_non_zero);
+        let degree = Self::degree(
+            matrices.num_constraints,
+            matrices.num_instance_variables + matrices.num_witness_variables,
+            num_non_zero,
+        );
+
+        let ck = ark_gemini::kzg::CommitterKey::<YT6_776>::new(degree, Self::MAX_EVAL_POINTS, rng);
+        let vk = Self::verifying_key(&ck, matrices);
+        Ok((ck, vk))
+    }
+
+    fn prove<R: Rng + CryptoRng>(pk: &Self::ProvingKey, circuit: Self::Circuit, _rng: &mut R) -> anyhow::Result<Self::Proof> {
+        circuit.check_predicates()?;
+        let r1cs = ark_gemini::circuit::generate_relation::<ark_bls12_381::Fq, GeminiNativeCircuit>(circuit);
+        Self::check_committer_key(pk, &r1cs)?;
+        Ok(ark_gemini::snark::Proof::new_time(&r1cs, pk))
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        gid: &ark_bls12_381::Fq12,
+        ct: &Ciphertext<ark_bls12_381::G1Projective>,
+        predicate_inputs: &[Self::Field],
+        proof: &Self::Proof,
+    ) -> anyhow::Result<bool> {
+        if !predicate_inputs.is_empty() {
+            return Err(anyhow!("plaintext predicates are not supported by Gemini"));
+        }
+        let r1cs = Self::relation(vk, gid, ct)?;
+        Ok(proof.verify(&r1cs, &vk.kzg).is_ok())
+    }
+}
+
+/// Halo2 with the IPA commitment scheme over Pallas for the [`halo2::TimelockCircuit`].
+///
+/// The IPA setup is transparent, the randomness passed to [`TimelockProver::setup`] is unused.
+/// Plaintext predicates are not supported.
+pub struct Halo2;
+
+/// Proving key of [`Halo2`], along with the parameters of the commitment scheme.
+#[derive(Clone, Debug)]
+pub struct Halo2ProvingKey {
+    pub params: Params<EqAffine>,
+    pub pk: plonk::ProvingKey<EqAffine>,
+}
+
+/// Verifying key of [`Halo2`], along with the parameters of the commitment scheme.
+#[derive(Clone, Debug)]
+pub struct Halo2VerifyingKey {
+    pub params: Params<EqAffine>,
+    pub vk: plonk::VerifyingKey<EqAffine>,
+}
+
+impl TimelockProver for Halo2 {
+    type Curve = Bls12_381;
+    type Circuit = halo2::TimelockCircuit;
+    type Field = halo2_proofs::pasta::Fp;
+    type ProvingKey = Halo2ProvingKey;
+    type VerifyingKey = Halo2VerifyingKey;
+    type Proof = Vec<u8>;
+
+    fn setup<R: Rng + CryptoRng>(circuit: Self::Circuit, _rng: &mut R) -> anyhow::Result<(Self::ProvingKey, Self::VerifyingKey)> {
+        let params = Params::<EqAffine>::new(halo2::K);
+        let vk = plonk::keygen_vk(&params, &circuit)
+            .map_err(|e| anyhow!("error generating verifying key: {e}"))?;
+        let pk = plonk::keygen_pk(&params, vk.clone(), &circuit)
+            .map_err(|e| anyhow!("error generating proving key: {e}"))?;
+
+        Ok((Halo2ProvingKey { params: params.clone(), pk }, Halo2VerifyingKey { params, vk }))
+    }
+
+    fn prove<R: Rng + CryptoRng>(pk: &Self::ProvingKey, circuit: Self::Circuit, rng: &mut R) -> anyhow::Result<Self::Proof> {
+        let gid = circuit.gid.as_ref().ok_or_else(|| anyhow!("circuit is missing gid"))?;
+        let ct = circuit.ciphertext.as_ref().ok_or_else(|| anyhow!("circuit is missing the ciphertext"))?;
+        let public_inputs = halo2::TimelockCircuit::get_public_inputs(gid, ct);
+
+        let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+        plonk::create_proof(&pk.params, &pk.pk, &[circuit], &[&[&public_inputs]], rng, &mut transcript)
+            .map_err(|e| anyhow!("error proving encryption: {e}"))?;
+        Ok(transcript.finalize())
+    }
+
+    fn verify(
+        vk: &Self::VerifyingKey,
+        gid: &ark_bls12_381::Fq12,
+        ct: &Ciphertext<ark_bls12_381::G1Projective>,
+        predicate_inputs: &[Self::Field],
+        proof: &Self::Proof,
+    ) -> anyhow::Result<bool> {
+        if !predicate_inputs.is_empty() {
+            return Err(anyhow!("plaintext predicates are not supported by Halo2"));
+        }
+        let public_inputs = halo2::TimelockCircuit::get_public_inputs(gid, ct);
+        let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+        let strategy = SingleVerifier::new(&vk.params);
+        Ok(plonk::verify_proof(&vk.params, &vk.vk, strategy, &[&[&public_inputs]], &mut transcript).is_ok())
+    }
+}
+
+/// Synthesizes `circuit` without assignments for `goal` and returns its constraint matrices.
+fn blank_matrices<F: PrimeField, C: ConstraintSynthesizer<F>>(circuit: C, goal: OptimizationGoal) -> anyhow::Result<ConstraintMatrices<F>> {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(goal);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone())
+        .map_err(|e| anyhow!("error synthesizing circuit: {e}"))?;
+    cs.finalize();
+
+    cs.to_matrices()
+        .ok_or_else(|| anyhow!("constraint matrices are unavailable"))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::{AffineRepr, Group};
+    use ark_ff::Zero;
+    use ark_r1cs_std::eq::EqGadget;
+    use ark_relations::ns;
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+    use ark_std::{test_rng, UniformRand};
+    use crate::predicate::PlaintextPredicate;
+    use super::*;
+
+    /// Returns a random master key of the network and a random plaintext.
+    fn random_master_and_msg<R: Rng>(rng: &mut R) -> (PublicKey<Bls12_381>, Plaintext<ark_bls12_381::G1Projective>) {
+        let master = (ark_bls12_381::G1Projective::generator() * ark_bls12_381::Fr::rand(rng)).into_affine();
+        (master, ark_bls12_381::Fq::rand(rng))
+    }
+
+    #[test]
+    fn test_gemini_rejects_tampered_ciphertext() {
+        let mut rng = test_rng();
+        let sk = ark_bls12_381::Fr::rand(&mut rng);
+        let master = (ark_bls12_381::G1Projective::generator() * sk).into_affine();
+        let id = b"round-1000";
+        let msg = ark_bls12_381::Fq::rand(&mut rng);
+
+        let (ck, vk) = Gemini::setup(GeminiNativeCircuit::blank(), &mut rng).unwrap();
+        let header = keys::KeyHeader::new::<YT6_776, _>(GeminiNativeCircuit::blank(), Gemini::OPTIMIZATION_GOAL).unwrap();
+        let mut bytes = vec![];
+        keys::write_key(&header, &vk, &mut bytes).unwrap();
+        let vk: GeminiVerifyingKey = keys::read_key(&header, &bytes[..]).unwrap();
+
+        let circuit = GeminiNativeCircuit(Circuit::new(master, id, msg, &mut rng).unwrap());
+        let gid = circuit.0.gid.clone().unwrap();
+        let ct = circuit.0.ciphertext.clone().unwrap();
+        let proof = Gemini::prove(&ck, circuit, &mut rng).unwrap();
+
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let proof = ark_gemini::snark::Proof::<YT6_776>::deserialize_compressed(&bytes[..]).unwrap();
+        assert!(Gemini::verify(&vk, &gid, &ct, &[], &proof).unwrap());
+
+        let mut tampered = ct.clone();
+        tampered.w += ark_bls12_381::Fq::one();
+        assert!(!Gemini::verify(&vk, &gid, &tampered, &[], &proof).unwrap());
+
+        let other_gid = GeminiNativeCircuit(Circuit::new(master, b"round-1001", msg, &mut rng).unwrap()).0.gid.unwrap();
+        assert!(!Gemini::verify(&vk, &other_gid, &ct, &[], &proof).unwrap());
+    }
+
+    #[test]
+    fn test_gemini_committer_key_too_small() {
+        let mut rng = test_rng();
+        let master = ark_bls12_381::G1Affine::generator();
+        let msg = ark_bls12_381::Fq::rand(&mut rng);
+
+        let circuit = GeminiNativeCircuit(Circuit::new(master, b"round-1000", msg, &mut rng).unwrap());
+        let r1cs = ark_gemini::circuit::generate_relation::<ark_bls12_381::Fq, GeminiNativeCircuit>(circuit);
+        let degree = Gemini::committer_key_degree(&r1cs);
+
+        let ck = ark_gemini::kzg::CommitterKey::<YT6_776>::new(degree / 2, 5, &mut rng);
+        let err = Gemini::check_committer_key(&ck, &r1cs).unwrap_err();
+        assert!(err.to_string().contains(&degree.to_string()));
+    }
+
+    #[test]
+    fn test_groth16_native_with_predicate() {
+        // The plaintext is revealed as a public input.
+        struct Reveal(ark_bls12_377::Fq);
+
+        impl PlaintextPredicate<ark_bls12_377::Fq> for Reveal {
+            fn enforce(&self, cs: ConstraintSystemRef<ark_bls12_377::Fq>, msg: &FpVar<ark_bls12_377::Fq>) -> Result<(), SynthesisError> {
+                FpVar::new_input(ns!(cs, "revealed"), || Ok(self.0))?.enforce_equal(msg)
+            }
+
+            fn public_inputs(&self) -> Vec<ark_bls12_377::Fq> {
+                vec![self.0]
+            }
+        }
+
+        type Prover = Groth16Native<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters, ark_bw6_761::BW6_761>;
+        type TestCircuit = Circuit<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters>;
+        let mut rng = test_rng();
+        let master = ark_bls12_377::G1Affine::generator();
+        let msg = ark_bls12_377::Fq::rand(&mut rng);
+
+        // The value of the predicate is only used for the assignment.
+        let (pk, vk) = Prover::setup(TestCircuit::blank().with_predicate(Reveal(ark_bls12_377::Fq::zero())), &mut rng).unwrap();
+        let circuit = TestCircuit::new(master, b"round-1000", msg, &mut rng).unwrap()
+            .with_predicate(Reveal(msg));
+        let gid = circuit.gid.clone().unwrap();
+        let ct = circuit.ciphertext.clone().unwrap();
+        let predicate_inputs = circuit.predicate_inputs();
+        let proof = Prover::prove(&pk, circuit, &mut rng).unwrap();
+        assert!(Prover::verify(&vk, &gid, &ct, &predicate_inputs, &proof).unwrap());
+        assert!(!Prover::verify(&vk, &gid, &ct, &[msg + ark_bls12_377::Fq::one()], &proof).unwrap());
+        assert!(Prover::verify(&vk, &gid, &ct, &[], &proof).is_err());
+    }
+
+    #[test]
+    fn test_groth16_decryption() {
+        type Prover = Groth16Decryption<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters, ark_bw6_761::BW6_761>;
+        type TestCircuit = Circuit<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters>;
+        let mut rng = test_rng();
+        let sk = ark_bls12_377::Fr::rand(&mut rng);
+        let master = (ark_bls12_377::G1Projective::generator() * sk).into_affine();
+        let signature = (ark_bls12_377::Bls12_377::hash(b"round-1000", crate::H2C_DST).unwrap() * sk).into_affine();
+        let msg = ark_bls12_377::Fq::rand(&mut rng);
+
+        let (pk, vk) = Prover::setup(&mut rng).unwrap();
+        let ct = TestCircuit::encrypt(&master, b"round-1000", &msg, &mut rng).unwrap();
+        let gid = TestCircuit::gid(&master, b"round-1000").unwrap();
+        let (pt, proof) = Prover::prove(&pk, signature, ct.clone(), &mut rng).unwrap();
+        assert_eq!(pt, msg);
+        assert!(Prover::verify(&vk, &gid, &ct, &msg, &proof).unwrap());
+        assert!(!Prover::verify(&vk, &gid, &ct, &(msg + ark_bls12_377::Fq::one()), &proof).unwrap());
+    }
+
+    // Runs the setup and proof of the round number circuit over YT6-776, whose in-circuit hash to
+    // G2 and pairing take minutes.
+    #[test]
+    #[ignore]
+    fn test_groth16_round_number() {
+        type Prover = Groth16RoundNumber<YT6_776>;
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        let (pk, vk) = Prover::setup(master, &mut rng).unwrap();
+        let circuit = RoundNumberCircuit::new(master, 1000, msg, &mut rng).unwrap();
+        let ct = circuit.circuit.ciphertext.clone().unwrap();
+        let proof = Prover::prove(&pk, circuit, &mut rng).unwrap();
+        assert!(Prover::verify(&vk, 1000, &ct, &proof).unwrap());
+        assert!(!Prover::verify(&vk, 1001, &ct, &proof).unwrap());
+    }
+
+    // Runs the IPA setup and proof of the Halo2 circuit at `K = 19`, which take minutes.
+    #[test]
+    #[ignore]
+    fn test_halo2() {
+        let mut rng = test_rng();
+        let (master, msg) = random_master_and_msg(&mut rng);
+
+        let (pk, vk) = Halo2::setup(halo2::TimelockCircuit::blank(), &mut rng).unwrap();
+        let circuit = halo2::TimelockCircuit::new(master, b"round-1000", msg, &mut rng).unwrap();
+        let gid = circuit.gid.unwrap();
+        let ct = circuit.ciphertext.clone().unwrap();
+        let proof = Halo2::prove(&pk, circuit, &mut rng).unwrap();
+        assert!(Halo2::verify(&vk, &gid, &ct, &[], &proof).unwrap());
+
+        let tampered = Ciphertext { w: ct.w + ark_bls12_381::Fq::one(), ..ct };
+        assert!(!Halo2::verify(&vk, &gid, &tampered, &[], &proof).unwrap());
+    }
+
+    #[test]
+    fn test_marlin_native() {
+        type Prover = MarlinNative<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters, ark_bw6_761::BW6_761>;
+        type TestCircuit = Circuit<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters>;
+        let mut rng = test_rng();
+        let master = ark_bls12_377::G1Affine::generator();
+        let msg = ark_bls12_377::Fq::rand(&mut rng);
+
+        let (pk, vk) = Prover::setup(TestCircuit::blank(), &mut rng).unwrap();
+        let circuit = TestCircuit::new(master, b"round-1000", msg, &mut rng).unwrap();
+        let gid = circuit.gid.clone().unwrap();
+        let ct = circuit.ciphertext.clone().unwrap();
+        let proof = Prover::prove(&pk, circuit, &mut rng).unwrap();
+        assert!(Prover::verify(&vk, &gid, &ct, &[], &proof).unwrap());
+
+        let mut tampered = ct.clone();
+        tampered.w += ark_bls12_377::Fq::one();
+        assert!(!Prover::verify(&vk, &gid, &tampered, &[], &proof).unwrap());
+    }
+
+    #[test]
+    fn test_marlin_round_circuit() {
+        type Prover = MarlinNative<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters, ark_bw6_761::BW6_761>;
+        type TestCircuit = RoundCircuit<ark_bls12_377::Bls12_377, ark_bls12_377::Parameters>;
+        let mut rng = test_rng();
+        let master = ark_bls12_377::G1Affine::generator();
+        let msg = ark_bls12_377::Fq::rand(&mut rng);
+
+        let srs = Prover::universal_setup_for_circuit(&mut rng).unwrap();
+        let circuit = TestCircuit::new(master, b"round-1000", msg, &mut rng).unwrap();
+        let (pk, vk) = Prover::index_round(&srs, &circuit.gid).unwrap();
+        let ct = circuit.circuit.ciphertext.clone().unwrap();
+        let proof = Prover::prove_round(&pk, circuit, &mut rng).unwrap();
+        assert!(Prover::verify_round(&vk, &ct, &proof).unwrap());
+
+        let other = TestCircuit::new(master, b"round-1001", msg, &mut rng).unwrap();
+        let (_, other_vk) = Prover::index_round(&srs, &other.gid).unwrap();
+        assert!(!Prover::verify_round(&other_vk, &ct, &proof).unwrap());
+    }
+}
@@ -0,0 +1,753 @@
+//! Solidity verifier for BN254 timelock proofs, see [`crate::bn254`].
+//!
+//! The generated contract checks Groth16 proofs with the `ecAdd`, `ecMul` and `ecPairing`
+//! precompiles and recomputes the packed public inputs from `gid` and the ciphertext,
+//! exactly as [`NonnativeCircuit::get_public_inputs`](crate::NonnativeCircuit::get_public_inputs) does.
+//! BLS12-381 base field elements are passed to it as `[hi, lo]` 256-bit words of their
+//! big-endian encoding, which is what [`encode_fq`] produces.
+//!
+//! [`generate_verifier_bytecode`] assembles the same contract directly into EVM bytecode, so
+//! that it can be deployed, and is tested, without a Solidity compiler. With the `solc` feature,
+//! [`compile_verifier`] compiles the Solidity source instead, with a `solc` binary that is
+//! installed on first use.
+
+use std::fmt::Write;
+use anyhow::anyhow;
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::CurveGroup;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use num_bigint::BigUint;
+use num_traits::One;
+use sha3::{Digest, Keccak256};
+use crate::Ciphertext;
+use crate::nonnative::fp12_coefficients;
+
+/// Signature of the contract method that verifies a proof against `gid` and a ciphertext.
+pub const VERIFY_ENCRYPTION_SIGNATURE: &str = "verifyEncryption(uint256[8],uint256[2][12],uint256[2][4])";
+/// Signature of the contract method that packs `gid` and a ciphertext into the public inputs.
+pub const PUBLIC_INPUTS_SIGNATURE: &str = "publicInputs(uint256[2][12],uint256[2][4])";
+/// Signature of the contract method that verifies a proof against packed public inputs.
+pub const VERIFY_PROOF_SIGNATURE: &str = "verifyProof(uint256[8],uint256[])";
+
+/// Number of BLS12-381 base field elements in the public input: 12 coefficients of `gid`, then `U.x`, `U.y`, `V`, `W`.
+const NUM_PUBLIC_ELEMENTS: usize = 16;
+const FQ_BITS: usize = ark_bls12_381::Fq::MODULUS_BIT_SIZE as usize;
+const CHUNK_BITS: usize = Fr::MODULUS_BIT_SIZE as usize - 1;
+const TOTAL_BITS: usize = NUM_PUBLIC_ELEMENTS * FQ_BITS;
+const NUM_INPUTS: usize = TOTAL_BITS.div_ceil(CHUNK_BITS);
+
+/// `len` bits of the `elem`-th public element, starting from its `offset`-th least significant
+/// one, placed at bit `shift` of a packed input.
+struct Segment {
+    elem: usize,
+    offset: usize,
+    len: usize,
+    shift: usize,
+}
+
+/// Segments of the `i`-th input, which holds `CHUNK_BITS` bits of the little-endian
+/// concatenation of all elements.
+fn input_segments(i: usize) -> Vec<Segment> {
+    let (start, end) = (i * CHUNK_BITS, ((i + 1) * CHUNK_BITS).min(TOTAL_BITS));
+    (start / FQ_BITS..=(end - 1) / FQ_BITS)
+        .map(|j| {
+            let (seg_start, seg_end) = (start.max(j * FQ_BITS), end.min((j + 1) * FQ_BITS));
+            Segment { elem: j, offset: seg_start - j * FQ_BITS, len: seg_end - seg_start, shift: seg_start - start }
+        })
+        .collect()
+}
+
+fn check_vk(vk: &VerifyingKey<Bn254>) -> anyhow::Result<()> {
+    if vk.gamma_abc_g1.len() != NUM_INPUTS + 1 {
+        return Err(anyhow!(
+            "verifying key has {} public inputs, a timelock circuit has {NUM_INPUTS}",
+            vk.gamma_abc_g1.len() - 1
+        ));
+    }
+
+    Ok(())
+}
+
+/// Generates the Solidity source of a `TimelockVerifier` contract for `vk`.
+pub fn generate_verifier(vk: &VerifyingKey<Bn254>) -> anyhow::Result<String> {
+    check_vk(vk)?;
+
+    let mut src = String::new();
+    let [p_hi, p_lo] = split_words(&ark_bls12_381::Fq::MODULUS.to_bytes_be());
+
+    writeln!(src, "// SPDX-License-Identifier: MIT")?;
+    writeln!(src, "// Generated by zk-tlock from a Groth16 verifying key, do not edit.")?;
+    writeln!(src, "pragma solidity ^0.8.0;")?;
+    writeln!(src)?;
+    writeln!(src, "/// @title Verifier of proofs that a ciphertext is a valid timelock encryption to `gid`.")?;
+    writeln!(src, "/// @notice BLS12-381 base field elements are passed as `[hi, lo]` words of their big-endian encoding.")?;
+    writeln!(src, "contract TimelockVerifier {{")?;
+    writeln!(src, "    // Base field modulus of BN254.")?;
+    writeln!(src, "    uint256 constant Q = {};", word(&Fq::MODULUS.to_bytes_be()))?;
+    writeln!(src, "    // Scalar field modulus of BN254.")?;
+    writeln!(src, "    uint256 constant R = {};", word(&Fr::MODULUS.to_bytes_be()))?;
+    writeln!(src, "    // Base field modulus of BLS12-381.")?;
+    writeln!(src, "    uint256 constant P_HI = {};", word(&p_hi))?;
+    writeln!(src, "    uint256 constant P_LO = {};", word(&p_lo))?;
+    writeln!(src, "    uint256 constant NUM_INPUTS = {NUM_INPUTS};")?;
+    writeln!(src)?;
+    write_g1_constant(&mut src, "ALPHA", &vk.alpha_g1)?;
+    write_g2_constant(&mut src, "BETA", &vk.beta_g2)?;
+    write_g2_constant(&mut src, "GAMMA", &vk.gamma_g2)?;
+    write_g2_constant(&mut src, "DELTA", &vk.delta_g2)?;
+    writeln!(src)?;
+
+    writeln!(src, "    function verifyEncryption(")?;
+    writeln!(src, "        uint256[8] calldata proof,")?;
+    writeln!(src, "        uint256[2][12] calldata gid,")?;
+    writeln!(src, "        uint256[2][4] calldata ct")?;
+    writeln!(src, "    ) external view returns (bool) {{")?;
+    writeln!(src, "        return verifyProof(proof, publicInputs(gid, ct));")?;
+    writeln!(src, "    }}")?;
+    writeln!(src)?;
+
+    // Each input holds `CHUNK_BITS` bits of the little-endian concatenation of all elements.
+    writeln!(src, "    function publicInputs(")?;
+    writeln!(src, "        uint256[2][12] calldata gid,")?;
+    writeln!(src, "        uint256[2][4] calldata ct")?;
+    writeln!(src, "    ) public pure returns (uint256[] memory input) {{")?;
+    writeln!(src, "        for (uint256 i = 0; i < 12; i++) _checkFq(gid[i]);")?;
+    writeln!(src, "        for (uint256 i = 0; i < 4; i++) _checkFq(ct[i]);")?;
+    writeln!(src)?;
+    writeln!(src, "        input = new uint256[](NUM_INPUTS);")?;
+    for i in 0..NUM_INPUTS {
+        let segments = input_segments(i).into_iter()
+            .map(|seg| {
+                let elem = if seg.elem < 12 { format!("gid[{}]", seg.elem) } else { format!("ct[{}]", seg.elem - 12) };
+                let bits = format!("_bits({elem}, {}, {})", seg.offset, seg.len);
+                match seg.shift {
+                    0 => bits,
+                    shift => format!("({bits} << {shift})"),
+                }
+            })
+            .collect::<Vec<_>>();
+        writeln!(src, "        input[{i}] = {};", segments.join(" | "))?;
+    }
+    writeln!(src, "    }}")?;
+    writeln!(src)?;
+
+    writeln!(src, "    function verifyProof(uint256[8] calldata proof, uint256[] memory input) public view returns (bool) {{")?;
+    writeln!(src, "        require(input.length == NUM_INPUTS, \"invalid number of inputs\");")?;
+    writeln!(src, "        for (uint256 i = 0; i < 8; i++) require(proof[i] < Q, \"proof element not in field\");")?;
+    writeln!(src)?;
+    writeln!(src, "        uint256[2] memory acc;")?;
+    writeln!(src, "        acc[0] = {};", fq_word(&vk.gamma_abc_g1[0].x))?;
+    writeln!(src, "        acc[1] = {};", fq_word(&vk.gamma_abc_g1[0].y))?;
+    for (i, p) in vk.gamma_abc_g1.iter().skip(1).enumerate() {
+        writeln!(src, "        _mulAcc(acc, {}, {}, input[{i}]);", fq_word(&p.x), fq_word(&p.y))?;
+    }
+    writeln!(src)?;
+    writeln!(src, "        // e(-A, B) * e(alpha, beta) * e(acc, gamma) * e(C, delta) == 1")?;
+    writeln!(src, "        uint256[24] memory p;")?;
+    writeln!(src, "        p[0] = proof[0];")?;
+    writeln!(src, "        p[1] = (Q - proof[1]) % Q;")?;
+    writeln!(src, "        p[2] = proof[2];")?;
+    writeln!(src, "        p[3] = proof[3];")?;
+    writeln!(src, "        p[4] = proof[4];")?;
+    writeln!(src, "        p[5] = proof[5];")?;
+    writeln!(src, "        p[6] = ALPHA_X;")?;
+    writeln!(src, "        p[7] = ALPHA_Y;")?;
+    writeln!(src, "        p[8] = BETA_X1;")?;
+    writeln!(src, "        p[9] = BETA_X0;")?;
+    writeln!(src, "        p[10] = BETA_Y1;")?;
+    writeln!(src, "        p[11] = BETA_Y0;")?;
+    writeln!(src, "        p[12] = acc[0];")?;
+    writeln!(src, "        p[13] = acc[1];")?;
+    writeln!(src, "        p[14] = GAMMA_X1;")?;
+    writeln!(src, "        p[15] = GAMMA_X0;")?;
+    writeln!(src, "        p[16] = GAMMA_Y1;")?;
+    writeln!(src, "        p[17] = GAMMA_Y0;")?;
+    writeln!(src, "        p[18] = proof[6];")?;
+    writeln!(src, "        p[19] = proof[7];")?;
+    writeln!(src, "        p[20] = DELTA_X1;")?;
+    writeln!(src, "        p[21] = DELTA_X0;")?;
+    writeln!(src, "        p[22] = DELTA_Y1;")?;
+    writeln!(src, "        p[23] = DELTA_Y0;")?;
+    writeln!(src)?;
+    writeln!(src, "        uint256[1] memory out;")?;
+    writeln!(src, "        bool ok;")?;
+    writeln!(src, "        assembly {{")?;
+    writeln!(src, "            ok := staticcall(gas(), 8, p, 0x300, out, 0x20)")?;
+    writeln!(src, "        }}")?;
+    writeln!(src, "        return ok && out[0] == 1;")?;
+    writeln!(src, "    }}")?;
+    writeln!(src)?;
+
+    writeln!(src, "    function _mulAcc(uint256[2] memory acc, uint256 x, uint256 y, uint256 s) internal view {{")?;
+    writeln!(src, "        require(s < R, \"input not in field\");")?;
+    writeln!(src, "        uint256[4] memory buf;")?;
+    writeln!(src, "        buf[0] = x;")?;
+    writeln!(src, "        buf[1] = y;")?;
+    writeln!(src, "        buf[2] = s;")?;
+    writeln!(src, "        bool ok;")?;
+    writeln!(src, "        assembly {{")?;
+    writeln!(src, "            ok := staticcall(gas(), 7, buf, 0x60, buf, 0x40)")?;
+    writeln!(src, "        }}")?;
+    writeln!(src, "        require(ok, \"ecMul failed\");")?;
+    writeln!(src, "        buf[2] = acc[0];")?;
+    writeln!(src, "        buf[3] = acc[1];")?;
+    writeln!(src, "        assembly {{")?;
+    writeln!(src, "            ok := staticcall(gas(), 6, buf, 0x80, acc, 0x40)")?;
+    writeln!(src, "        }}")?;
+    writeln!(src, "        require(ok, \"ecAdd failed\");")?;
+    writeln!(src, "    }}")?;
+    writeln!(src)?;
+
+    writeln!(src, "    function _checkFq(uint256[2] calldata e) internal pure {{")?;
+    writeln!(src, "        require(e[0] < P_HI || (e[0] == P_HI && e[1] < P_LO), \"not a BLS12-381 base field element\");")?;
+    writeln!(src, "    }}")?;
+    writeln!(src)?;
+
+    writeln!(src, "    // Returns `len` bits of `e` starting from the `offset`-th least significant one.")?;
+    writeln!(src, "    function _bits(uint256[2] calldata e, uint256 offset, uint256 len) internal pure returns (uint256) {{")?;
+    writeln!(src, "        uint256 v = offset >= 256 ? e[0] >> (offset - 256) : (e[1] >> offset) | (e[0] << (256 - offset));")?;
+    writeln!(src, "        return v & ((1 << len) - 1);")?;
+    writeln!(src, "    }}")?;
+    writeln!(src, "}}")?;
+
+    Ok(src)
+}
+
+/// Generates the creation bytecode of the contract of [`generate_verifier`] for `vk`.
+///
+/// The contract is assembled directly rather than compiled, with the same methods, the same
+/// checks and the same results. Failed checks revert without a reason string.
+pub fn generate_verifier_bytecode(vk: &VerifyingKey<Bn254>) -> anyhow::Result<Vec<u8>> {
+    check_vk(vk)?;
+
+    // Memory layout: the accumulated input commitment, the ecMul/ecAdd buffer, the output of
+    // the pairing check, the packed inputs (preceded by their ABI offset and length, to be
+    // returned by `publicInputs`) and the input of the pairing check.
+    const ACC: u64 = 0x00;
+    const BUF: u64 = 0x40;
+    const OUT: u64 = 0xc0;
+    const RESULT: u64 = 0x1c0;
+    const INPUTS: u64 = 0x200;
+    const PAIRING: u64 = INPUTS + 32 * NUM_INPUTS as u64;
+    // Calldata offsets of the arguments, after the selector.
+    const PROOF: u64 = 4;
+    const GID: u64 = PROOF + 8 * 32;
+    const CALLDATA_SIZE: u64 = GID + 32 * 32;
+
+    let q = word_expr(&Fq::MODULUS.to_bytes_be());
+    let r = word_expr(&Fr::MODULUS.to_bytes_be());
+    let [p_hi, p_lo] = split_words(&ark_bls12_381::Fq::MODULUS.to_bytes_be()).map(Expr::Word);
+    let input = |i: usize| mload(lit(INPUTS + 32 * i as u64));
+    let proof = |i: u64| calldataload(lit(PROOF + 32 * i));
+
+    let mut asm = Asm::default();
+    let revert = asm.label();
+    let verify_encryption = asm.label();
+    let public_inputs = asm.label();
+    let verify_proof = asm.label();
+    let verify = asm.label();
+
+    // Dispatch on the selector, all methods are non-payable.
+    asm.jump_if(revert, op(CALLVALUE, []));
+    for (signature, label) in [
+        (VERIFY_ENCRYPTION_SIGNATURE, verify_encryption),
+        (PUBLIC_INPUTS_SIGNATURE, public_inputs),
+        (VERIFY_PROOF_SIGNATURE, verify_proof),
+    ] {
+        let selector = word_expr(&Keccak256::digest(signature.as_bytes())[..4]);
+        asm.jump_if(label, op(EQ, [op(SHR, [lit(224), calldataload(lit(0))]), selector]));
+    }
+    asm.jump(revert);
+
+    // Writes the packed inputs of the `gid` and ciphertext arguments starting at `base`.
+    let pack_inputs = |asm: &mut Asm, base: u64| {
+        let elem = |j: usize| [0, 32].map(|w| calldataload(lit(base + 64 * j as u64 + w)));
+        for j in 0..NUM_PUBLIC_ELEMENTS {
+            let [hi, lo] = elem(j);
+            let valid = op(OR, [
+                op(LT, [hi.clone(), p_hi.clone()]),
+                op(AND, [op(EQ, [hi, p_hi.clone()]), op(LT, [lo, p_lo.clone()])]),
+            ]);
+            asm.jump_if(revert, op(ISZERO, [valid]));
+        }
+        for i in 0..NUM_INPUTS {
+            let value = input_segments(i).into_iter()
+                .map(|seg| {
+                    let [hi, lo] = elem(seg.elem);
+                    let bits = match seg.offset {
+                        0 => lo,
+                        offset if offset >= 256 => op(SHR, [lit(offset as u64 - 256), hi]),
+                        offset => op(OR, [op(SHR, [lit(offset as u64), lo]), op(SHL, [lit(256 - offset as u64), hi])]),
+                    };
+                    let mask = (BigUint::one() << seg.len) - BigUint::one();
+                    let bits = op(AND, [bits, word_expr(&mask.to_bytes_be())]);
+                    match seg.shift {
+                        0 => bits,
+                        shift => op(SHL, [lit(shift as u64), bits]),
+                    }
+                })
+                .reduce(|acc, bits| op(OR, [acc, bits]))
+                .unwrap();
+            asm.stmt(mstore(lit(INPUTS + 32 * i as u64), value));
+        }
+    };
+
+    asm.bind(verify_encryption);
+    asm.jump_if(revert, op(LT, [op(CALLDATASIZE, []), lit(CALLDATA_SIZE)]));
+    pack_inputs(&mut asm, GID);
+    asm.jump(verify);
+
+    asm.bind(public_inputs);
+    asm.jump_if(revert, op(LT, [op(CALLDATASIZE, []), lit(CALLDATA_SIZE - GID + PROOF)]));
+    pack_inputs(&mut asm, PROOF);
+    asm.stmt(mstore(lit(RESULT), lit(0x20)));
+    asm.stmt(mstore(lit(RESULT + 0x20), lit(NUM_INPUTS as u64)));
+    asm.stmt(op(RETURN, [lit(RESULT), lit(0x40 + 32 * NUM_INPUTS as u64)]));
+
+    // The inputs are a dynamic array, whose offset follows the proof.
+    asm.bind(verify_proof);
+    let offset = calldataload(lit(GID));
+    asm.jump_if(revert, op(GT, [offset.clone(), lit(u32::MAX as u64)]));
+    asm.jump_if(revert, op(LT, [op(CALLDATASIZE, []), op(ADD, [offset.clone(), lit(PROOF + 32 + 32 * NUM_INPUTS as u64)])]));
+    asm.jump_if(revert, op(ISZERO, [op(EQ, [calldataload(op(ADD, [offset.clone(), lit(PROOF)])), lit(NUM_INPUTS as u64)])]));
+    asm.stmt(op(CALLDATACOPY, [lit(INPUTS), op(ADD, [offset, lit(PROOF + 32)]), lit(32 * NUM_INPUTS as u64)]));
+    asm.jump(verify);
+
+    asm.bind(verify);
+    for i in 0..8 {
+        asm.jump_if(revert, op(ISZERO, [op(LT, [proof(i), q.clone()])]));
+    }
+    asm.stmt(mstore(lit(ACC), fq_expr(&vk.gamma_abc_g1[0].x)));
+    asm.stmt(mstore(lit(ACC + 0x20), fq_expr(&vk.gamma_abc_g1[0].y)));
+    for (i, p) in vk.gamma_abc_g1.iter().skip(1).enumerate() {
+        // acc += p * input[i]
+        asm.jump_if(revert, op(ISZERO, [op(LT, [input(i), r.clone()])]));
+        asm.stmt(mstore(lit(BUF), fq_expr(&p.x)));
+        asm.stmt(mstore(lit(BUF + 0x20), fq_expr(&p.y)));
+        asm.stmt(mstore(lit(BUF + 0x40), input(i)));
+        asm.jump_if(revert, op(ISZERO, [staticcall(7, BUF, 0x60, BUF, 0x40)]));
+        asm.stmt(mstore(lit(BUF + 0x40), mload(lit(ACC))));
+        asm.stmt(mstore(lit(BUF + 0x60), mload(lit(ACC + 0x20))));
+        asm.jump_if(revert, op(ISZERO, [staticcall(6, BUF, 0x80, ACC, 0x40)]));
+    }
+
+    // e(-A, B) * e(alpha, beta) * e(acc, gamma) * e(C, delta) == 1
+    let g2 = |p: &G2Affine| [p.x.c1, p.x.c0, p.y.c1, p.y.c0].map(|fe| fq_expr(&fe));
+    let pairing = [proof(0), op(MOD, [op(SUB, [q.clone(), proof(1)]), q.clone()])].into_iter()
+        .chain((2..6).map(proof))
+        .chain([fq_expr(&vk.alpha_g1.x), fq_expr(&vk.alpha_g1.y)])
+        .chain(g2(&vk.beta_g2))
+        .chain([mload(lit(ACC)), mload(lit(ACC + 0x20))])
+        .chain(g2(&vk.gamma_g2))
+        .chain([proof(6), proof(7)])
+        .chain(g2(&vk.delta_g2));
+    for (i, value) in pairing.enumerate() {
+        asm.stmt(mstore(lit(PAIRING + 32 * i as u64), value));
+    }
+    asm.stmt(mstore(lit(OUT + 0x20), staticcall(8, PAIRING, 0x300, OUT, 0x20)));
+    asm.stmt(mstore(lit(0), op(AND, [mload(lit(OUT + 0x20)), op(EQ, [mload(lit(OUT)), lit(1)])])));
+    asm.stmt(op(RETURN, [lit(0), lit(0x20)]));
+
+    asm.bind(revert);
+    asm.stmt(op(REVERT, [lit(0), lit(0)]));
+
+    Ok(asm.deployment())
+}
+
+/// Version of `solc` used by [`compile_verifier`].
+#[cfg(feature = "solc")]
+pub const SOLC_VERSION: &str = "0.8.26";
+
+/// Compiles the contract of [`generate_verifier`] for `vk` into its creation bytecode.
+///
+/// Uses `solc` [`SOLC_VERSION`] from the svm directory, installing it first if it's missing.
+#[cfg(feature = "solc")]
+pub fn compile_verifier(vk: &VerifyingKey<Bn254>) -> anyhow::Result<Vec<u8>> {
+    use std::io::Write as _;
+    use std::process::{Command, Stdio};
+
+    let src = generate_verifier(vk)?;
+    let mut solc = svm::version_binary(SOLC_VERSION);
+    if !solc.exists() {
+        solc = svm::blocking_install(&semver::Version::parse(SOLC_VERSION)?)
+            .map_err(|e| anyhow!("error installing solc {SOLC_VERSION}: {e}"))?;
+    }
+
+    let mut child = Command::new(&solc)
+        .args(["--optimize", "--bin", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("error running {}: {e}", solc.display()))?;
+    child.stdin.take().unwrap().write_all(src.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!("error compiling verifier: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    // The output is a `======= <stdin>:TimelockVerifier =======` header, then `Binary:` and the hex code.
+    let stdout = String::from_utf8(output.stdout)?;
+    let bin = stdout.lines()
+        .skip_while(|line| line.trim() != "Binary:")
+        .nth(1)
+        .ok_or_else(|| anyhow!("no bytecode in solc output"))?;
+    hex::decode(bin.trim()).map_err(|e| anyhow!("error decoding bytecode: {e}"))
+}
+
+/// Encodes a proof as the `uint256[8]` expected by the contract: `A`, then `B` with
+/// the imaginary parts first as required by the `ecPairing` precompile, then `C`.
+pub fn encode_proof(proof: &Proof<Bn254>) -> [[u8; 32]; 8] {
+    let (a, b, c) = (proof.a, proof.b, proof.c);
+    [
+        a.x, a.y,
+        b.x.c1, b.x.c0, b.y.c1, b.y.c0,
+        c.x, c.y,
+    ].map(|fe| to_word(&fe.into_bigint().to_bytes_be()))
+}
+
+/// Encodes a BLS12-381 base field element as `[hi, lo]` words of its big-endian encoding.
+pub fn encode_fq(fe: &ark_bls12_381::Fq) -> [[u8; 32]; 2] {
+    split_words(&fe.into_bigint().to_bytes_be())
+}
+
+/// Encodes the calldata of a `verifyEncryption(proof, gid, ct)` call.
+pub fn encode_verify_encryption_calldata(
+    proof: &Proof<Bn254>,
+    gid: &ark_bls12_381::Fq12,
+    ct: &Ciphertext<ark_bls12_381::G1Projective>,
+) -> Vec<u8> {
+    let u = ct.u.into_affine();
+    let elems = fp12_coefficients(gid).into_iter().chain([u.x, u.y, ct.v, ct.w]);
+
+    let mut calldata = Keccak256::digest(VERIFY_ENCRYPTION_SIGNATURE.as_bytes())[..4].to_vec();
+    calldata.extend(encode_proof(proof).concat());
+    calldata.extend(elems.flat_map(|fe| encode_fq(&fe)).flatten());
+    calldata
+}
+
+fn write_g1_constant(src: &mut String, name: &str, p: &G1Affine) -> std::fmt::Result {
+    writeln!(src, "    uint256 constant {name}_X = {};", fq_word(&p.x))?;
+    writeln!(src, "    uint256 constant {name}_Y = {};", fq_word(&p.y))
+}
+
+fn write_g2_constant(src: &mut String, name: &str, p: &G2Affine) -> std::fmt::Result {
+    writeln!(src, "    uint256 constant {name}_X1 = {};", fq_word(&p.x.c1))?;
+    writeln!(src, "    uint256 constant {name}_X0 = {};", fq_word(&p.x.c0))?;
+    writeln!(src, "    uint256 constant {name}_Y1 = {};", fq_word(&p.y.c1))?;
+    writeln!(src, "    uint256 constant {name}_Y0 = {};", fq_word(&p.y.c0))
+}
+
+fn fq_word(fe: &Fq) -> String {
+    word(&fe.into_bigint().to_bytes_be())
+}
+
+fn split_words(be: &[u8]) -> [[u8; 32]; 2] {
+    let mut bytes = [0u8; 64];
+    bytes[64 - be.len()..].copy_from_slice(be);
+
+    let (hi, lo) = bytes.split_at(32);
+    [to_word(hi), to_word(lo)]
+}
+
+fn to_word(be: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[32 - be.len()..].copy_from_slice(be);
+    word
+}
+
+fn word(be: &[u8]) -> String {
+    format!("0x{}", hex::encode(to_word(be)))
+}
+
+// Opcodes used by `generate_verifier_bytecode`.
+const ADD: u8 = 0x01;
+const SUB: u8 = 0x03;
+const MOD: u8 = 0x06;
+const LT: u8 = 0x10;
+const GT: u8 = 0x11;
+const EQ: u8 = 0x14;
+const ISZERO: u8 = 0x15;
+const AND: u8 = 0x16;
+const OR: u8 = 0x17;
+const SHL: u8 = 0x1b;
+const SHR: u8 = 0x1c;
+const CALLVALUE: u8 = 0x34;
+const CALLDATALOAD: u8 = 0x35;
+const CALLDATASIZE: u8 = 0x36;
+const CALLDATACOPY: u8 = 0x37;
+const CODECOPY: u8 = 0x39;
+const MLOAD: u8 = 0x51;
+const MSTORE: u8 = 0x52;
+const JUMP: u8 = 0x56;
+const JUMPI: u8 = 0x57;
+const GAS: u8 = 0x5a;
+const JUMPDEST: u8 = 0x5b;
+const PUSH1: u8 = 0x60;
+const PUSH2: u8 = 0x61;
+const DUP1: u8 = 0x80;
+const RETURN: u8 = 0xf3;
+const STATICCALL: u8 = 0xfa;
+const REVERT: u8 = 0xfd;
+
+/// Expression evaluated on the EVM stack, with the arguments of an opcode in the same order as
+/// in Yul, e.g. `op(SUB, [a, b])` is `a - b`.
+#[derive(Clone)]
+enum Expr {
+    Word([u8; 32]),
+    Label(usize),
+    Op(u8, Vec<Expr>),
+}
+
+fn op<const N: usize>(code: u8, args: [Expr; N]) -> Expr {
+    Expr::Op(code, args.into())
+}
+
+fn lit(n: u64) -> Expr {
+    word_expr(&n.to_be_bytes())
+}
+
+fn word_expr(be: &[u8]) -> Expr {
+    Expr::Word(to_word(be))
+}
+
+fn fq_expr(fe: &Fq) -> Expr {
+    word_expr(&fe.into_bigint().to_bytes_be())
+}
+
+fn calldataload(offset: Expr) -> Expr {
+    op(CALLDATALOAD, [offset])
+}
+
+fn mload(offset: Expr) -> Expr {
+    op(MLOAD, [offset])
+}
+
+fn mstore(offset: Expr, value: Expr) -> Expr {
+    op(MSTORE, [offset, value])
+}
+
+/// Calls the precompile at `address`, returning whether it succeeded.
+fn staticcall(address: u64, input: u64, input_size: u64, output: u64, output_size: u64) -> Expr {
+    op(STATICCALL, [op(GAS, []), lit(address), lit(input), lit(input_size), lit(output), lit(output_size)])
+}
+
+/// Assembler of EVM bytecode with forward jumps to labels.
+#[derive(Default)]
+struct Asm {
+    code: Vec<u8>,
+    labels: Vec<Option<usize>>,
+    // Positions of the 2-byte jump destinations to patch, with their label.
+    jumps: Vec<(usize, usize)>,
+}
+
+impl Asm {
+    fn label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    fn bind(&mut self, label: usize) {
+        self.labels[label] = Some(self.code.len());
+        self.code.push(JUMPDEST);
+    }
+
+    /// Emits the code of `expr`, e.g. a statement that leaves nothing on the stack.
+    fn stmt(&mut self, expr: Expr) {
+        self.expr(&expr);
+    }
+
+    fn jump(&mut self, label: usize) {
+        self.stmt(op(JUMP, [Expr::Label(label)]));
+    }
+
+    fn jump_if(&mut self, label: usize, cond: Expr) {
+        self.stmt(op(JUMPI, [Expr::Label(label), cond]));
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Word(word) => {
+                let be = match word.iter().position(|b| *b != 0) {
+                    Some(start) => &word[start..],
+                    None => &word[31..],
+                };
+                self.code.push(PUSH1 + be.len() as u8 - 1);
+                self.code.extend_from_slice(be);
+            }
+            Expr::Label(label) => {
+                self.code.push(PUSH2);
+                self.jumps.push((self.code.len(), *label));
+                self.code.extend_from_slice(&[0, 0]);
+            }
+            Expr::Op(code, args) => {
+                for arg in args.iter().rev() {
+                    self.expr(arg);
+                }
+                self.code.push(*code);
+            }
+        }
+    }
+
+    /// Returns the creation code deploying the assembled code.
+    fn deployment(mut self) -> Vec<u8> {
+        for (pos, label) in self.jumps {
+            let dest = self.labels[label].expect("unbound label") as u16;
+            self.code[pos..pos + 2].copy_from_slice(&dest.to_be_bytes());
+        }
+
+        // codecopy(0, 12, len); return(0, len), where 12 is the length of these instructions.
+        let len = (self.code.len() as u16).to_be_bytes();
+        let mut creation = vec![PUSH2, len[0], len[1], DUP1, PUSH1, 12, PUSH1, 0, CODECOPY, PUSH1, 0, RETURN];
+        creation.extend(self.code);
+        creation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::Group;
+    use ark_groth16::Groth16;
+    use ark_r1cs_std::fields::fp::FpVar;
+    use ark_r1cs_std::prelude::*;
+    use ark_relations::ns;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+    use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+    use ark_std::{test_rng, UniformRand};
+    use revm::{Evm, InMemoryDB};
+    use revm::primitives::{ExecutionResult, Output, TxKind};
+    use crate::bn254::{public_inputs, Bn254Circuit};
+    use super::*;
+
+    // Exposes the given values as public inputs, standing in for the (much larger)
+    // timelock circuit, with which it shares the input layout.
+    struct InputsCircuit(Vec<Fr>);
+
+    impl ConstraintSynthesizer<Fr> for InputsCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            for value in self.0 {
+                let input = FpVar::new_input(ns!(cs, "input"), || Ok(value))?;
+                let witness = FpVar::new_witness(ns!(cs, "witness"), || Ok(value))?;
+                input.enforce_equal(&witness)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn new_circuit() -> Bn254Circuit {
+        let mut rng = test_rng();
+        let master = (ark_bls12_381::G1Projective::generator() * ark_bls12_381::Fr::rand(&mut rng)).into_affine();
+        let msg = ark_bls12_381::Fq::rand(&mut rng);
+        Bn254Circuit::new(master, b"round-1000", msg, &mut rng).unwrap()
+    }
+
+    fn call(evm: &mut Evm<'_, (), InMemoryDB>, to: TxKind, data: Vec<u8>) -> ExecutionResult {
+        evm.tx_mut().transact_to = to;
+        evm.tx_mut().data = data.into();
+        evm.tx_mut().gas_limit = 30_000_000;
+        evm.transact_commit().unwrap()
+    }
+
+    fn transact(evm: &mut Evm<'_, (), InMemoryDB>, to: TxKind, data: Vec<u8>) -> Output {
+        match call(evm, to, data) {
+            ExecutionResult::Success { output, .. } => output,
+            result => panic!("transaction failed: {result:?}"),
+        }
+    }
+
+    /// Deploys the creation `bytecode` of a verifier on an in-process EVM and checks its methods
+    /// against a valid `proof` for `gid` and `ct`.
+    fn check_verifier_on_evm(
+        bytecode: Vec<u8>,
+        proof: &Proof<Bn254>,
+        gid: &ark_bls12_381::Fq12,
+        ct: &Ciphertext<ark_bls12_381::G1Projective>,
+    ) {
+        let inputs = public_inputs(gid, ct);
+        let mut evm = Evm::builder().with_db(InMemoryDB::default()).build();
+        let address = match transact(&mut evm, TxKind::Create, bytecode) {
+            Output::Create(_, Some(address)) => address,
+            output => panic!("unexpected output: {output:?}"),
+        };
+
+        // `publicInputs` takes the same arguments as `verifyEncryption`, minus the proof.
+        let calldata = encode_verify_encryption_calldata(proof, gid, ct);
+        let mut packing = Keccak256::digest(PUBLIC_INPUTS_SIGNATURE.as_bytes())[..4].to_vec();
+        packing.extend(&calldata[4 + 8 * 32..]);
+        let out = transact(&mut evm, TxKind::Call(address), packing).into_data();
+        let packed = out[64..].chunks(32).map(Fr::from_be_bytes_mod_order).collect::<Vec<_>>();
+        assert_eq!(packed, inputs);
+
+        let out = transact(&mut evm, TxKind::Call(address), calldata.clone()).into_data();
+        assert_eq!(out[31], 1);
+
+        // `verifyProof` takes the proof and the packed inputs, as a dynamic array.
+        let mut unpacked = Keccak256::digest(VERIFY_PROOF_SIGNATURE.as_bytes())[..4].to_vec();
+        unpacked.extend(&calldata[4..4 + 8 * 32]);
+        unpacked.extend(to_word(&(9 * 32u64).to_be_bytes()));
+        unpacked.extend(to_word(&(inputs.len() as u64).to_be_bytes()));
+        unpacked.extend(inputs.iter().flat_map(|x| to_word(&x.into_bigint().to_bytes_be())));
+        let out = transact(&mut evm, TxKind::Call(address), unpacked).into_data();
+        assert_eq!(out[31], 1);
+
+        let mut tampered = calldata.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        let out = transact(&mut evm, TxKind::Call(address), tampered).into_data();
+        assert_eq!(out[31], 0);
+
+        // Elements of `gid` and the ciphertext must be reduced.
+        let mut unreduced = calldata;
+        unreduced[4 + 8 * 32..4 + 10 * 32].copy_from_slice(&split_words(&ark_bls12_381::Fq::MODULUS.to_bytes_be()).concat());
+        let result = call(&mut evm, TxKind::Call(address), unreduced);
+        assert!(matches!(result, ExecutionResult::Revert { .. }), "unexpected result: {result:?}");
+    }
+
+    #[test]
+    fn test_generate_verifier_rejects_foreign_vk() {
+        let mut rng = test_rng();
+        let circuit = InputsCircuit(vec![Fr::rand(&mut rng)]);
+        let (_, vk) = Groth16::<Bn254>::setup(circuit, &mut rng).unwrap();
+
+        assert!(generate_verifier(&vk).is_err());
+    }
+
+    /// Returns a proof with a key of `InputsCircuit`, whose public inputs are those of the
+    /// timelock circuit, as the contract only depends on their layout.
+    fn prove_inputs_circuit() -> (VerifyingKey<Bn254>, Proof<Bn254>, ark_bls12_381::Fq12, Ciphertext<ark_bls12_381::G1Projective>) {
+        let mut rng = test_rng();
+        let circuit = new_circuit();
+        let (gid, ct) = (circuit.gid.unwrap(), circuit.ciphertext.unwrap());
+        let inputs = public_inputs(&gid, &ct);
+
+        let (pk, vk) = Groth16::<Bn254>::setup(InputsCircuit(inputs.clone()), &mut rng).unwrap();
+        let proof = Groth16::<Bn254>::prove(&pk, InputsCircuit(inputs), &mut rng).unwrap();
+        (vk, proof, gid, ct)
+    }
+
+    #[test]
+    fn test_verifier_on_evm() {
+        let (vk, proof, gid, ct) = prove_inputs_circuit();
+        check_verifier_on_evm(generate_verifier_bytecode(&vk).unwrap(), &proof, &gid, &ct);
+    }
+
+    #[test]
+    #[cfg(feature = "solc")]
+    fn test_compiled_verifier_on_evm() {
+        let (vk, proof, gid, ct) = prove_inputs_circuit();
+        check_verifier_on_evm(compile_verifier(&vk).unwrap(), &proof, &gid, &ct);
+    }
+
+    // Runs the setup and proof of the non-native timelock circuit, which take minutes.
+    #[test]
+    #[ignore]
+    fn test_timelock_verifier_on_evm() {
+        let mut rng = test_rng();
+        let circuit = new_circuit();
+        let (gid, ct) = (circuit.gid.unwrap(), circuit.ciphertext.clone().unwrap());
+
+        let (pk, vk) = crate::bn254::setup(&mut rng).unwrap();
+        let proof = crate::bn254::prove(&pk, circuit, &mut rng).unwrap();
+        check_verifier_on_evm(generate_verifier_bytecode(&vk).unwrap(), &proof, &gid, &ct);
+    }
+}
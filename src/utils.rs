@@ -1,16 +1,22 @@
 use anyhow::anyhow;
 use ark_bls12_377::Bls12_377;
 use ark_bls12_381::Bls12_381;
-use ark_ec::{bls12, CurveGroup, AffineRepr};
+use ark_ec::{bls12, CurveGroup, AffineRepr, Group};
 use ark_ec::bls12::Bls12Parameters;
 use ark_ec::pairing::Pairing;
-use ark_ec::short_weierstrass::Affine;
-use ark_ff::{BigInteger, BigInteger256, BigInteger384, Field, PrimeField, QuadExtField, Zero};
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ff::{BigInteger, BigInteger256, BigInteger384, Field, Fp12, PrimeField, QuadExtField, Zero};
 use ark_r1cs_std::fields::fp12::Fp12Var;
 use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::groups::curves::short_weierstrass::ProjectiveVar;
+use ark_r1cs_std::groups::CurveVar;
+use ark_r1cs_std::prelude::Boolean;
+use ark_r1cs_std::select::TwoBitLookupGadget;
+use ark_relations::r1cs::SynthesisError;
 use ark_serialize::{CanonicalDeserialize, SerializationError};
 use group::Curve;
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 
 pub trait Hash2Curve: Pairing {
     fn hash(msg: &[u8], dst: &[u8]) -> anyhow::Result<Self::G2Affine>;
@@ -30,6 +36,12 @@ impl Hash2Curve for Bls12_377 {
     }
 }
 
+/// Returns the identity that drand signs in round `round`, i.e. `sha256(round)`, to be hashed to
+/// G2 with [`Hash2Curve`] and [`H2C_DST`](crate::H2C_DST).
+pub fn round_identity(round: u64) -> [u8; 32] {
+    Sha256::digest(&round.to_be_bytes()).into()
+}
+
 pub trait ZkCryptoDeserialize: Sized {
     fn deserialize_zk_crypto(bytes: &[u8]) -> anyhow::Result<Self>;
     fn deserialize_zk_crypto_uncompressed(bytes: &[u8]) -> anyhow::Result<Self>;
@@ -148,6 +160,111 @@ pub fn gt_scalar_mul_le<T: Field + Zero, B: AsRef<[u8]>>(trg: T, rhs: B) -> T {
     res
 }
 
+/// Raises `base` to the power of the little-endian `bits`. `base` must lie in the cyclotomic
+/// subgroup, which is the case of pairing outputs such as `Gid`.
+///
+/// The exponent is processed from its most significant 2-bit window: each window costs two
+/// cyclotomic squarings, which are much cheaper than generic `Fp12` squarings, and a single
+/// multiplication by one of the precomputed `base^0..base^3`.
+pub fn gt_pow_le<P: Bls12Parameters>(
+    base: &Fp12Var<P::Fp12Config>,
+    bits: &[Boolean<P::Fp>],
+) -> Result<Fp12Var<P::Fp12Config>, SynthesisError> {
+    let square = base.cyclotomic_square()?;
+    let table = [Fp12Var::<P::Fp12Config>::one(), base.clone(), square.clone(), square * base];
+
+    let mut res = Fp12Var::<P::Fp12Config>::one();
+    for (i, window) in bits.chunks(2).rev().enumerate() {
+        if i > 0 {
+            res = res.cyclotomic_square()?.cyclotomic_square()?;
+        }
+        let hi = window.get(1).cloned().unwrap_or(Boolean::FALSE);
+        let power = hi.select(
+            &window[0].select(&table[3], &table[2])?,
+            &window[0].select(&table[1], &table[0])?,
+        )?;
+        res *= power;
+    }
+    Ok(res)
+}
+
+/// Precomputes the tables of `base` used by [`gt_fixed_base_pow_le`] for exponents of up to
+/// `num_bits` bits: the `i`-th table holds `base^(j * 4^i)` for `j` in `0..4`.
+pub fn gt_fixed_base_tables<T: Field>(base: T, num_bits: usize) -> Vec<[T; 4]> {
+    let mut tables = Vec::with_capacity((num_bits + 1) / 2);
+    let mut power = base;
+    for _ in 0..(num_bits + 1) / 2 {
+        let square = power.square();
+        tables.push([T::one(), power, square, square * power]);
+        power = square.square();
+    }
+    tables
+}
+
+/// Raises a constant to the power of the little-endian `bits`, given its [`gt_fixed_base_tables`].
+///
+/// Each 2-bit window selects its power with a single two-bit lookup per base field element, so
+/// that the exponentiation costs one `Fp12` multiplication per window instead of a squaring and
+/// a multiplication per bit.
+pub fn gt_fixed_base_pow_le<P: Bls12Parameters>(
+    tables: &[[Fp12<P::Fp12Config>; 4]],
+    bits: &[Boolean<P::Fp>],
+) -> Result<Fp12Var<P::Fp12Config>, SynthesisError> {
+    assert!(bits.len() <= 2 * tables.len(), "exponent has more bits than the precomputed tables");
+
+    let mut res = Fp12Var::<P::Fp12Config>::one();
+    for (window, table) in bits.chunks(2).zip(tables) {
+        let window = [window[0].clone(), window.get(1).cloned().unwrap_or(Boolean::FALSE)];
+        res *= Fp12Var::<P::Fp12Config>::two_bit_lookup(&window, table)?;
+    }
+    Ok(res)
+}
+
+/// Multiplies the constant `base` by the little-endian `bits` with 3-bit windows.
+///
+/// The multiples of `base` for each window are precomputed, and their coordinates selected with
+/// two two-bit lookups and a selection, so that the multiplication costs a single addition per
+/// window instead of an addition and a selection of points per bit.
+pub fn fixed_base_mul_le<C: SWCurveConfig>(
+    base: Projective<C>,
+    bits: &[Boolean<C::BaseField>],
+) -> Result<ProjectiveVar<C, FpVar<C::BaseField>>, SynthesisError>
+    where C::BaseField: PrimeField
+{
+    let mut res: Option<ProjectiveVar<C, FpVar<C::BaseField>>> = None;
+    let mut power = base;
+    let mut offset = Projective::<C>::zero();
+    for window in bits.chunks(3) {
+        // Entries are the `j + 1` rather than `j` multiples of the window's power, so that none
+        // of them is the point at infinity. The excess is subtracted once at the end.
+        let mut table = vec![power; 8];
+        for j in 1..8 {
+            table[j] = table[j - 1] + power;
+        }
+        let table = Projective::normalize_batch(&table);
+
+        let bit = |i: usize| window.get(i).cloned().unwrap_or(Boolean::FALSE);
+        let lookup = |coords: Vec<C::BaseField>| -> Result<FpVar<C::BaseField>, SynthesisError> {
+            let lo = FpVar::two_bit_lookup(&[bit(0), bit(1)], &coords[..4])?;
+            let hi = FpVar::two_bit_lookup(&[bit(0), bit(1)], &coords[4..])?;
+            bit(2).select(&hi, &lo)
+        };
+        let x = lookup(table.iter().map(|p| p.x).collect())?;
+        let y = lookup(table.iter().map(|p| p.y).collect())?;
+
+        let point = ProjectiveVar::new(x, y, FpVar::one());
+        res = Some(match res {
+            Some(res) => res + point,
+            None => point,
+        });
+        offset += power;
+        power = power.double().double().double();
+    }
+
+    let res = res.unwrap_or_else(ProjectiveVar::zero);
+    Ok(res - offset)
+}
+
 pub fn curve_scalar_mul_le<T: CurveGroup + Zero, B: AsRef<[u8]>>(trg: T, rhs: B) -> T {
     let mut res = T::zero();
     let mut mul = trg;
@@ -228,9 +345,100 @@ pub fn gtvar_to_fqvars<E: Pairing, P: Bls12Parameters<Fp = <E::G1 as CurveGroup>
 
 #[cfg(test)]
 mod tests {
-    use ark_std::test_rng;
+    use ark_r1cs_std::prelude::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::Rng;
+    use ark_std::{test_rng, UniformRand};
     use super::*;
 
+    // Square-and-multiply that `Circuit` used before `gt_pow_le`, kept as a baseline.
+    fn gt_pow_le_baseline<P: Bls12Parameters>(
+        base: &Fp12Var<P::Fp12Config>,
+        bits: &[Boolean<P::Fp>],
+    ) -> Result<Fp12Var<P::Fp12Config>, SynthesisError> {
+        let mut res = Fp12Var::<P::Fp12Config>::one();
+        let mut mul = base.clone();
+        for bit in bits {
+            let tmp = res.clone() * &mul;
+            res = bit.select(&tmp, &res)?;
+            mul.square_in_place()?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the number of constraints of the baseline and of `gt_pow_le` for a 256-bit exponent.
+    fn gt_pow_num_constraints<P: Bls12Parameters>() -> (usize, usize) {
+        let mut rng = test_rng();
+        let base = bls12::Bls12::<P>::pairing(
+            <bls12::Bls12<P> as Pairing>::G1::rand(&mut rng),
+            <bls12::Bls12<P> as Pairing>::G2::rand(&mut rng),
+        ).0;
+        let exp: [u8; 32] = rng.gen();
+        let expected = gt_scalar_mul_le(base, exp);
+
+        let num_constraints = |pow: fn(&Fp12Var<P::Fp12Config>, &[Boolean<P::Fp>]) -> Result<Fp12Var<P::Fp12Config>, SynthesisError>| {
+            let cs = ConstraintSystem::<P::Fp>::new_ref();
+            let base = Fp12Var::<P::Fp12Config>::new_witness(cs.clone(), || Ok(base)).unwrap();
+            let bits = exp.iter()
+                .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+                .map(|bit| Boolean::new_witness(cs.clone(), || Ok(bit)).unwrap())
+                .collect::<Vec<_>>();
+            let before = cs.num_constraints();
+
+            let res = pow(&base, &bits).unwrap();
+            assert_eq!(res.value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap());
+            cs.num_constraints() - before
+        };
+
+        (num_constraints(gt_pow_le_baseline::<P>), num_constraints(gt_pow_le::<P>))
+    }
+
+    #[test]
+    fn test_gt_pow() {
+        // The counts are recorded in BENCHMARKS.md.
+        let (before, after) = gt_pow_num_constraints::<ark_bls12_377::Parameters>();
+        assert!(after < before);
+
+        let (before, after) = gt_pow_num_constraints::<ark_bls12_381::Parameters>();
+        assert!(after < before);
+    }
+
+    #[test]
+    fn test_fixed_base_mul() {
+        let mut rng = test_rng();
+        let base = ark_bls12_381::G1Projective::rand(&mut rng);
+        let exp: [u8; 32] = rng.gen();
+
+        let cs = ConstraintSystem::<ark_bls12_381::Fq>::new_ref();
+        let bits = exp.iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .map(|bit| Boolean::new_witness(cs.clone(), || Ok(bit)).unwrap())
+            .collect::<Vec<_>>();
+        let res = fixed_base_mul_le(base, &bits).unwrap();
+
+        assert_eq!(res.value().unwrap(), curve_scalar_mul_le(base, exp));
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_gt_fixed_base_pow() {
+        let mut rng = test_rng();
+        let base = ark_bls12_381::Fq12::rand(&mut rng);
+        let exp: [u8; 32] = rng.gen();
+
+        let cs = ConstraintSystem::<ark_bls12_381::Fq>::new_ref();
+        let bits = exp.iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .map(|bit| Boolean::new_witness(cs.clone(), || Ok(bit)).unwrap())
+            .collect::<Vec<_>>();
+        let tables = gt_fixed_base_tables(base, bits.len());
+        let res = gt_fixed_base_pow_le::<ark_bls12_381::Parameters>(&tables, &bits).unwrap();
+
+        assert_eq!(res.value().unwrap(), gt_scalar_mul_le(base, exp));
+        assert!(cs.is_satisfied().unwrap());
+    }
+
     #[test]
     fn test_decode() {
         let pk = {